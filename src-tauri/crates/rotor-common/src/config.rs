@@ -51,6 +51,8 @@ static DEFAULT_CONFIG: LazyLock<Config> = LazyLock::new(|| {
         ("if_auto_change_save_path".into(), "true".into()),
         ("if_ask_save_path".into(), "true".into()),
         ("zoom_delta".into(), "2".into()),
+        ("screenshot_history".into(), "false".into()),
+        ("screenshot_history_limit".into(), "500".into()),
         ("current_workspace".into(), "0".into()),
         ("shortcut_search".into(), DEFAULT_SEARCH_SHORTCUT.into()),
         (
@@ -69,15 +71,49 @@ static DEFAULT_CONFIG: LazyLock<Config> = LazyLock::new(|| {
             "shortcut_translate_input".into(),
             DEFAULT_TRANSLATE_INPUT_SHORTCUT.into(),
         ),
+        // Opt-in, as it pastes over the selection in whatever app has focus.
+        ("shortcut_translate_replace".into(), "".into()),
         ("translator_engine".into(), "google".into()),
         ("translator_deepseek_api_key".into(), "".into()),
         (
             "translator_deepseek_model".into(),
             DEFAULT_TRANSLATOR_DEEPSEEK_MODEL.into(),
         ),
+        (
+            "translator_openai_base_url".into(),
+            "https://api.openai.com/v1".into(),
+        ),
+        ("translator_openai_api_key".into(), "".into()),
+        ("translator_openai_model".into(), "".into()),
+        (
+            "translator_ollama_url".into(),
+            "http://127.0.0.1:11434".into(),
+        ),
+        ("translator_ollama_model".into(), "".into()),
         ("translator_custom_url".into(), "".into()),
         ("translator_custom_key".into(), "".into()),
+        ("translator_custom_method".into(), "GET".into()),
+        ("translator_custom_headers".into(), "".into()),
+        ("translator_custom_body".into(), "".into()),
+        ("translator_custom_result".into(), "".into()),
+        ("translator_offline_model_dir".into(), "".into()),
         ("translator_target_lang".into(), "auto".into()),
+        ("translator_my_languages".into(), "zh-CN,en".into()),
+        ("translator_fallback_engines".into(), "".into()),
+        ("translator_retries".into(), "2".into()),
+        ("network_proxy".into(), "".into()),
+        ("network_no_proxy".into(), "".into()),
+        ("network_ca_certificates".into(), "".into()),
+        ("translator_glossary".into(), "[]".into()),
+        // Empty stands for the built-in presets until they are edited.
+        ("translator_prompts".into(), "".into()),
+        ("translator_prompt".into(), "default".into()),
+        ("translator_history".into(), "true".into()),
+        ("translator_dictionary".into(), "true".into()),
+        ("translator_history_limit".into(), "5000".into()),
+        ("translator_cache".into(), "true".into()),
+        ("translator_cache_limit".into(), "1000".into()),
+        ("translator_cache_ttl_hours".into(), "168".into()),
         ("quick_actions".into(), DEFAULT_QUICK_ACTIONS.into()),
         (
             "quick_actions_revision".into(),
@@ -87,6 +123,8 @@ static DEFAULT_CONFIG: LazyLock<Config> = LazyLock::new(|| {
             "search_excluded_dirs".into(),
             DEFAULT_SEARCH_EXCLUDED_DIRS.into(),
         ),
+        ("search_mount_points".into(), "".into()),
+        ("search_fuzzy_match".into(), "false".into()),
     ])
 });
 
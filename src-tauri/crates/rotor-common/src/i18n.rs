@@ -17,6 +17,10 @@ impl I18n {
         zh_cn.insert("quit".to_string(), "退出".to_string());
         zh_cn.insert("settingWindowTitle".to_string(), "设置".to_string());
         zh_cn.insert("pinWindowName".to_string(), "小云视窗".to_string());
+        zh_cn.insert(
+            "translateReplaceFailed".to_string(),
+            "翻译替换失败".to_string(),
+        );
         translations.insert("zh-CN".to_string(), zh_cn);
 
         let mut en_us = HashMap::new();
@@ -25,6 +29,10 @@ impl I18n {
         en_us.insert("quit".to_string(), "Quit".to_string());
         en_us.insert("settingWindowTitle".to_string(), "Settings".to_string());
         en_us.insert("pinWindowName".to_string(), "Rotor Pin Window".to_string());
+        en_us.insert(
+            "translateReplaceFailed".to_string(),
+            "Replace with Translation failed".to_string(),
+        );
         translations.insert("en-US".to_string(), en_us);
 
         I18n { translations }
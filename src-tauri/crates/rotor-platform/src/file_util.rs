@@ -83,6 +83,50 @@ pub fn get_app_trans_names(app_path: &Path) -> Result<HashMap<String, String>, B
     Ok(translations)
 }
 
+// Reads the `Name` and localized `Name[lang]` keys of a freedesktop `.desktop` entry.
+pub fn get_desktop_entry_names(
+    entry_path: &Path,
+) -> Result<HashMap<String, String>, Box<dyn Error>> {
+    let content = fs::read_to_string(entry_path)?;
+    Ok(parse_desktop_entry_names(&content))
+}
+
+fn parse_desktop_entry_names(content: &str) -> HashMap<String, String> {
+    let mut names = HashMap::new();
+    let mut in_desktop_entry = false;
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_desktop_entry = line == "[Desktop Entry]";
+            continue;
+        }
+        if !in_desktop_entry {
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim();
+        let value = value.trim();
+        if value.is_empty() {
+            continue;
+        }
+
+        if key == "Name" {
+            names.insert("default".to_string(), value.to_string());
+        } else if let Some(lang) = key
+            .strip_prefix("Name[")
+            .and_then(|rest| rest.strip_suffix(']'))
+        {
+            names.insert(lang.to_string(), value.to_string());
+        }
+    }
+
+    names
+}
+
 pub fn open_file(file_path: String) -> Result<(), Box<dyn Error>> {
     let path = Path::new(&file_path);
     if !path.exists() {
@@ -103,6 +147,20 @@ pub fn open_file(file_path: String) -> Result<(), Box<dyn Error>> {
         std::process::Command::new("open").arg(&file_path).spawn()?;
     }
 
+    #[cfg(target_os = "linux")]
+    {
+        // xdg-open would show a desktop entry in an editor, so launch it via gio instead.
+        if path.extension().is_some_and(|ext| ext == "desktop") {
+            std::process::Command::new("gio")
+                .args(["launch", &file_path])
+                .spawn()?;
+        } else {
+            std::process::Command::new("xdg-open")
+                .arg(&file_path)
+                .spawn()?;
+        }
+    }
+
     Ok(())
 }
 
@@ -124,10 +182,17 @@ pub fn open_file_as_admin(file_path: String) -> Result<(), Box<dyn Error>> {
         Ok(())
     }
 
+    // Elsewhere the file is opened normally, so callers still see a launch.
     #[cfg(target_os = "macos")]
     {
-        open_file(file_path)?;
-        Err("MacOS does not support, use normal open instead".into())
+        log::warn!("MacOS does not support opening as administrator, use normal open instead");
+        open_file(file_path)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        log::warn!("Linux does not support opening as administrator, use normal open instead");
+        open_file(file_path)
     }
 }
 
@@ -164,3 +229,26 @@ pub fn get_file_icon_data(file_path: &str) -> Option<String> {
         Err(_) => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::parse_desktop_entry_names;
+
+    #[test]
+    fn parses_localized_desktop_entry_names() {
+        let names = parse_desktop_entry_names(
+            r#"[Desktop Entry]
+            Name=Files
+            Name[zh_CN]=文件
+            Exec=nautilus
+
+            [Desktop Action new-window]
+            Name=New Window
+            "#,
+        );
+
+        assert_eq!(names.get("default").map(String::as_str), Some("Files"));
+        assert_eq!(names.get("zh_CN").map(String::as_str), Some("文件"));
+        assert_eq!(names.len(), 2);
+    }
+}
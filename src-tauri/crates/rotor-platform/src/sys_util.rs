@@ -40,6 +40,16 @@ pub fn run_as_admin() -> Result<bool, Box<dyn Error>> {
     Ok(true)
 }
 
+// Lets a GUI subsystem process print to the console it was started from.
+#[cfg(target_os = "windows")]
+pub fn attach_parent_console() {
+    use windows::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+
+    if let Err(error) = unsafe { AttachConsole(ATTACH_PARENT_PROCESS) } {
+        log::debug!("Failed to attach parent console: {error}");
+    }
+}
+
 // Check whether the disk represented by a drive letter is in ntfs format
 #[cfg(target_os = "windows")]
 pub fn is_ntfs(vol: char) -> bool {
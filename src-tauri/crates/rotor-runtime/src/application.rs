@@ -12,6 +12,7 @@ use serde::Serialize;
 use tauri::{AppHandle, Emitter};
 use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutEvent, ShortcutState};
 
+use crate::ipc;
 use crate::quick::Quick;
 use crate::tray::Tray;
 
@@ -92,6 +93,16 @@ fn dispatch_global_hotkey_event(dispatch: GlobalHotkeyDispatch) {
             handled = true;
         }
 
+        if !handled && rotor_app.translate_replace_shortcut == Some(shortcut) {
+            let result = rotor_app.translator.run_replace();
+            rotor_app.finish_shortcut_trigger(shortcut_id);
+            result.unwrap_or_else(|e| {
+                let flag = rotor_app.translator.flag();
+                log::error!("Module {flag} run error: {e}")
+            });
+            handled = true;
+        }
+
         if !handled && rotor_app.translate_input_shortcut == Some(shortcut) {
             let result = rotor_app.translator.run_input();
             rotor_app.finish_shortcut_trigger(shortcut_id);
@@ -135,6 +146,7 @@ pub struct Application {
     search_shortcut: Option<Shortcut>,
     translate_select_shortcut: Option<Shortcut>,
     translate_input_shortcut: Option<Shortcut>,
+    translate_replace_shortcut: Option<Shortcut>,
     pressed_shortcuts: HashSet<u32>,
     last_shortcut_triggers: HashMap<u32, Instant>,
     shortcut_registration_notices: Vec<ShortcutRegistrationNotice>,
@@ -156,6 +168,7 @@ impl Application {
             search_shortcut: None,
             translate_select_shortcut: None,
             translate_input_shortcut: None,
+            translate_replace_shortcut: None,
             pressed_shortcuts: HashSet::new(),
             last_shortcut_triggers: HashMap::new(),
             shortcut_registration_notices: Vec::new(),
@@ -196,6 +209,7 @@ impl Application {
             let flag = self.searcher.flag();
             log::error!("Module {flag} init error: {e}");
         }
+        ipc::start(ipc::RuntimeService::new(self.searcher.client()));
         self.search_shortcut = self.searcher.get_shortcut();
         if let Some(shortcut) = self.search_shortcut {
             if let Err(e) = app.global_shortcut().register(shortcut) {
@@ -225,6 +239,14 @@ impl Application {
                 self.notify_shortcut_registration_error("shortcut_translate_input", shortcut, e);
             }
         }
+        self.translate_replace_shortcut = self.translator.get_replace_shortcut();
+        if let Some(shortcut) = self.translate_replace_shortcut {
+            if let Err(e) = app.global_shortcut().register(shortcut) {
+                let flag = self.translator.flag();
+                log::error!("Module {flag} shortcut registration error: {e}");
+                self.notify_shortcut_registration_error("shortcut_translate_replace", shortcut, e);
+            }
+        }
 
         self.quick.reload();
         for (action_id, shortcut) in self.quick.get_shortcuts() {
@@ -259,6 +281,7 @@ impl Application {
         filename: String,
         update_result: Vec<SearchResultItem>,
         if_increase: bool,
+        error: Option<String>,
     ) {
         let app_handle = {
             let app = Application::lock_global();
@@ -269,7 +292,7 @@ impl Application {
             if let Err(e) = app_handle.emit_to(
                 "searcher",
                 "update_result",
-                (filename, update_result, if_increase),
+                (filename, update_result, if_increase, error),
             ) {
                 log::warn!("Failed to emit search result update: {e}");
             }
@@ -337,6 +360,7 @@ impl Application {
             "shortcut_search" => self.search_shortcut = Some(shortcut),
             "shortcut_translate_select" => self.translate_select_shortcut = Some(shortcut),
             "shortcut_translate_input" => self.translate_input_shortcut = Some(shortcut),
+            "shortcut_translate_replace" => self.translate_replace_shortcut = Some(shortcut),
             _ => {}
         }
     }
@@ -0,0 +1,624 @@
+mod service;
+#[cfg(windows)]
+mod windows_pipe;
+
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use rotor_screenshot::history::HistoryMatch;
+use rotor_screenshot::img_util::TextResult;
+use rotor_screenshot::CaptureRegion;
+use rotor_searcher::file_data::{SearchIndexStatus, SearchResultItem};
+use rotor_translator::engine::TranslateResult;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+
+pub use service::RuntimeService;
+
+/*
+Local JSON-RPC 2.0 endpoint for scripts and editor plugins, one message per line:
+    {"jsonrpc":"2.0","id":1,"method":"find","params":{"query":"ext:pdf report","limit":10}}
+    {"jsonrpc":"2.0","id":2,"method":"index_status"}
+    {"jsonrpc":"2.0","id":3,"method":"rebuild"}
+    {"jsonrpc":"2.0","id":4,"method":"screenshot","params":{"region":{"x":0,"y":0,"width":800,"height":600},"out":"/tmp/shot.png"}}
+    {"jsonrpc":"2.0","id":5,"method":"ocr","params":{"image":"/tmp/shot.png"}}
+    {"jsonrpc":"2.0","id":6,"method":"translate","params":{"text":"Hello"}}
+    {"jsonrpc":"2.0","id":7,"method":"screenshot_history","params":{"query":"error dialog","limit":10}}
+Paths must be absolute as the server does not share the caller's working dir.
+It listens on `ipc/rotor.sock` in the user data dir, or on Windows the
+`\\.\pipe\rotor-<user SID>-<session id>` named pipe that only the user can open.
+Requests without an id are notifications and get no response.
+*/
+
+#[cfg(unix)]
+const SOCKET_DIR: &str = "ipc";
+#[cfg(unix)]
+const SOCKET_FILE: &str = "rotor.sock";
+const DEFAULT_FIND_LIMIT: u8 = 20;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const SERVER_ERROR: i64 = -32000;
+
+// What the endpoint serves, implemented by `RuntimeService`. Methods may
+// block, they run on the blocking thread pool.
+pub trait IpcService: Send + Sync + 'static {
+    fn find(&self, query: String, limit: u8) -> Result<Vec<SearchResultItem>, String>;
+    fn index_status(&self) -> SearchIndexStatus;
+    fn rebuild_index(&self);
+    fn screenshot(
+        &self,
+        region: Option<CaptureRegion>,
+        out: PathBuf,
+    ) -> Result<ScreenshotOutput, String>;
+    fn ocr(&self, image: PathBuf) -> Result<Vec<TextResult>, String>;
+    fn translate(&self, text: String) -> Result<TranslateResult, String>;
+    fn screenshot_history(&self, query: String, limit: usize) -> Vec<HistoryMatch>;
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ScreenshotOutput {
+    pub path: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Deserialize)]
+struct RpcRequest {
+    #[serde(default)]
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Serialize, Debug)]
+struct RpcResponse {
+    jsonrpc: &'static str,
+    id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<RpcError>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> RpcError {
+        RpcError {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl RpcResponse {
+    fn new(id: Value, result: Result<Value, RpcError>) -> RpcResponse {
+        let (result, error) = match result {
+            Ok(result) => (Some(result), None),
+            Err(error) => (None, Some(error)),
+        };
+
+        RpcResponse {
+            jsonrpc: "2.0",
+            id,
+            result,
+            error,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct FindParams {
+    query: String,
+    #[serde(default)]
+    limit: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct ScreenshotParams {
+    #[serde(default)]
+    region: Option<CaptureRegion>,
+    out: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct OcrParams {
+    image: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct TranslateParams {
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct HistoryParams {
+    #[serde(default)]
+    query: String,
+    #[serde(default)]
+    limit: Option<u64>,
+}
+
+#[cfg(unix)]
+pub fn socket_path() -> Option<PathBuf> {
+    rotor_common::file_path::get_userdata_path()
+        .map(|user_data| user_data.join(SOCKET_DIR).join(SOCKET_FILE))
+}
+
+// Sends a single request to the running instance and waits for its result.
+pub fn call(method: &str, params: Value) -> Result<Value, String> {
+    let stream = connect().map_err(|error| format!("Rotor is not running ({error})"))?;
+    let request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": method,
+        "params": params,
+    });
+
+    let exchange = || -> io::Result<String> {
+        writeln!(&stream, "{request}")?;
+        let mut line = String::new();
+        io::BufReader::new(&stream).read_line(&mut line)?;
+        Ok(line)
+    };
+    let line = exchange().map_err(|error| format!("Failed to talk to Rotor: {error}"))?;
+
+    let mut response = serde_json::from_str::<Value>(&line)
+        .map_err(|error| format!("Invalid response from Rotor: {error}"))?;
+    if let Some(error) = response.get("error") {
+        let message = error
+            .get("message")
+            .and_then(Value::as_str)
+            .unwrap_or("Unknown error");
+        return Err(message.to_string());
+    }
+    Ok(response
+        .get_mut("result")
+        .map(Value::take)
+        .unwrap_or_default())
+}
+
+#[cfg(unix)]
+fn connect() -> io::Result<std::os::unix::net::UnixStream> {
+    let path = socket_path().ok_or_else(|| {
+        io::Error::new(io::ErrorKind::NotFound, "Unable to resolve user data path")
+    })?;
+    std::os::unix::net::UnixStream::connect(path)
+}
+
+#[cfg(windows)]
+fn connect() -> io::Result<std::fs::File> {
+    std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(windows_pipe::PipeOwner::current()?.pipe_name())
+}
+
+pub fn start(service: impl IpcService) {
+    let service = Arc::new(service);
+    tauri::async_runtime::spawn(async move {
+        if let Err(error) = serve(service).await {
+            log::error!("Local IPC server stopped: {error}");
+        }
+    });
+}
+
+#[cfg(unix)]
+async fn serve<S: IpcService>(service: Arc<S>) -> io::Result<()> {
+    let path = socket_path().ok_or_else(|| {
+        io::Error::new(io::ErrorKind::NotFound, "Unable to resolve user data path")
+    })?;
+    if let Some(socket_dir) = path.parent() {
+        create_private_dir(socket_dir)?;
+    }
+
+    // The single-instance plugin guarantees a socket left here is stale.
+    match std::fs::remove_file(&path) {
+        Err(error) if error.kind() != io::ErrorKind::NotFound => return Err(error),
+        _ => {}
+    }
+    let listener = tokio::net::UnixListener::bind(&path)?;
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        spawn_connection(service.clone(), stream);
+    }
+}
+
+// The socket is bound inside a directory only the user can enter, so it is
+// never reachable by others, not even before its own mode could be changed.
+#[cfg(unix)]
+fn create_private_dir(dir: &std::path::Path) -> io::Result<()> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+
+    if let Some(parent) = dir.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    match std::fs::DirBuilder::new().mode(0o700).create(dir) {
+        Err(error) if error.kind() != io::ErrorKind::AlreadyExists => return Err(error),
+        _ => {}
+    }
+    // A directory left by an older version keeps its mode until tightened here.
+    std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))
+}
+
+#[cfg(windows)]
+async fn serve<S: IpcService>(service: Arc<S>) -> io::Result<()> {
+    let owner = windows_pipe::PipeOwner::current()?;
+    let mut server = owner.create_pipe(true)?;
+
+    loop {
+        server.connect().await?;
+        // Create the next instance before serving so clients never see the pipe missing.
+        let stream = std::mem::replace(&mut server, owner.create_pipe(false)?);
+        spawn_connection(service.clone(), stream);
+    }
+}
+
+fn spawn_connection<S, T>(service: Arc<S>, stream: T)
+where
+    S: IpcService,
+    T: AsyncRead + AsyncWrite + Send + 'static,
+{
+    tokio::spawn(async move {
+        if let Err(error) = serve_connection(service, stream).await {
+            log::warn!("Local IPC connection error: {error}");
+        }
+    });
+}
+
+async fn serve_connection<S, T>(service: Arc<S>, stream: T) -> io::Result<()>
+where
+    S: IpcService,
+    T: AsyncRead + AsyncWrite,
+{
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if let Some(response) = handle_request(&service, &line).await {
+            let mut response = serde_json::to_string(&response)?;
+            response.push('\n');
+            writer.write_all(response.as_bytes()).await?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn handle_request<S: IpcService>(service: &Arc<S>, line: &str) -> Option<RpcResponse> {
+    let request = match serde_json::from_str::<RpcRequest>(line) {
+        Ok(request) => request,
+        Err(error) => {
+            let code = if error.is_data() {
+                INVALID_REQUEST
+            } else {
+                PARSE_ERROR
+            };
+            return Some(RpcResponse::new(
+                Value::Null,
+                Err(RpcError::new(code, error.to_string())),
+            ));
+        }
+    };
+
+    let result = dispatch(service, &request.method, request.params).await;
+    request.id.map(|id| RpcResponse::new(id, result))
+}
+
+async fn dispatch<S: IpcService>(
+    service: &Arc<S>,
+    method: &str,
+    params: Value,
+) -> Result<Value, RpcError> {
+    let service = service.clone();
+    match method {
+        "find" => {
+            let params = parse_params::<FindParams>(params)?;
+            let limit = params
+                .limit
+                .map(|limit| limit.clamp(1, u8::MAX as u64) as u8)
+                .unwrap_or(DEFAULT_FIND_LIMIT);
+
+            let items = run_blocking(move || service.find(params.query, limit))
+                .await?
+                .map_err(|error| RpcError::new(SERVER_ERROR, error))?;
+            to_value(items)
+        }
+        "index_status" => to_value(run_blocking(move || service.index_status()).await?),
+        "rebuild" => {
+            service.rebuild_index();
+            Ok(Value::Null)
+        }
+        "screenshot" => {
+            let params = parse_params::<ScreenshotParams>(params)?;
+            to_value(
+                run_blocking(move || service.screenshot(params.region, params.out))
+                    .await?
+                    .map_err(|error| RpcError::new(SERVER_ERROR, error))?,
+            )
+        }
+        "ocr" => {
+            let params = parse_params::<OcrParams>(params)?;
+            to_value(
+                run_blocking(move || service.ocr(params.image))
+                    .await?
+                    .map_err(|error| RpcError::new(SERVER_ERROR, error))?,
+            )
+        }
+        "translate" => {
+            let params = parse_params::<TranslateParams>(params)?;
+            to_value(
+                run_blocking(move || service.translate(params.text))
+                    .await?
+                    .map_err(|error| RpcError::new(SERVER_ERROR, error))?,
+            )
+        }
+        "screenshot_history" => {
+            let params = parse_params::<HistoryParams>(params)?;
+            let limit = params.limit.unwrap_or(DEFAULT_FIND_LIMIT as u64) as usize;
+            to_value(run_blocking(move || service.screenshot_history(params.query, limit)).await?)
+        }
+        _ => Err(RpcError::new(
+            METHOD_NOT_FOUND,
+            format!("Unknown method `{method}`"),
+        )),
+    }
+}
+
+async fn run_blocking<T: Send + 'static>(
+    task: impl FnOnce() -> T + Send + 'static,
+) -> Result<T, RpcError> {
+    tokio::task::spawn_blocking(task)
+        .await
+        .map_err(|error| RpcError::new(SERVER_ERROR, error.to_string()))
+}
+
+fn parse_params<T: serde::de::DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    serde_json::from_value(params).map_err(|error| RpcError::new(INVALID_PARAMS, error.to_string()))
+}
+
+fn to_value(value: impl Serialize) -> Result<Value, RpcError> {
+    serde_json::to_value(value).map_err(|error| RpcError::new(SERVER_ERROR, error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct FakeService {
+        finds: Mutex<Vec<(String, u8)>>,
+        rebuilds: Mutex<usize>,
+        screenshots: Mutex<Vec<(Option<CaptureRegion>, PathBuf)>>,
+        history_queries: Mutex<Vec<(String, usize)>>,
+    }
+
+    impl IpcService for FakeService {
+        fn find(&self, query: String, limit: u8) -> Result<Vec<SearchResultItem>, String> {
+            if query == "regex:(" {
+                return Err("Invalid regular expression".to_string());
+            }
+            self.finds.lock().unwrap().push((query.clone(), limit));
+            Ok(vec![SearchResultItem {
+                path: "/docs".to_string(),
+                file_path: "/docs/report.pdf".to_string(),
+                file_name: "report.pdf".to_string(),
+                rank: 10,
+                icon_data: None,
+                alias: None,
+                size: Some(1024),
+                modified_at: None,
+                is_dir: false,
+            }])
+        }
+
+        fn index_status(&self) -> SearchIndexStatus {
+            SearchIndexStatus::empty()
+        }
+
+        fn rebuild_index(&self) {
+            *self.rebuilds.lock().unwrap() += 1;
+        }
+
+        fn screenshot(
+            &self,
+            region: Option<CaptureRegion>,
+            out: PathBuf,
+        ) -> Result<ScreenshotOutput, String> {
+            self.screenshots.lock().unwrap().push((region, out.clone()));
+            Ok(ScreenshotOutput {
+                path: out.to_string_lossy().into_owned(),
+                width: region.map_or(1920, |region| region.width),
+                height: region.map_or(1080, |region| region.height),
+            })
+        }
+
+        fn ocr(&self, image: PathBuf) -> Result<Vec<TextResult>, String> {
+            Err(format!("Failed to open {}", image.display()))
+        }
+
+        fn translate(&self, text: String) -> Result<TranslateResult, String> {
+            Ok(TranslateResult {
+                translated: text.to_uppercase(),
+                text,
+                from: "en".to_string(),
+                to: "en".to_string(),
+                engine: "fake".to_string(),
+            })
+        }
+
+        fn screenshot_history(&self, query: String, limit: usize) -> Vec<HistoryMatch> {
+            self.history_queries.lock().unwrap().push((query, limit));
+            Vec::new()
+        }
+    }
+
+    async fn request(service: &Arc<FakeService>, line: &str) -> Option<Value> {
+        let response = handle_request(service, line).await?;
+        Some(serde_json::to_value(response).unwrap())
+    }
+
+    #[tokio::test]
+    async fn find_returns_results_and_clamps_limit() {
+        let service = Arc::new(FakeService::default());
+
+        let response = request(
+            &service,
+            r#"{"jsonrpc":"2.0","id":1,"method":"find","params":{"query":"report","limit":1000}}"#,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response["id"], json!(1));
+        assert_eq!(
+            response["result"][0]["file_path"],
+            json!("/docs/report.pdf")
+        );
+        assert_eq!(
+            *service.finds.lock().unwrap(),
+            vec![("report".to_string(), u8::MAX)]
+        );
+
+        let response = request(
+            &service,
+            r#"{"jsonrpc":"2.0","id":2,"method":"find","params":{"query":"regex:("}}"#,
+        )
+        .await
+        .unwrap();
+        assert_eq!(response["error"]["code"], json!(SERVER_ERROR));
+    }
+
+    #[tokio::test]
+    async fn reports_protocol_errors() {
+        let service = Arc::new(FakeService::default());
+
+        let parse_error = request(&service, "{").await.unwrap();
+        assert_eq!(parse_error["error"]["code"], json!(PARSE_ERROR));
+
+        let invalid_request = request(&service, r#"{"id":1}"#).await.unwrap();
+        assert_eq!(invalid_request["error"]["code"], json!(INVALID_REQUEST));
+
+        let unknown_method = request(&service, r#"{"id":"a","method":"delete"}"#)
+            .await
+            .unwrap();
+        assert_eq!(unknown_method["id"], json!("a"));
+        assert_eq!(unknown_method["error"]["code"], json!(METHOD_NOT_FOUND));
+
+        let invalid_params = request(&service, r#"{"id":2,"method":"find","params":[]}"#)
+            .await
+            .unwrap();
+        assert_eq!(invalid_params["error"]["code"], json!(INVALID_PARAMS));
+    }
+
+    #[tokio::test]
+    async fn notifications_run_without_response() {
+        let service = Arc::new(FakeService::default());
+
+        assert!(request(&service, r#"{"jsonrpc":"2.0","method":"rebuild"}"#)
+            .await
+            .is_none());
+        assert_eq!(*service.rebuilds.lock().unwrap(), 1);
+
+        let status = request(&service, r#"{"id":3,"method":"index_status"}"#)
+            .await
+            .unwrap();
+        assert_eq!(status["result"]["state"], json!("unavailable"));
+    }
+
+    #[tokio::test]
+    async fn forwards_screenshot_ocr_translate_and_history_params() {
+        let service = Arc::new(FakeService::default());
+
+        let screenshot = request(
+            &service,
+            r#"{"id":1,"method":"screenshot","params":{"region":{"x":-10,"y":0,"width":800,"height":600},"out":"/tmp/shot.png"}}"#,
+        )
+        .await
+        .unwrap();
+        assert_eq!(screenshot["result"]["width"], json!(800));
+        assert_eq!(
+            *service.screenshots.lock().unwrap(),
+            vec![(
+                Some(CaptureRegion {
+                    x: -10,
+                    y: 0,
+                    width: 800,
+                    height: 600,
+                }),
+                PathBuf::from("/tmp/shot.png")
+            )]
+        );
+
+        let missing_out = request(&service, r#"{"id":2,"method":"screenshot","params":{}}"#)
+            .await
+            .unwrap();
+        assert_eq!(missing_out["error"]["code"], json!(INVALID_PARAMS));
+
+        let ocr = request(
+            &service,
+            r#"{"id":3,"method":"ocr","params":{"image":"/tmp/missing.png"}}"#,
+        )
+        .await
+        .unwrap();
+        assert_eq!(ocr["error"]["code"], json!(SERVER_ERROR));
+
+        let translate = request(
+            &service,
+            r#"{"id":4,"method":"translate","params":{"text":"hi"}}"#,
+        )
+        .await
+        .unwrap();
+        assert_eq!(translate["result"]["translated"], json!("HI"));
+
+        let history = request(
+            &service,
+            r#"{"id":5,"method":"screenshot_history","params":{"query":"error dialog"}}"#,
+        )
+        .await
+        .unwrap();
+        assert_eq!(history["result"], json!([]));
+        assert_eq!(
+            *service.history_queries.lock().unwrap(),
+            vec![("error dialog".to_string(), DEFAULT_FIND_LIMIT as usize)]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn socket_dir_is_private_even_if_it_existed() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = std::env::temp_dir().join(format!("rotor-ipc-{}", std::process::id()));
+        let dir = root.join("ipc");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        create_private_dir(&dir).unwrap();
+        let mode = std::fs::metadata(&dir).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        create_private_dir(&dir).unwrap();
+        let mode = std::fs::metadata(&dir).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
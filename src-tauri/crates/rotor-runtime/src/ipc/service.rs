@@ -0,0 +1,86 @@
+use std::path::PathBuf;
+
+use rotor_screenshot::history::HistoryMatch;
+use rotor_screenshot::img_util::{self, TextResult};
+use rotor_screenshot::CaptureRegion;
+use rotor_searcher::file_data::{SearchIndexStatus, SearchResultItem};
+use rotor_searcher::SearchClient;
+use rotor_translator::engine::TranslateResult;
+use tauri::path::BaseDirectory;
+use tauri::Manager;
+
+use super::{IpcService, ScreenshotOutput};
+use crate::Application;
+
+// Serves IPC requests with the modules of the running application.
+pub struct RuntimeService {
+    search: SearchClient,
+}
+
+impl RuntimeService {
+    pub fn new(search: SearchClient) -> RuntimeService {
+        RuntimeService { search }
+    }
+}
+
+impl IpcService for RuntimeService {
+    fn find(&self, query: String, limit: u8) -> Result<Vec<SearchResultItem>, String> {
+        self.search.find(query, limit)
+    }
+
+    fn index_status(&self) -> SearchIndexStatus {
+        self.search.index_status()
+    }
+
+    fn rebuild_index(&self) {
+        self.search.rebuild_index();
+    }
+
+    fn screenshot(
+        &self,
+        region: Option<CaptureRegion>,
+        out: PathBuf,
+    ) -> Result<ScreenshotOutput, String> {
+        let image = rotor_screenshot::capture_region(region)
+            .map_err(|error| format!("Failed to capture screenshot: {error}"))?;
+        image
+            .save(&out)
+            .map_err(|error| format!("Failed to save {}: {error}", out.display()))?;
+
+        Ok(ScreenshotOutput {
+            path: out.to_string_lossy().into_owned(),
+            width: image.width(),
+            height: image.height(),
+        })
+    }
+
+    fn ocr(&self, image: PathBuf) -> Result<Vec<TextResult>, String> {
+        let app = Application::lock_global()
+            .app
+            .clone()
+            .ok_or("Rotor is not initialized")?;
+        let model_path = app
+            .path()
+            .resolve("assets/model", BaseDirectory::Resource)
+            .map_err(|error| format!("Failed to resolve model path: {error}"))?;
+        let img = image::open(&image)
+            .map_err(|error| format!("Failed to open {}: {error}", image.display()))?;
+
+        img_util::img2text(&model_path, &img).map_err(|error| format!("Failed to run OCR: {error}"))
+    }
+
+    fn translate(&self, text: String) -> Result<TranslateResult, String> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err("Empty text".to_string());
+        }
+
+        tauri::async_runtime::block_on(rotor_translator::engine::translate(text, None, |_| {}))
+            .map_err(|error| format!("Translate error: {error}"))
+    }
+
+    fn screenshot_history(&self, query: String, limit: usize) -> Vec<HistoryMatch> {
+        let history = Application::lock_global().screenshot.history();
+        history.search(&query, limit)
+    }
+}
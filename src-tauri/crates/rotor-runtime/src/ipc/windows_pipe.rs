@@ -0,0 +1,103 @@
+use std::ffi::c_void;
+use std::{io, mem};
+
+use tokio::net::windows::named_pipe::{NamedPipeServer, ServerOptions};
+use windows::core::{HSTRING, PWSTR};
+use windows::Win32::Foundation::{CloseHandle, LocalFree, HANDLE, HLOCAL};
+use windows::Win32::Security::Authorization::{
+    ConvertSidToStringSidW, ConvertStringSecurityDescriptorToSecurityDescriptorW, SDDL_REVISION_1,
+};
+use windows::Win32::Security::{
+    GetTokenInformation, TokenSessionId, TokenUser, PSECURITY_DESCRIPTOR, SECURITY_ATTRIBUTES,
+    TOKEN_QUERY, TOKEN_USER,
+};
+use windows::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+
+// The user and logon session the endpoint belongs to. Both are part of the pipe
+// name so every session gets its own pipe, and only the user may open it.
+pub(super) struct PipeOwner {
+    sid: String,
+    session_id: u32,
+}
+
+impl PipeOwner {
+    pub(super) fn current() -> io::Result<PipeOwner> {
+        let mut token = HANDLE::default();
+        unsafe { OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token) }
+            .map_err(io::Error::other)?;
+        let owner = unsafe { query_token(token) };
+        let _ = unsafe { CloseHandle(token) };
+        owner
+    }
+
+    pub(super) fn pipe_name(&self) -> String {
+        format!(r"\\.\pipe\rotor-{}-{}", self.sid, self.session_id)
+    }
+
+    // The protected DACL grants the user full access and nobody else anything,
+    // instead of the default one that lets every local account connect.
+    pub(super) fn create_pipe(&self, first_instance: bool) -> io::Result<NamedPipeServer> {
+        let sddl = HSTRING::from(format!("D:P(A;;GA;;;{})", self.sid));
+        let mut descriptor = PSECURITY_DESCRIPTOR::default();
+        unsafe {
+            ConvertStringSecurityDescriptorToSecurityDescriptorW(
+                &sddl,
+                SDDL_REVISION_1,
+                &mut descriptor,
+                None,
+            )
+        }
+        .map_err(io::Error::other)?;
+
+        let mut attributes = SECURITY_ATTRIBUTES {
+            nLength: mem::size_of::<SECURITY_ATTRIBUTES>() as u32,
+            lpSecurityDescriptor: descriptor.0,
+            bInheritHandle: false.into(),
+        };
+        let server = unsafe {
+            ServerOptions::new()
+                .first_pipe_instance(first_instance)
+                .reject_remote_clients(true)
+                .create_with_security_attributes_raw(
+                    self.pipe_name(),
+                    &mut attributes as *mut SECURITY_ATTRIBUTES as *mut c_void,
+                )
+        };
+        let _ = unsafe { LocalFree(Some(HLOCAL(descriptor.0))) };
+        server
+    }
+}
+
+unsafe fn query_token(token: HANDLE) -> io::Result<PipeOwner> {
+    // TOKEN_USER points into the same buffer for its SID, so ask for the size first.
+    let mut size = 0u32;
+    let _ = GetTokenInformation(token, TokenUser, None, 0, &mut size);
+    let mut buffer = vec![0u64; (size as usize).div_ceil(mem::size_of::<u64>())];
+    GetTokenInformation(
+        token,
+        TokenUser,
+        Some(buffer.as_mut_ptr().cast()),
+        size,
+        &mut size,
+    )
+    .map_err(io::Error::other)?;
+    let user = &*(buffer.as_ptr() as *const TOKEN_USER);
+
+    let mut sid = PWSTR::null();
+    ConvertSidToStringSidW(user.User.Sid, &mut sid).map_err(io::Error::other)?;
+    let sid_string = sid.to_string();
+    let _ = LocalFree(Some(HLOCAL(sid.0.cast())));
+    let sid = sid_string.map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+
+    let mut session_id = 0u32;
+    GetTokenInformation(
+        token,
+        TokenSessionId,
+        Some((&mut session_id as *mut u32).cast()),
+        mem::size_of::<u32>() as u32,
+        &mut size,
+    )
+    .map_err(io::Error::other)?;
+
+    Ok(PipeOwner { sid, session_id })
+}
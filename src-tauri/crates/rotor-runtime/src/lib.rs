@@ -1,4 +1,5 @@
 pub mod application;
+pub mod ipc;
 pub mod quick;
 pub mod screenshot_data;
 pub mod tray;
@@ -0,0 +1,466 @@
+use image::{DynamicImage, RgbaImage};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{fs, io, thread};
+use toml;
+
+use rotor_common::AppConfig;
+use rotor_platform::file_util;
+
+use crate::img_util::{self, TextResult};
+
+const DEFAULT_HISTORY_LIMIT: usize = 500;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HistoryEntry {
+    pub id: u64,
+    // Seconds since the Unix epoch.
+    pub timestamp: u64,
+    #[serde(default)]
+    pub monitor_id: Option<u32>,
+    pub monitor_pos: (i32, i32),
+    pub monitor_size: (u32, u32),
+    pub rect: (u32, u32, u32, u32),
+    #[serde(default)]
+    pub texts: Vec<TextResult>,
+}
+
+impl HistoryEntry {
+    pub fn text(&self) -> String {
+        self.texts
+            .iter()
+            .map(|text| text.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct HistoryMatch {
+    #[serde(flatten)]
+    pub entry: HistoryEntry,
+    pub path: PathBuf,
+}
+
+// Where a capture came from, recorded alongside its image and OCR text.
+#[derive(Debug, Clone)]
+pub struct CaptureSource {
+    // Seconds since the Unix epoch, when the screen was captured.
+    pub timestamp: u64,
+    pub monitor_id: Option<u32>,
+    pub monitor_pos: (i32, i32),
+    pub monitor_size: (u32, u32),
+    pub rect: (u32, u32, u32, u32),
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct HistoryIndex {
+    #[serde(default)]
+    entries: Vec<HistoryEntry>,
+}
+
+// Screenshot history kept in the user data directory: one PNG per capture plus
+// an index of the capture metadata and recognized text.
+#[derive(Clone)]
+pub struct ScreenshotHistory {
+    root_path: Option<PathBuf>,
+    index: Arc<Mutex<HistoryIndex>>,
+}
+
+impl ScreenshotHistory {
+    pub fn new() -> ScreenshotHistory {
+        match file_util::get_userdata_path() {
+            Some(path) => ScreenshotHistory::open(path.join("screenshot_history")),
+            None => {
+                log::error!("Unable to resolve user data path for screenshot history");
+                ScreenshotHistory {
+                    root_path: None,
+                    index: Arc::default(),
+                }
+            }
+        }
+    }
+
+    pub fn open(root_path: PathBuf) -> ScreenshotHistory {
+        let index_str = fs::read_to_string(root_path.join("index.toml")).unwrap_or_default();
+        let index = toml::from_str::<HistoryIndex>(&index_str).unwrap_or_else(|error| {
+            log::warn!("Failed to parse screenshot history, starting empty: {error}");
+            HistoryIndex::default()
+        });
+
+        ScreenshotHistory {
+            root_path: Some(root_path),
+            index: Arc::new(Mutex::new(index)),
+        }
+    }
+
+    pub fn is_enabled() -> bool {
+        AppConfig::lock_global()
+            .get("screenshot_history")
+            .is_some_and(|value| value == "true")
+    }
+
+    fn limit() -> usize {
+        AppConfig::lock_global()
+            .get("screenshot_history_limit")
+            .and_then(|value| value.parse::<usize>().ok())
+            .unwrap_or(DEFAULT_HISTORY_LIMIT)
+    }
+
+    // Crops the selection out of the monitor capture, then saves and recognizes it
+    // on a background thread so finishing a screenshot never waits for OCR.
+    pub fn record_capture(
+        &self,
+        capture: Arc<RgbaImage>,
+        source: CaptureSource,
+        model_path: PathBuf,
+    ) -> thread::JoinHandle<()> {
+        let history = self.clone();
+        let limit = ScreenshotHistory::limit();
+        thread::spawn(move || {
+            let (x, y, width, height) = source.rect;
+            let right = x.saturating_add(width);
+            let bottom = y.saturating_add(height);
+            if width == 0 || height == 0 || right > capture.width() || bottom > capture.height() {
+                log::warn!(
+                    "Skipping screenshot history for invalid rect {:?}",
+                    source.rect
+                );
+                return;
+            }
+
+            let img = DynamicImage::ImageRgba8(
+                image::imageops::crop_imm(capture.as_ref(), x, y, width, height).to_image(),
+            );
+            history.recognize_and_add(&img, source, &model_path, limit, false);
+        })
+    }
+
+    // Records a screenshot saved to disk, which may carry annotations drawn on
+    // the pin. It takes the place of the entry recorded for the same capture.
+    pub fn record_saved(
+        &self,
+        img: DynamicImage,
+        source: CaptureSource,
+        model_path: PathBuf,
+    ) -> thread::JoinHandle<()> {
+        let history = self.clone();
+        let limit = ScreenshotHistory::limit();
+        thread::spawn(move || {
+            history.recognize_and_add(&img, source, &model_path, limit, true);
+        })
+    }
+
+    fn recognize_and_add(
+        &self,
+        img: &DynamicImage,
+        source: CaptureSource,
+        model_path: &Path,
+        limit: usize,
+        replace: bool,
+    ) {
+        let texts = img_util::img2text(model_path, img).unwrap_or_else(|error| {
+            log::warn!("Failed to recognize screenshot history text: {error}");
+            Vec::new()
+        });
+        if let Err(error) = self.add(img, source, texts, limit, replace) {
+            log::error!("Failed to record screenshot history: {error}");
+        }
+    }
+
+    // A capture is identified by its timestamp and monitor; the rect is left out
+    // since a pin can be cropped after it was recorded. An entry for the same
+    // capture is overwritten when `replace` is set and kept as is otherwise.
+    pub fn add(
+        &self,
+        img: &DynamicImage,
+        source: CaptureSource,
+        texts: Vec<TextResult>,
+        limit: usize,
+        replace: bool,
+    ) -> Result<HistoryEntry, Box<dyn Error>> {
+        let root_path = self.root_path()?;
+        fs::create_dir_all(root_path)?;
+
+        let mut index = self.lock_index();
+        let existing = index.entries.iter().position(|entry| {
+            entry.timestamp == source.timestamp
+                && entry.monitor_id == source.monitor_id
+                && entry.monitor_pos == source.monitor_pos
+        });
+        if let Some(position) = existing {
+            if !replace {
+                return Ok(index.entries[position].clone());
+            }
+        }
+
+        let id = match existing {
+            Some(position) => index.entries[position].id,
+            None => {
+                let now = SystemTime::now().duration_since(UNIX_EPOCH)?;
+                let last_id = index.entries.iter().map(|entry| entry.id).max();
+                last_id.map_or(0, |id| id + 1).max(now.as_millis() as u64)
+            }
+        };
+        img.save(image_path(root_path, id))?;
+
+        let entry = HistoryEntry {
+            id,
+            timestamp: source.timestamp,
+            monitor_id: source.monitor_id,
+            monitor_pos: source.monitor_pos,
+            monitor_size: source.monitor_size,
+            rect: source.rect,
+            texts,
+        };
+        match existing {
+            Some(position) => index.entries[position] = entry.clone(),
+            None => index.entries.push(entry.clone()),
+        }
+
+        let overflow = index.entries.len().saturating_sub(limit.max(1));
+        for removed in index.entries.drain(..overflow) {
+            remove_image(root_path, removed.id);
+        }
+        self.save(&index)?;
+        Ok(entry)
+    }
+
+    // Returns the captures whose recognized text contains every word of `query`,
+    // newest first. An empty query lists the most recent captures.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<HistoryMatch> {
+        let Ok(root_path) = self.root_path() else {
+            return Vec::new();
+        };
+        let terms = query
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect::<Vec<_>>();
+
+        let index = self.lock_index();
+        index
+            .entries
+            .iter()
+            .rev()
+            .filter(|entry| {
+                let text = entry.text().to_lowercase();
+                terms.iter().all(|term| text.contains(term.as_str()))
+            })
+            .take(limit)
+            .map(|entry| HistoryMatch {
+                entry: entry.clone(),
+                path: image_path(root_path, entry.id),
+            })
+            .collect()
+    }
+
+    pub fn delete(&self, id: u64) -> Result<(), Box<dyn Error>> {
+        let root_path = self.root_path()?;
+        let mut index = self.lock_index();
+        let len = index.entries.len();
+        index.entries.retain(|entry| entry.id != id);
+        if index.entries.len() != len {
+            remove_image(root_path, id);
+            self.save(&index)?;
+        }
+        Ok(())
+    }
+
+    pub fn clear(&self) -> Result<(), Box<dyn Error>> {
+        let root_path = self.root_path()?;
+        let mut index = self.lock_index();
+        for entry in index.entries.drain(..) {
+            remove_image(root_path, entry.id);
+        }
+        self.save(&index)
+    }
+
+    fn root_path(&self) -> Result<&Path, Box<dyn Error>> {
+        self.root_path
+            .as_deref()
+            .ok_or_else(|| Box::<dyn Error>::from("Screenshot history path is unavailable"))
+    }
+
+    fn lock_index(&self) -> MutexGuard<'_, HistoryIndex> {
+        self.index
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn save(&self, index: &HistoryIndex) -> Result<(), Box<dyn Error>> {
+        let index_str = toml::to_string_pretty(index)?;
+        fs::write(self.root_path()?.join("index.toml"), index_str)?;
+        Ok(())
+    }
+}
+
+impl Default for ScreenshotHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Seconds since the Unix epoch, the unit of capture timestamps.
+pub fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+fn image_path(root_path: &Path, id: u64) -> PathBuf {
+    root_path.join(format!("{id}.png"))
+}
+
+fn remove_image(root_path: &Path, id: u64) {
+    match fs::remove_file(image_path(root_path, id)) {
+        Ok(()) => {}
+        Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+        Err(error) => log::warn!("Failed to delete screenshot history image {id}: {error}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CaptureSource, ScreenshotHistory};
+    use crate::img_util::TextResult;
+    use image::{DynamicImage, RgbaImage};
+    use std::path::PathBuf;
+
+    fn temp_root(name: &str) -> PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("rotor-history-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        root
+    }
+
+    fn source(timestamp: u64) -> CaptureSource {
+        CaptureSource {
+            timestamp,
+            monitor_id: Some(1),
+            monitor_pos: (0, 0),
+            monitor_size: (1920, 1080),
+            rect: (10, 20, 4, 4),
+        }
+    }
+
+    fn texts(lines: &[&str]) -> Vec<TextResult> {
+        lines
+            .iter()
+            .map(|line| TextResult {
+                left: 0,
+                top: 0,
+                width: 1,
+                height: 1,
+                text: line.to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn search_matches_every_term_newest_first() {
+        let root = temp_root("search");
+        let history = ScreenshotHistory::open(root.clone());
+        let img = DynamicImage::ImageRgba8(RgbaImage::new(4, 4));
+
+        let first = history
+            .add(
+                &img,
+                source(1),
+                texts(&["Error", "Access denied"]),
+                10,
+                false,
+            )
+            .unwrap();
+        let second = history
+            .add(
+                &img,
+                source(2),
+                texts(&["Build error: file not found"]),
+                10,
+                false,
+            )
+            .unwrap();
+        history
+            .add(&img, source(3), texts(&["Welcome"]), 10, false)
+            .unwrap();
+
+        let ids = |query: &str| {
+            history
+                .search(query, 10)
+                .into_iter()
+                .map(|item| item.entry.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids("error"), vec![second.id, first.id]);
+        assert_eq!(ids("ERROR denied"), vec![first.id]);
+        assert_eq!(ids("").len(), 3);
+        assert!(ids("missing").is_empty());
+
+        let reopened = ScreenshotHistory::open(root.clone());
+        let found = reopened.search("not found", 10);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].entry.rect, (10, 20, 4, 4));
+        assert!(found[0].path.exists());
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn oldest_entries_are_pruned_beyond_limit() {
+        let root = temp_root("prune");
+        let history = ScreenshotHistory::open(root.clone());
+        let img = DynamicImage::ImageRgba8(RgbaImage::new(4, 4));
+
+        let first = history
+            .add(&img, source(1), texts(&["one"]), 2, false)
+            .unwrap();
+        history
+            .add(&img, source(2), texts(&["two"]), 2, false)
+            .unwrap();
+        history
+            .add(&img, source(3), texts(&["three"]), 2, false)
+            .unwrap();
+
+        assert_eq!(history.search("", 10).len(), 2);
+        assert!(history.search("one", 10).is_empty());
+        assert!(!root.join(format!("{}.png", first.id)).exists());
+
+        history.clear().unwrap();
+        assert!(history.search("", 10).is_empty());
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn saved_capture_replaces_its_recorded_entry() {
+        let root = temp_root("saved");
+        let history = ScreenshotHistory::open(root.clone());
+        let img = DynamicImage::ImageRgba8(RgbaImage::new(4, 4));
+
+        let recorded = history
+            .add(&img, source(7), texts(&["draft"]), 10, false)
+            .unwrap();
+        let mut cropped = source(7);
+        cropped.rect = (11, 21, 2, 2);
+        let saved = history
+            .add(&img, cropped.clone(), texts(&["annotated"]), 10, true)
+            .unwrap();
+        let late = history
+            .add(&img, cropped, texts(&["draft"]), 10, false)
+            .unwrap();
+
+        assert_eq!(saved.id, recorded.id);
+        assert_eq!(late.id, recorded.id);
+        let found = history.search("", 10);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].entry.timestamp, 7);
+        assert_eq!(found[0].entry.rect, (11, 21, 2, 2));
+        assert_eq!(found[0].entry.text(), "annotated");
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
@@ -1,19 +1,24 @@
 mod capture_cache;
+pub mod history;
 pub mod img_util;
 mod monitor;
 mod platform;
 pub mod shotter_record;
 
 use crate::capture_cache::CaptureCache;
+use crate::history::{unix_timestamp, CaptureSource, ScreenshotHistory};
 use crate::monitor::{capture_all, current_configs, mask_label, sorted_configs, MonitorConfig};
+pub use crate::monitor::{capture_region, CaptureRegion};
 use crate::platform::{disable_window_animation, prepare_overlay_window, raise_overlay_window};
 use crate::shotter_record::{ShotterConfig, ShotterRecord};
 use image::{DynamicImage, RgbaImage};
 use std::error::Error;
+use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::Arc;
 use std::thread;
+use tauri::path::BaseDirectory;
 use tauri::{Emitter, Manager, PhysicalPosition, WebviewUrl, WebviewWindowBuilder};
 use tauri_plugin_global_shortcut::Shortcut;
 use xcap::Monitor;
@@ -78,6 +83,7 @@ pub struct ScreenShotter {
     app_handle: Option<tauri::AppHandle>,
     capture_cache: CaptureCache,
     shotter_record: ShotterRecord,
+    history: ScreenshotHistory,
     max_pin_id: u32,
     current_monitors: Vec<MonitorConfig>,
     screenshot_session_id: u32,
@@ -174,6 +180,7 @@ impl ScreenShotter {
             app_handle: None,
             capture_cache: CaptureCache::new(),
             shotter_record: ShotterRecord::new(),
+            history: ScreenshotHistory::new(),
             max_pin_id: 0,
             current_monitors: Vec::new(),
             screenshot_session_id: 0,
@@ -282,6 +289,10 @@ impl ScreenShotter {
         self.shotter_record.del_shotter(id)
     }
 
+    pub fn history(&self) -> ScreenshotHistory {
+        self.history.clone()
+    }
+
     pub fn new_pin(
         &mut self,
         monitor_pos: (i32, i32),
@@ -290,6 +301,22 @@ impl ScreenShotter {
         offset: (i32, i32),
         mask_label: String,
     ) -> Result<(), Box<dyn Error>> {
+        let captured_at = unix_timestamp();
+        // Hold on to the capture now: loading the pin clears the capture cache.
+        let history_capture = ScreenshotHistory::is_enabled()
+            .then(|| self.capture_cache.get(&mask_label))
+            .flatten()
+            .map(|capture| {
+                let source = CaptureSource {
+                    timestamp: captured_at,
+                    monitor_id: self.monitor_id(&mask_label),
+                    monitor_pos,
+                    monitor_size,
+                    rect,
+                };
+                (capture, source)
+            });
+
         let config = ShotterConfig {
             monitor_pos,
             monitor_size,
@@ -299,6 +326,7 @@ impl ScreenShotter {
             zoom_factor: 100,
             mask_label,
             minimized: false,
+            captured_at: Some(captured_at),
         };
 
         let pin_id = self.max_pin_id;
@@ -317,6 +345,9 @@ impl ScreenShotter {
         }
         let pin_label = format!("sspin-{pin_id}");
         self.max_pin_id = self.max_pin_id.saturating_add(1);
+        if let Some((capture, source)) = history_capture {
+            self.record_history(capture, source);
+        }
         if let Err(error) = self.app_handle()?.emit_to(&pin_label, "show-pin", ()) {
             // The pin page also loads immediately on mount, so a missed wake-up is recoverable.
             log::warn!("Failed to emit show-pin to {pin_label}: {error}");
@@ -381,6 +412,53 @@ impl ScreenShotter {
         self.max_pin_id = max_id.saturating_add(1);
     }
 
+    // Records an image saved from pin `id` under the capture the pin was made from.
+    pub fn record_saved_pin(&self, id: u32, img: DynamicImage) {
+        if !ScreenshotHistory::is_enabled() {
+            return;
+        }
+        let Some(record) = self.shotter_record.get_record(id) else {
+            return;
+        };
+
+        let source = CaptureSource {
+            timestamp: record.captured_at.unwrap_or_else(unix_timestamp),
+            monitor_id: self.monitor_id(&record.mask_label),
+            monitor_pos: record.monitor_pos,
+            monitor_size: record.monitor_size,
+            rect: record.rect,
+        };
+        match self.model_path() {
+            Ok(model_path) => {
+                let _record_task = self.history.record_saved(img, source, model_path);
+            }
+            Err(error) => log::warn!("Failed to resolve OCR model for screenshot history: {error}"),
+        }
+    }
+
+    fn record_history(&self, capture: Arc<RgbaImage>, source: CaptureSource) {
+        match self.model_path() {
+            Ok(model_path) => {
+                let _record_task = self.history.record_capture(capture, source, model_path);
+            }
+            Err(error) => log::warn!("Failed to resolve OCR model for screenshot history: {error}"),
+        }
+    }
+
+    fn model_path(&self) -> Result<PathBuf, Box<dyn Error>> {
+        Ok(self
+            .app_handle()?
+            .path()
+            .resolve("assets/model", BaseDirectory::Resource)?)
+    }
+
+    fn monitor_id(&self, mask_label: &str) -> Option<u32> {
+        self.current_monitors
+            .iter()
+            .find(|config| crate::monitor::mask_label(config.id) == mask_label)
+            .map(|config| config.id)
+    }
+
     fn app_handle(&self) -> Result<&tauri::AppHandle, Box<dyn Error>> {
         self.app_handle
             .as_ref()
@@ -1,4 +1,7 @@
+use image::imageops::{self, FilterType};
 use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+use std::cmp;
 use std::collections::HashMap;
 use std::error::Error;
 use std::sync::mpsc;
@@ -31,6 +34,146 @@ impl MonitorConfig {
     }
 }
 
+// A rectangle in the coordinate space the monitors report their positions in.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CaptureRegion {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl CaptureRegion {
+    fn right(&self) -> i64 {
+        self.x as i64 + self.width as i64
+    }
+
+    fn bottom(&self) -> i64 {
+        self.y as i64 + self.height as i64
+    }
+
+    fn of_monitor(config: &MonitorConfig) -> CaptureRegion {
+        CaptureRegion {
+            x: config.x,
+            y: config.y,
+            width: config.width,
+            height: config.height,
+        }
+    }
+
+    fn intersect(&self, other: &CaptureRegion) -> Option<CaptureRegion> {
+        let x = cmp::max(self.x, other.x);
+        let y = cmp::max(self.y, other.y);
+        let right = cmp::min(self.right(), other.right());
+        let bottom = cmp::min(self.bottom(), other.bottom());
+        if right <= x as i64 || bottom <= y as i64 {
+            return None;
+        }
+
+        Some(CaptureRegion {
+            x,
+            y,
+            width: (right - x as i64) as u32,
+            height: (bottom - y as i64) as u32,
+        })
+    }
+
+    fn union(&self, other: &CaptureRegion) -> CaptureRegion {
+        let x = cmp::min(self.x, other.x);
+        let y = cmp::min(self.y, other.y);
+        CaptureRegion {
+            x,
+            y,
+            width: (cmp::max(self.right(), other.right()) - x as i64) as u32,
+            height: (cmp::max(self.bottom(), other.bottom()) - y as i64) as u32,
+        }
+    }
+}
+
+// Captures a region spanning any number of monitors without showing the mask
+// windows, or the whole desktop when no region is given.
+pub fn capture_region(region: Option<CaptureRegion>) -> Result<RgbaImage, Box<dyn Error>> {
+    let monitors = Monitor::all()?;
+    let configs = monitors
+        .iter()
+        .map(MonitorConfig::from_monitor)
+        .collect::<Result<Vec<_>, _>>()?;
+    let captures = capture_all(monitors)?;
+
+    let region = match region {
+        Some(region) => region,
+        None => configs
+            .iter()
+            .map(CaptureRegion::of_monitor)
+            .reduce(|bounds, monitor| bounds.union(&monitor))
+            .ok_or("No monitors available for screenshot capture")?,
+    };
+    compose_region(region, &configs, &captures)
+}
+
+// Stitches the parts of the monitor captures inside `region`. Monitors may
+// capture at different pixel densities, the output uses the highest one.
+fn compose_region(
+    region: CaptureRegion,
+    configs: &[MonitorConfig],
+    captures: &HashMap<String, RgbaImage>,
+) -> Result<RgbaImage, Box<dyn Error>> {
+    let parts = configs
+        .iter()
+        .filter_map(|config| {
+            let image = captures.get(&mask_label(config.id))?;
+            let bounds = region.intersect(&CaptureRegion::of_monitor(config))?;
+            let scale = image.width() as f32 / cmp::max(config.width, 1) as f32;
+            Some((config, image, bounds, scale))
+        })
+        .collect::<Vec<_>>();
+    if parts.is_empty() {
+        return Err("Capture region is outside of all monitors".into());
+    }
+
+    let scale = parts
+        .iter()
+        .map(|(_, _, _, scale)| *scale)
+        .fold(f32::MIN_POSITIVE, f32::max);
+    let mut output = RgbaImage::new(
+        scale_length(region.width, scale),
+        scale_length(region.height, scale),
+    );
+    for (config, image, bounds, monitor_scale) in parts {
+        let part = imageops::crop_imm(
+            image,
+            scale_length((bounds.x - config.x) as u32, monitor_scale),
+            scale_length((bounds.y - config.y) as u32, monitor_scale),
+            scale_length(bounds.width, monitor_scale),
+            scale_length(bounds.height, monitor_scale),
+        )
+        .to_image();
+        let part = if monitor_scale == scale {
+            part
+        } else {
+            imageops::resize(
+                &part,
+                scale_length(bounds.width, scale),
+                scale_length(bounds.height, scale),
+                FilterType::Triangle,
+            )
+        };
+
+        imageops::replace(
+            &mut output,
+            &part,
+            scale_length((bounds.x - region.x) as u32, scale) as i64,
+            scale_length((bounds.y - region.y) as u32, scale) as i64,
+        );
+    }
+
+    Ok(output)
+}
+
+fn scale_length(length: u32, scale: f32) -> u32 {
+    (length as f32 * scale).round() as u32
+}
+
 pub(crate) fn mask_label(id: u32) -> String {
     format!("ssmask-{id}")
 }
@@ -160,3 +303,60 @@ fn capture_current_monitor(monitor: Monitor) -> Result<(String, RgbaImage), Stri
         .map_err(|error| format!("monitor {id}: {error}"))?;
     Ok((mask_label(id), image))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(id: u32, x: i32, width: u32, scale_factor: f32) -> MonitorConfig {
+        MonitorConfig {
+            id,
+            x,
+            y: 0,
+            width,
+            height: 10,
+            scale_factor,
+        }
+    }
+
+    fn filled(width: u32, height: u32, value: u8) -> RgbaImage {
+        RgbaImage::from_pixel(width, height, image::Rgba([value, value, value, 255]))
+    }
+
+    #[test]
+    fn compose_region_stitches_monitors_at_the_highest_density() {
+        let configs = vec![monitor(1, 0, 10, 1.0), monitor(2, 10, 10, 2.0)];
+        let captures = HashMap::from([
+            (mask_label(1), filled(10, 10, 50)),
+            (mask_label(2), filled(20, 20, 200)),
+        ]);
+        let region = CaptureRegion {
+            x: 5,
+            y: 0,
+            width: 10,
+            height: 5,
+        };
+
+        let image = compose_region(region, &configs, &captures).unwrap();
+
+        assert_eq!(image.dimensions(), (20, 10));
+        assert_eq!(image.get_pixel(0, 0)[0], 50);
+        assert_eq!(image.get_pixel(9, 9)[0], 50);
+        assert_eq!(image.get_pixel(10, 0)[0], 200);
+        assert_eq!(image.get_pixel(19, 9)[0], 200);
+    }
+
+    #[test]
+    fn compose_region_rejects_regions_outside_all_monitors() {
+        let configs = vec![monitor(1, 0, 10, 1.0)];
+        let captures = HashMap::from([(mask_label(1), filled(10, 10, 50))]);
+        let region = CaptureRegion {
+            x: 20,
+            y: 0,
+            width: 5,
+            height: 5,
+        };
+
+        assert!(compose_region(region, &configs, &captures).is_err());
+    }
+}
@@ -21,6 +21,9 @@ pub struct ShotterConfig {
     pub zoom_factor: u32,
     pub mask_label: String,
     pub minimized: bool,
+    // When the screen was captured, matching the screenshot history timestamp.
+    #[serde(default)]
+    pub captured_at: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -28,7 +28,7 @@ impl ExcludedDirs {
         self.matches_configured_path(path) || self.has_excluded_name_component(path)
     }
 
-    #[cfg(target_os = "macos")]
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
     pub fn is_excluded_parent_path(&self, path: &Path) -> bool {
         path.parent()
             .is_some_and(|parent| self.is_excluded_path(parent))
@@ -73,7 +73,7 @@ fn parse_excluded_dirs(value: &str, home: Option<&Path>) -> ExcludedDirs {
     ExcludedDirs { names, paths }
 }
 
-fn expand_home(entry: &str, home: Option<&Path>) -> String {
+pub(super) fn expand_home(entry: &str, home: Option<&Path>) -> String {
     let Some(home) = home else {
         return entry.to_string();
     };
@@ -99,7 +99,7 @@ fn is_path_entry(entry: &str) -> bool {
         || entry.contains('\\')
 }
 
-fn normalize_path(path: &Path) -> PathBuf {
+pub(super) fn normalize_path(path: &Path) -> PathBuf {
     let mut normalized = PathBuf::new();
 
     for component in path.components() {
@@ -117,7 +117,7 @@ fn normalize_path(path: &Path) -> PathBuf {
     normalized
 }
 
-fn home_dir() -> Option<PathBuf> {
+pub(super) fn home_dir() -> Option<PathBuf> {
     std::env::var_os("HOME").map(PathBuf::from)
 }
 
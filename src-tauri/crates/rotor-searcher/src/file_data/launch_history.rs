@@ -0,0 +1,203 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{fs, io};
+
+use rotor_platform::file_util;
+
+use super::volume::RankBoosts;
+
+const LAUNCH_HISTORY_FILE: &str = "launch_history.toml";
+// A launch loses half of its weight every week.
+const HALF_LIFE_MS: f64 = 7.0 * 24.0 * 60.0 * 60.0 * 1000.0;
+const MIN_SCORE: f64 = 0.05;
+const MAX_ENTRIES: usize = 512;
+const RANK_BOOST_SCALE: f64 = 20.0;
+const MAX_RANK_BOOST: i32 = 80;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+struct LaunchEntry {
+    score: f64,
+    last_launched_at: u64,
+}
+
+impl LaunchEntry {
+    fn score_at(&self, now: u64) -> f64 {
+        let elapsed = now.saturating_sub(self.last_launched_at) as f64;
+        self.score * 0.5f64.powf(elapsed / HALF_LIFE_MS)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct LaunchRecord {
+    #[serde(default)]
+    launches: HashMap<String, LaunchEntry>,
+}
+
+// Frecency of files opened from the searcher. Every launch adds one to an
+// exponentially decaying score, so both frequent and recent launches count.
+pub(crate) struct LaunchHistory {
+    record: LaunchRecord,
+    path: Option<PathBuf>,
+}
+
+impl LaunchHistory {
+    pub(crate) fn load() -> LaunchHistory {
+        let path =
+            file_util::get_userdata_path().map(|user_data| user_data.join(LAUNCH_HISTORY_FILE));
+        Self::load_from(path)
+    }
+
+    fn load_from(path: Option<PathBuf>) -> LaunchHistory {
+        let record = match path.as_ref().map(fs::read_to_string) {
+            Some(Ok(record_str)) => toml::from_str(&record_str).unwrap_or_else(|error| {
+                log::warn!("Failed to parse launch history: {error}");
+                LaunchRecord::default()
+            }),
+            Some(Err(error)) if error.kind() != io::ErrorKind::NotFound => {
+                log::warn!("Failed to read launch history: {error}");
+                LaunchRecord::default()
+            }
+            _ => LaunchRecord::default(),
+        };
+
+        LaunchHistory { record, path }
+    }
+
+    pub(crate) fn record(&mut self, file_path: &str, now: u64) {
+        let score = self
+            .record
+            .launches
+            .get(file_path)
+            .map(|entry| entry.score_at(now))
+            .unwrap_or(0.0);
+        self.record.launches.insert(
+            file_path.to_string(),
+            LaunchEntry {
+                score: score + 1.0,
+                last_launched_at: now,
+            },
+        );
+        self.prune(now);
+
+        if let Err(error) = self.save() {
+            log::warn!("Failed to save launch history: {error}");
+        }
+    }
+
+    // Extra rank for a search result, growing logarithmically with the score.
+    pub(crate) fn rank_boost(&self, file_path: &str, now: u64) -> i32 {
+        let Some(entry) = self.record.launches.get(file_path) else {
+            return 0;
+        };
+
+        let boost = (entry.score_at(now).ln_1p() * RANK_BOOST_SCALE).round() as i32;
+        boost.min(MAX_RANK_BOOST)
+    }
+
+    // Boosts of every launched file, for the volumes to rank their matches.
+    pub(crate) fn rank_boosts(&self, now: u64) -> RankBoosts {
+        RankBoosts::new(
+            self.record
+                .launches
+                .keys()
+                .map(|file_path| (file_path.clone(), self.rank_boost(file_path, now)))
+                .filter(|(_, boost)| *boost > 0),
+        )
+    }
+
+    fn prune(&mut self, now: u64) {
+        self.record
+            .launches
+            .retain(|_, entry| entry.score_at(now) >= MIN_SCORE);
+
+        if self.record.launches.len() > MAX_ENTRIES {
+            let mut scores = self
+                .record
+                .launches
+                .iter()
+                .map(|(path, entry)| (entry.score_at(now), path.clone()))
+                .collect::<Vec<_>>();
+            scores.sort_by(|a, b| b.0.total_cmp(&a.0));
+            for (_, path) in scores.into_iter().skip(MAX_ENTRIES) {
+                self.record.launches.remove(&path);
+            }
+        }
+    }
+
+    fn save(&self) -> Result<(), Box<dyn Error>> {
+        let Some(path) = self.path.as_ref() else {
+            return Ok(());
+        };
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, toml::to_string_pretty(&self.record)?)?;
+        Ok(())
+    }
+}
+
+pub(crate) fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+
+    #[test]
+    fn frequent_launches_rank_higher_and_decay_over_time() {
+        let mut history = LaunchHistory::load_from(None);
+        let now = 100 * DAY_MS;
+
+        history.record("/apps/editor.desktop", now);
+        for _ in 0..20 {
+            history.record("/apps/browser.desktop", now);
+        }
+
+        let editor = history.rank_boost("/apps/editor.desktop", now);
+        let browser = history.rank_boost("/apps/browser.desktop", now);
+        assert!(editor > 0);
+        assert!(browser > editor);
+        assert_eq!(history.rank_boost("/apps/unknown.desktop", now), 0);
+
+        let later = history.rank_boost("/apps/browser.desktop", now + 14 * DAY_MS);
+        assert!(later < browser);
+        assert!(history.rank_boost("/apps/browser.desktop", now) <= MAX_RANK_BOOST);
+    }
+
+    #[test]
+    fn prune_drops_faded_entries() {
+        let mut history = LaunchHistory::load_from(None);
+
+        history.record("/old.txt", 0);
+        history.record("/new.txt", 365 * DAY_MS);
+
+        assert!(!history.record.launches.contains_key("/old.txt"));
+        assert!(history.record.launches.contains_key("/new.txt"));
+    }
+
+    #[test]
+    fn save_and_load_round_trips_entries() {
+        let path =
+            std::env::temp_dir().join(format!("rotor-launch-history-{}.toml", std::process::id()));
+        let mut history = LaunchHistory::load_from(Some(path.clone()));
+        history.record("C:\\Apps\\Editor.lnk", DAY_MS);
+
+        let restored = LaunchHistory::load_from(Some(path.clone()));
+        assert_eq!(
+            restored.record.launches.get("C:\\Apps\\Editor.lnk"),
+            history.record.launches.get("C:\\Apps\\Editor.lnk")
+        );
+
+        let _ = fs::remove_file(path);
+    }
+}
@@ -1,6 +1,9 @@
 mod volume;
 
 mod excluded_dirs;
+mod launch_history;
+#[cfg(target_os = "linux")]
+mod search_roots;
 use std::collections::VecDeque;
 use std::sync::{
     atomic::{AtomicBool, Ordering},
@@ -11,12 +14,16 @@ use std::time::{Duration, Instant};
 #[cfg(target_os = "windows")]
 use windows::Win32::Storage::FileSystem;
 
+use launch_history::LaunchHistory;
 #[cfg(target_os = "windows")]
 use rotor_platform::sys_util::is_ntfs;
-#[cfg(target_os = "macos")]
+#[cfg(any(target_os = "macos", target_os = "linux"))]
 use volume::default_volume::Volume;
 #[cfg(target_os = "windows")]
 use volume::ntfs_volume::Volume;
+use volume::search_match::MatchMode;
+use volume::search_query::SearchQuery;
+use volume::RankBoosts;
 pub use volume::{SearchResultItem, VolumeIndexStatus};
 
 pub enum SearcherMessage {
@@ -25,6 +32,16 @@ pub enum SearcherMessage {
     Find(String),
     Release,
     Status(mpsc::Sender<SearchIndexStatus>),
+    Launch(String),
+    Query(SearchRequest),
+}
+
+// A one-off search that replies with the best `limit` results instead of
+// paging them to the searcher window.
+pub struct SearchRequest {
+    pub query: String,
+    pub limit: u8,
+    pub reply: mpsc::Sender<Result<Vec<SearchResultItem>, String>>,
 }
 
 #[derive(Clone, Debug, serde::Serialize)]
@@ -87,8 +104,12 @@ struct VolumePack {
 }
 
 struct VolumeFindTask {
-    filename: String,
+    query: Arc<SearchQuery>,
+    boosts: Arc<RankBoosts>,
     batch: u8,
+    // Continue after the previous page of the same query instead of searching
+    // from the start.
+    paged: bool,
     cancel: Arc<AtomicBool>,
     result_sender: mpsc::Sender<Option<Vec<SearchResultItem>>>,
 }
@@ -100,15 +121,23 @@ struct SearchTask {
 }
 
 impl SearchTask {
-    fn dispatch(volume_packs: &[VolumePack], filename: String, batch: u8) -> SearchTask {
+    fn dispatch(
+        volume_packs: &[VolumePack],
+        query: Arc<SearchQuery>,
+        boosts: Arc<RankBoosts>,
+        batch: u8,
+        paged: bool,
+    ) -> SearchTask {
         let cancel = Arc::new(AtomicBool::new(false));
         let (result_sender, result_receiver) = mpsc::channel::<Option<Vec<SearchResultItem>>>();
         let mut pending = 0;
 
         for VolumePack { find_sender, .. } in volume_packs {
             let task = VolumeFindTask {
-                filename: filename.clone(),
+                query: query.clone(),
+                boosts: boosts.clone(),
                 batch,
+                paged,
                 cancel: cancel.clone(),
                 result_sender: result_sender.clone(),
             };
@@ -153,6 +182,10 @@ impl SearchTask {
     }
 }
 
+// Receives the query, a page of results, whether the page extends the previous
+// one and an error message when the query cannot be searched.
+type FindResultCallback = Box<dyn Fn(String, Vec<SearchResultItem>, bool, Option<String>) + Send>;
+
 pub struct SearchResult {
     pub items: Vec<SearchResultItem>,
     pub query: String,
@@ -161,12 +194,14 @@ pub struct SearchResult {
 pub struct FileData {
     vols: Vec<String>,
     finding_name: String,
+    finding_query: Arc<SearchQuery>,
     finding_result: SearchResult,
     volume_packs: Vec<VolumePack>,
+    launch_history: LaunchHistory,
     state: SharedFileState,
     show_num: usize,
     batch: u8,
-    find_result_callback: Box<dyn Fn(String, Vec<SearchResultItem>, bool) + Send>,
+    find_result_callback: FindResultCallback,
     state_change_callback: Option<Box<dyn Fn(String) + Send>>,
 }
 
@@ -177,12 +212,14 @@ impl FileData {
         state: SharedFileState,
     ) -> FileData
     where
-        F: Fn(String, Vec<SearchResultItem>, bool) + Send + 'static,
+        F: Fn(String, Vec<SearchResultItem>, bool, Option<String>) + Send + 'static,
     {
         FileData {
             vols: Vec::new(),
             volume_packs: Vec::new(),
+            launch_history: LaunchHistory::load(),
             finding_name: String::new(),
+            finding_query: Arc::new(SearchQuery::parse("")),
             finding_result: SearchResult {
                 items: Vec::new(),
                 query: String::new(),
@@ -255,6 +292,22 @@ impl FileData {
                             log::warn!("Send search index status failed");
                         }
                     }
+                    Ok(SearcherMessage::Launch(file_path)) => {
+                        file_data.record_launch(&file_path);
+                    }
+                    Ok(SearcherMessage::Query(request)) => match file_data.state() {
+                        FileState::Released => {
+                            wait_deals.push_back(SearcherMessage::Update);
+                            wait_deals.push_back(SearcherMessage::Query(request));
+                        }
+                        FileState::Ready => file_data.query(request),
+                        state => {
+                            let error = format!("Search index is {}", state.as_str());
+                            if request.reply.send(Err(error)).is_err() {
+                                log::warn!("Send search query result failed");
+                            }
+                        }
+                    },
                     Err(_) => {}
                 }
             }
@@ -323,6 +376,19 @@ impl FileData {
             self.vols.push("/Applications".to_string());
             self.vols.push("/System/Applications".to_string());
 
+            self.vols.len() as u8
+        }
+        #[cfg(target_os = "linux")]
+        {
+            self.vols = search_roots::linux_search_roots();
+
+            self.volume_packs.retain(|volume_pack| {
+                if let Ok(volume) = volume_pack.volume.lock() {
+                    return self.vols.contains(&volume.drive);
+                }
+                false
+            });
+
             self.vols.len() as u8
         }
     }
@@ -338,7 +404,7 @@ impl FileData {
             .into_iter()
             .map(SearchResultItem::attach_icon_data)
             .collect();
-        (self.find_result_callback)(filename, update_result, if_increase);
+        (self.find_result_callback)(filename, update_result, if_increase, None);
     }
 
     pub fn find(
@@ -359,18 +425,33 @@ impl FileData {
                 return reply;
             }
         } else {
+            // Parse the query once; paging requests reuse the same AST.
+            self.finding_query =
+                Arc::new(SearchQuery::parse(&filename).with_mode(MatchMode::from_config()));
             self.finding_name = filename.clone();
             need_num = self.batch as usize;
             self.show_num = 0;
             self.finding_result.items.clear();
             self.finding_result.query = filename.clone();
+
+            if let Some(error) = self.finding_query.error() {
+                let error = error.to_string();
+                (self.find_result_callback)(filename, Vec::new(), false, Some(error));
+                return reply;
+            }
         }
 
-        if filename.is_empty() {
+        if self.finding_query.is_empty() {
             return reply;
         }
 
-        let mut task = SearchTask::dispatch(&self.volume_packs, filename.clone(), self.batch);
+        let mut task = SearchTask::dispatch(
+            &self.volume_packs,
+            self.finding_query.clone(),
+            self.rank_boosts(),
+            self.batch,
+            true,
+        );
 
         while task.pending > 0 {
             while let Ok(searcher_msg) = msg_reciever.try_recv() {
@@ -380,6 +461,12 @@ impl FileData {
                             log::warn!("Send search index status failed");
                         }
                     }
+                    SearcherMessage::Launch(file_path) => {
+                        self.record_launch(&file_path);
+                    }
+                    SearcherMessage::Query(request) => {
+                        self.query(request);
+                    }
                     searcher_msg => {
                         task.cancel();
                         task.drain_cancelled();
@@ -409,9 +496,7 @@ impl FileData {
         }
 
         if reply.is_none() {
-            self.finding_result
-                .items
-                .sort_by_key(|item| std::cmp::Reverse(item.rank)); // sort by rank desc
+            sort_by_rank(&self.launch_history, &mut self.finding_result.items);
             let return_result = if self.finding_result.items.len() > self.show_num {
                 let max = std::cmp::min(self.finding_result.items.len(), need_num);
                 self.finding_result.items[self.show_num..max].to_vec()
@@ -424,6 +509,54 @@ impl FileData {
         reply
     }
 
+    fn query(&self, request: SearchRequest) {
+        let query = SearchQuery::parse(&request.query).with_mode(MatchMode::from_config());
+        let result = match query.error() {
+            Some(error) => Err(error.to_string()),
+            None => {
+                let mut task = SearchTask::dispatch(
+                    &self.volume_packs,
+                    Arc::new(query),
+                    self.rank_boosts(),
+                    request.limit,
+                    false,
+                );
+                let mut items = Vec::new();
+                while task.pending > 0 {
+                    match task.result_receiver.recv() {
+                        Ok(op_result) => {
+                            task.pending -= 1;
+                            if let Some(mut result) = op_result {
+                                items.append(&mut result);
+                            }
+                        }
+                        Err(_) => break,
+                    }
+                }
+
+                sort_by_rank(&self.launch_history, &mut items);
+                items.truncate(request.limit as usize);
+                Ok(items)
+            }
+        };
+
+        if request.reply.send(result).is_err() {
+            log::warn!("Send search query result failed");
+        }
+    }
+
+    fn rank_boosts(&self) -> Arc<RankBoosts> {
+        Arc::new(
+            self.launch_history
+                .rank_boosts(launch_history::now_millis()),
+        )
+    }
+
+    fn record_launch(&mut self, file_path: &str) {
+        self.launch_history
+            .record(file_path, launch_history::now_millis());
+    }
+
     pub fn init_volumes(&mut self) -> bool {
         self.volume_packs.clear();
         self.update_valid_vols();
@@ -451,7 +584,14 @@ impl FileData {
                         worker_volume
                             .lock()
                             .unwrap_or_else(|poisoned| poisoned.into_inner())
-                            .find(task.filename, task.batch, task.cancel, task.result_sender);
+                            .find(
+                                task.query,
+                                task.boosts,
+                                task.batch,
+                                task.paged,
+                                task.cancel,
+                                task.result_sender,
+                            );
                     }
                 });
 
@@ -571,6 +711,14 @@ impl FileData {
     }
 }
 
+// sort by rank desc, boosted by how often and how recently a file was launched
+fn sort_by_rank(launch_history: &LaunchHistory, items: &mut [SearchResultItem]) {
+    let now = launch_history::now_millis();
+    items.sort_by_cached_key(|item| {
+        std::cmp::Reverse(item.rank as i32 + launch_history.rank_boost(&item.file_path, now))
+    });
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -581,7 +729,7 @@ mod tests {
         let observed_states = Arc::new(Mutex::new(Vec::new()));
         let callback_states = observed_states.clone();
         let file_data = FileData::new(
-            |_, _, _| {},
+            |_, _, _, _| {},
             Some(Box::new(move |state| {
                 callback_states
                     .lock()
@@ -0,0 +1,131 @@
+use std::path::{Path, PathBuf};
+
+use rotor_common::AppConfig;
+
+use super::excluded_dirs::{expand_home, home_dir, normalize_path};
+
+pub const SEARCH_MOUNT_POINTS_CONFIG_KEY: &str = "search_mount_points";
+
+const LINUX_APPLICATION_DIRS: [&str; 2] =
+    ["/usr/share/applications", "~/.local/share/applications"];
+
+// Directories indexed on Linux: the home directory, the desktop entry folders
+// and any mount points configured by the user.
+pub fn linux_search_roots() -> Vec<String> {
+    let home = home_dir();
+    if home.is_none() {
+        log::warn!("Failed to read HOME for search volumes");
+    }
+
+    let mount_points = AppConfig::lock_global()
+        .get(SEARCH_MOUNT_POINTS_CONFIG_KEY)
+        .cloned()
+        .unwrap_or_default();
+
+    let mut entries = Vec::new();
+    if let Some(home) = home.as_deref() {
+        entries.push(home.to_string_lossy().into_owned());
+    }
+    entries.extend(LINUX_APPLICATION_DIRS.iter().map(|dir| dir.to_string()));
+    entries.extend(parse_mount_point_entries(&mount_points));
+
+    collect_search_roots(&entries, home.as_deref(), |path| path.is_dir())
+}
+
+fn parse_mount_point_entries(value: &str) -> Vec<String> {
+    value
+        .lines()
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty() && !entry.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+// Expands and de-duplicates root entries. A root nested inside an earlier root
+// is skipped unless it sits below a hidden directory, which the walker of the
+// enclosing root never enters (e.g. `~/.local/share/applications`).
+fn collect_search_roots(
+    entries: &[String],
+    home: Option<&Path>,
+    exists: impl Fn(&Path) -> bool,
+) -> Vec<String> {
+    let mut roots: Vec<PathBuf> = Vec::new();
+
+    for entry in entries {
+        let path = normalize_path(Path::new(&expand_home(entry, home)));
+        if !path.is_absolute() {
+            log::warn!("Skip relative search mount point: {entry}");
+            continue;
+        }
+
+        if !exists(&path) {
+            log::info!("Skip missing search root: {}", path.display());
+            continue;
+        }
+
+        let covered = roots.iter().any(|root| {
+            path.strip_prefix(root)
+                .is_ok_and(|relative| !has_hidden_component(relative))
+        });
+        if !covered {
+            roots.push(path);
+        }
+    }
+
+    roots
+        .into_iter()
+        .map(|root| root.to_string_lossy().into_owned())
+        .collect()
+}
+
+fn has_hidden_component(path: &Path) -> bool {
+    path.components().any(|component| {
+        component
+            .as_os_str()
+            .to_str()
+            .is_some_and(|segment| segment.starts_with('.'))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roots(entries: &[&str]) -> Vec<String> {
+        let entries = entries
+            .iter()
+            .map(|entry| entry.to_string())
+            .collect::<Vec<_>>();
+        collect_search_roots(&entries, Some(Path::new("/home/alice")), |_| true)
+    }
+
+    #[test]
+    fn expands_home_and_skips_nested_roots() {
+        assert_eq!(
+            roots(&["~", "~/Documents", "/mnt/data", "/mnt/data/"]),
+            vec!["/home/alice".to_string(), "/mnt/data".to_string()]
+        );
+    }
+
+    #[test]
+    fn keeps_roots_below_hidden_directories() {
+        assert_eq!(
+            roots(&["/home/alice", "~/.local/share/applications"]),
+            vec![
+                "/home/alice".to_string(),
+                "/home/alice/.local/share/applications".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_comments_blank_lines_and_relative_entries() {
+        let entries = parse_mount_point_entries("# media\n\n /media/usb \nrelative/dir\n");
+        assert_eq!(entries, vec!["/media/usb", "relative/dir"]);
+
+        assert_eq!(
+            collect_search_roots(&entries, None, |_| true),
+            vec!["/media/usb".to_string()]
+        );
+    }
+}
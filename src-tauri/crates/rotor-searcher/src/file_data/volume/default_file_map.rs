@@ -6,20 +6,26 @@ use std::io::{self, Read, Write};
 use std::path::{Component, Path, PathBuf, MAIN_SEPARATOR};
 use std::sync::atomic::{AtomicBool, Ordering};
 
-use super::search_match::{make_filter, match_indexed_name, prepare_search_name, SearchAlias};
-use super::{read_string, read_u16, read_u32, read_u8, SearchResultItem};
+use super::search_match::{make_filter, prepare_search_name, SearchAlias};
+use super::search_query::{QueryCandidate, SearchQuery};
+use super::top_results::TopResults;
+use super::{
+    read_string, read_u16, read_u32, read_u8, FileAttributes, RankBoosts, SearchResultItem,
+};
 use rotor_platform::file_util;
 
 type DirId = u32;
 
 const ROOT_DIR_ID: DirId = 0;
 const INDEX_MAGIC: [u8; 4] = *b"RDFM";
-const INDEX_VERSION: u8 = 2;
+const INDEX_VERSION: u8 = 3;
 
 #[derive(Clone)]
 struct DirNode {
     parent_id: DirId,
     name: String,
+    // Characters of the whole path down to this directory, see `make_filter`.
+    filter: u32,
 }
 
 #[derive(Hash, PartialEq, Eq)]
@@ -39,6 +45,7 @@ impl DirectoryTree {
             nodes: vec![DirNode {
                 parent_id: ROOT_DIR_ID,
                 name: String::new(),
+                filter: 0,
             }],
             lookup: HashMap::default(),
         }
@@ -89,7 +96,12 @@ impl DirectoryTree {
             ));
         }
 
-        self.nodes.push(DirNode { parent_id, name });
+        let filter = self.filter(parent_id) | make_filter(&name);
+        self.nodes.push(DirNode {
+            parent_id,
+            name,
+            filter,
+        });
         Ok(dir_id)
     }
 
@@ -126,6 +138,13 @@ impl DirectoryTree {
         Some(path.to_string_lossy().into_owned())
     }
 
+    fn filter(&self, dir_id: DirId) -> u32 {
+        self.nodes
+            .get(dir_id as usize)
+            .map(|node| node.filter)
+            .unwrap_or(0)
+    }
+
     fn intern_child(&mut self, parent_id: DirId, name: String) -> DirId {
         let key = DirLookupKey {
             parent_id,
@@ -145,7 +164,12 @@ impl DirectoryTree {
                 return parent_id;
             }
         };
-        self.nodes.push(DirNode { parent_id, name });
+        let filter = self.filter(parent_id) | make_filter(&name);
+        self.nodes.push(DirNode {
+            parent_id,
+            name,
+            filter,
+        });
         self.lookup.insert(key, dir_id);
         dir_id
     }
@@ -218,6 +242,7 @@ pub struct FileView {
     pub rank: i8,
     pub aliases: Option<Box<[String]>>,
     pub search_aliases: Option<Box<[SearchAlias]>>,
+    pub attributes: FileAttributes,
 }
 
 impl PartialEq for FileView {
@@ -258,13 +283,19 @@ impl FileMap {
         }
     }
 
-    pub fn insert(&mut self, file_name: String, path: String) {
+    pub fn insert(&mut self, file_name: String, path: String, attributes: FileAttributes) {
         let parent_id = self.dir_tree.intern_path(Path::new(&path));
         let aliases = self.app_aliases(&file_name, &path);
-        self.insert_with_aliases(file_name, parent_id, aliases);
+        self.insert_with_aliases(file_name, parent_id, aliases, attributes);
     }
 
-    fn insert_with_aliases(&mut self, file_name: String, parent_id: DirId, aliases: Vec<String>) {
+    fn insert_with_aliases(
+        &mut self,
+        file_name: String,
+        parent_id: DirId,
+        aliases: Vec<String>,
+        attributes: FileAttributes,
+    ) {
         let rank = Self::get_file_rank(&file_name);
         let prepared = prepare_search_name(
             &file_name,
@@ -278,16 +309,20 @@ impl FileMap {
             rank,
             aliases: (!aliases.is_empty()).then(|| aliases.into_boxed_slice()),
             search_aliases: prepared.aliases,
+            attributes,
         });
     }
 
     fn app_aliases(&self, file_name: &str, path: &str) -> Vec<String> {
-        if !file_name.ends_with(".app") {
-            return Vec::new();
-        }
-
         let app_path = Path::new(path).join(file_name);
-        let Ok(trans_names) = file_util::get_app_trans_names(&app_path) else {
+        let trans_names = if file_name.ends_with(".app") {
+            file_util::get_app_trans_names(&app_path)
+        } else if file_name.ends_with(".desktop") {
+            file_util::get_desktop_entry_names(&app_path)
+        } else {
+            return Vec::new();
+        };
+        let Ok(trans_names) = trans_names else {
             return Vec::new();
         };
 
@@ -314,60 +349,73 @@ impl FileMap {
             rank,
             aliases: None,
             search_aliases: None,
+            attributes: FileAttributes::default(),
         };
         self.main_set.remove(&file);
     }
 
+    // Returns the matches after the first `offset` ones by rank, including
+    // the launch boosts.
     pub fn search(
         &self,
-        query: &str,
-        last_search_num: usize,
+        query: &SearchQuery,
+        boosts: &RankBoosts,
+        offset: usize,
         batch: u8,
         cancel: &AtomicBool,
-    ) -> (Option<Vec<SearchResultItem>>, usize) {
-        let mut result = Vec::new();
-        let mut find_num = 0;
-        let mut search_num: usize = 0;
-        let query_lower = query.to_lowercase();
-        let query_filter = make_filter(&query_lower);
-
-        let file_map_iter = self.iter().rev().skip(last_search_num);
-        for file in file_map_iter {
+    ) -> Option<Vec<SearchResultItem>> {
+        let mut results = TopResults::new(offset, batch);
+        let ceilings = boosts.ceilings(Self::get_file_rank);
+
+        for file in self.iter().rev() {
             if cancel.load(Ordering::Relaxed) {
-                return (None, 0);
+                return None;
             }
-            search_num += 1;
-
-            if let Some(file_alias) = match_indexed_name(
-                &file.file_name,
-                file.aliases.as_deref(),
-                file.search_aliases.as_deref(),
-                file.filter,
-                &query_lower,
-                query_filter,
-            ) {
-                let Some((path, file_path)) = self.result_paths(file.parent_id, &file.file_name)
-                else {
-                    continue;
-                };
+            if results.is_complete(file.rank as i32 + ceilings.at(file.rank)) {
+                break;
+            }
+
+            let candidate = QueryCandidate {
+                file_name: &file.file_name,
+                display_aliases: file.aliases.as_deref(),
+                search_aliases: file.search_aliases.as_deref(),
+                filter: file.filter,
+                dir_filter: self.dir_tree.filter(file.parent_id),
+                attributes: file.attributes,
+            };
+            let Some(name_match) = query.matches(&candidate, || self.dir_tree.path(file.parent_id))
+            else {
+                continue;
+            };
+            let rank = file.rank.saturating_add(name_match.score);
+            let boost = boosts.get(&file.file_name, || {
+                self.result_paths(file.parent_id, &file.file_name)
+                    .map(|(_, file_path)| file_path)
+            });
+            if !results.accepts(rank as i32 + boost) {
+                continue;
+            }
+            let Some((path, file_path)) = self.result_paths(file.parent_id, &file.file_name) else {
+                continue;
+            };
 
-                result.push(SearchResultItem {
+            results.push(
+                rank as i32 + boost,
+                SearchResultItem {
                     path,
                     file_path,
                     file_name: file.file_name.clone(),
-                    rank: file.rank,
+                    rank,
                     icon_data: None,
-                    alias: file_alias,
-                });
-
-                find_num += 1;
-                if find_num >= batch {
-                    break;
-                }
-            }
+                    alias: name_match.alias,
+                    size: file.attributes.size,
+                    modified_at: file.attributes.modified_at,
+                    is_dir: file.attributes.is_dir,
+                },
+            );
         }
 
-        (Some(result), search_num)
+        Some(results.into_page())
     }
 
     fn result_paths(&self, parent_id: DirId, file_name: &str) -> Option<(String, String)> {
@@ -406,6 +454,8 @@ impl FileMap {
                 writer.write_all(&(alias.len() as u16).to_be_bytes())?;
                 writer.write_all(alias.as_bytes())?;
             }
+
+            file.attributes.write_to(&mut writer)?;
         }
         writer.flush()?;
 
@@ -466,7 +516,8 @@ impl FileMap {
                 aliases.push(alias);
             }
 
-            next.insert_with_aliases(file_name, parent_id, aliases);
+            let attributes = FileAttributes::read_from(&mut reader)?;
+            next.insert_with_aliases(file_name, parent_id, aliases, attributes);
         }
 
         *self = next;
@@ -496,7 +547,10 @@ impl FileMap {
         let file_name_lower = file_name.to_lowercase();
         if file_name_lower.ends_with(".exe") {
             rank += 10;
-        } else if file_name_lower.ends_with(".app") || file_name_lower.ends_with(".lnk") {
+        } else if file_name_lower.ends_with(".app")
+            || file_name_lower.ends_with(".lnk")
+            || file_name_lower.ends_with(".desktop")
+        {
             rank += 25;
         }
 
@@ -511,13 +565,29 @@ impl FileMap {
 
 #[cfg(test)]
 mod tests {
+    use super::super::search_match::MatchMode;
     use super::*;
     use std::sync::atomic::AtomicBool;
 
     fn search_items(file_map: &FileMap, query: &str) -> Vec<SearchResultItem> {
         let cancel = AtomicBool::new(false);
-        let (result, _) = file_map.search(query, 0, 10, &cancel);
-        result.unwrap_or_default()
+        file_map
+            .search(
+                &SearchQuery::parse(query),
+                &RankBoosts::default(),
+                0,
+                10,
+                &cancel,
+            )
+            .unwrap_or_default()
+    }
+
+    fn file_attributes() -> FileAttributes {
+        FileAttributes {
+            size: Some(2048),
+            modified_at: Some(1_700_000_000_000),
+            is_dir: false,
+        }
     }
 
     fn search_names(file_map: &FileMap, query: &str) -> Vec<String> {
@@ -530,12 +600,118 @@ mod tests {
     #[test]
     fn search_matches_file_name_by_full_pinyin_and_initials() {
         let mut file_map = FileMap::new();
-        file_map.insert("微信.txt".to_string(), "/tmp".to_string());
+        file_map.insert(
+            "微信.txt".to_string(),
+            "/tmp".to_string(),
+            FileAttributes::default(),
+        );
 
         assert_eq!(search_names(&file_map, "weixin"), vec!["微信.txt"]);
         assert_eq!(search_names(&file_map, "wx"), vec!["微信.txt"]);
     }
 
+    #[test]
+    fn search_matches_terms_in_directory_paths() {
+        let mut file_map = FileMap::new();
+        let home = std::env::temp_dir().join("rotor-home");
+        for dir in ["Documents", "Downloads"] {
+            file_map.insert(
+                "report.pdf".to_string(),
+                home.join(dir).to_string_lossy().into_owned(),
+                FileAttributes::default(),
+            );
+        }
+
+        let index_path =
+            std::env::temp_dir().join(format!("rotor-path-terms-{}.fd", std::process::id()));
+        file_map.save(index_path.to_str().unwrap()).unwrap();
+        let mut restored = FileMap::new();
+        restored.read(index_path.to_str().unwrap()).unwrap();
+        let _ = fs::remove_file(index_path);
+
+        for file_map in [&file_map, &restored] {
+            let paths = |query: &str| {
+                search_items(file_map, query)
+                    .into_iter()
+                    .map(|item| item.path)
+                    .collect::<Vec<_>>()
+            };
+            let documents = home.join("Documents").to_string_lossy().into_owned();
+
+            assert_eq!(paths("documents report"), vec![documents.clone()]);
+            assert_eq!(paths("documents/report"), vec![documents]);
+            assert_eq!(paths("report").len(), 2);
+        }
+    }
+
+    #[test]
+    fn fuzzy_search_keeps_strong_matches_behind_a_full_batch() {
+        let mut file_map = FileMap::new();
+        // Each loose subsequence match outranks the typo match statically but
+        // not once the gap penalty is applied.
+        for filler in ['f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'p', 'q'] {
+            file_map.insert(
+                format!("vsc{}doe", filler.to_string().repeat(25)),
+                "/tmp".to_string(),
+                FileAttributes::default(),
+            );
+        }
+        let strong = format!("vscode-{}", "a".repeat(25));
+        file_map.insert(
+            strong.clone(),
+            "/tmp".to_string(),
+            FileAttributes::default(),
+        );
+
+        let cancel = AtomicBool::new(false);
+        let query = SearchQuery::parse("vscdoe").with_mode(MatchMode::Fuzzy);
+        let first_page = file_map
+            .search(&query, &RankBoosts::default(), 0, 10, &cancel)
+            .unwrap();
+        let second_page = file_map
+            .search(&query, &RankBoosts::default(), 10, 10, &cancel)
+            .unwrap();
+
+        assert_eq!(first_page.len(), 10);
+        assert_eq!(first_page[0].file_name, strong);
+        assert_eq!(second_page.len(), 2);
+        assert!(second_page.iter().all(|item| item.file_name != strong));
+    }
+
+    #[test]
+    fn launch_boost_lifts_low_rank_file_over_a_full_batch() {
+        let mut file_map = FileMap::new();
+        for index in 0..11 {
+            file_map.insert(
+                format!("report{index}.txt"),
+                "/tmp".to_string(),
+                FileAttributes::default(),
+            );
+        }
+        let launched = "quarterly-report-for-the-board-final.txt";
+        file_map.insert(
+            launched.to_string(),
+            "/tmp".to_string(),
+            FileAttributes::default(),
+        );
+        let launched_path = Path::new("/tmp")
+            .join(launched)
+            .to_string_lossy()
+            .into_owned();
+
+        let cancel = AtomicBool::new(false);
+        let query = SearchQuery::parse("report");
+        let boosts = RankBoosts::new([(launched_path, 40)]);
+        let boosted = file_map.search(&query, &boosts, 0, 10, &cancel).unwrap();
+        let unboosted = file_map
+            .search(&query, &RankBoosts::default(), 0, 10, &cancel)
+            .unwrap();
+
+        assert_eq!(boosted.len(), 10);
+        assert_eq!(boosted[0].file_name, launched);
+        assert!(unboosted.iter().all(|item| item.file_name != launched));
+    }
+
     #[test]
     fn directory_tree_intern_and_find_round_trip() {
         let mut tree = DirectoryTree::new();
@@ -554,8 +730,16 @@ mod tests {
         let parent = std::env::temp_dir().join("rotor-parent").join("nested");
         let parent_path = parent.to_string_lossy().into_owned();
 
-        file_map.insert("alpha.txt".to_string(), parent_path.clone());
-        file_map.insert("beta.txt".to_string(), parent_path.clone());
+        file_map.insert(
+            "alpha.txt".to_string(),
+            parent_path.clone(),
+            FileAttributes::default(),
+        );
+        file_map.insert(
+            "beta.txt".to_string(),
+            parent_path.clone(),
+            file_attributes(),
+        );
 
         let parent_ids = file_map
             .main_set
@@ -581,8 +765,16 @@ mod tests {
         let index_path =
             std::env::temp_dir().join(format!("rotor-default-file-map-{}.fd", std::process::id()));
 
-        file_map.insert("alpha.txt".to_string(), parent_path.clone());
-        file_map.insert("beta.txt".to_string(), parent_path.clone());
+        file_map.insert(
+            "alpha.txt".to_string(),
+            parent_path.clone(),
+            FileAttributes::default(),
+        );
+        file_map.insert(
+            "beta.txt".to_string(),
+            parent_path.clone(),
+            file_attributes(),
+        );
         file_map
             .save(&index_path.to_string_lossy())
             .expect("save default index");
@@ -599,6 +791,10 @@ mod tests {
             items[0].file_path,
             parent.join("beta.txt").to_string_lossy()
         );
+        assert_eq!(items[0].size, Some(2048));
+        assert_eq!(items[0].modified_at, Some(1_700_000_000_000));
+        assert!(!items[0].is_dir);
+        assert_eq!(search_names(&restored, "size:>1kb"), vec!["beta.txt"]);
 
         let _ = fs::remove_file(index_path);
     }
@@ -610,8 +806,16 @@ mod tests {
         let parent_path = parent.to_string_lossy().into_owned();
 
         assert!(file_map.is_empty());
-        file_map.insert("alpha.txt".to_string(), parent_path.clone());
-        file_map.insert("beta.txt".to_string(), parent_path.clone());
+        file_map.insert(
+            "alpha.txt".to_string(),
+            parent_path.clone(),
+            FileAttributes::default(),
+        );
+        file_map.insert(
+            "beta.txt".to_string(),
+            parent_path.clone(),
+            file_attributes(),
+        );
         assert_eq!(file_map.len(), 2);
 
         file_map.remove("alpha.txt".to_string(), parent_path);
@@ -11,7 +11,11 @@ use walkdir::{DirEntry, WalkDir};
 
 use super::super::excluded_dirs::ExcludedDirs;
 use super::default_file_map::FileMap;
-use super::{index_file_stem, metadata_modified_at, SearchResultItem, VolumeIndexStatus};
+use super::search_query::SearchQuery;
+use super::{
+    index_file_stem, metadata_modified_at, FileAttributes, RankBoosts, SearchResultItem,
+    VolumeIndexStatus,
+};
 use rotor_platform::file_util;
 
 #[derive(Debug, Clone, Copy)]
@@ -24,7 +28,7 @@ pub struct Volume {
     pub drive: String,
     file_map: FileMap,
     last_query: String,
-    last_search_num: usize,
+    last_result_num: usize,
     watcher: Option<RecommendedWatcher>,
     event_receiver: Option<mpsc::Receiver<notify::Result<Event>>>,
     saved_item_count: usize,
@@ -37,7 +41,7 @@ impl Volume {
             drive,
             file_map: FileMap::new(),
             last_query: String::new(),
-            last_search_num: 0,
+            last_result_num: 0,
             watcher: None,
             event_receiver: None,
             saved_item_count: 0,
@@ -122,7 +126,10 @@ impl Volume {
                 let file_name = file_name.to_string();
 
                 match action {
-                    FileAction::Insert => self.file_map.insert(file_name, parent_path),
+                    FileAction::Insert => {
+                        let attributes = FileAttributes::read(path).unwrap_or_default();
+                        self.file_map.insert(file_name, parent_path, attributes)
+                    }
                     FileAction::Remove => self.file_map.remove(file_name, parent_path),
                 }
             }
@@ -144,6 +151,14 @@ impl Volume {
                     self.process_path(path, FileAction::Remove);
                 }
             }
+            // Content or metadata changes only refresh the indexed attributes.
+            Modify(ModifyKind::Data(_) | ModifyKind::Metadata(_)) => {
+                for path in &event.paths {
+                    if path.exists() {
+                        self.process_path(path, FileAction::Insert);
+                    }
+                }
+            }
             Modify(Name(rename_mode)) => match rename_mode {
                 RenameMode::From => {
                     for path in &event.paths {
@@ -259,8 +274,16 @@ impl Volume {
                 None => root_path.clone(), // If no parent, use root
             };
 
+            let attributes = match entry.metadata() {
+                Ok(metadata) => FileAttributes::from_metadata(&metadata),
+                Err(_) => FileAttributes {
+                    is_dir: entry.file_type().is_dir(),
+                    ..FileAttributes::default()
+                },
+            };
+
             // Insert into file map
-            self.file_map.insert(file_name, parent_path);
+            self.file_map.insert(file_name, parent_path, attributes);
         }
 
         log::info!(
@@ -279,8 +302,10 @@ impl Volume {
     // searching
     pub fn find(
         &mut self,
-        query: String,
+        query: Arc<SearchQuery>,
+        boosts: Arc<RankBoosts>,
         batch: u8,
+        paged: bool,
         cancel: Arc<AtomicBool>,
         sender: mpsc::Sender<Option<Vec<SearchResultItem>>>,
     ) {
@@ -288,17 +313,18 @@ impl Volume {
         let sys_time = SystemTime::now();
 
         #[cfg(debug_assertions)]
-        log::info!("{} Begin Volume::Find {query}", self.drive);
+        log::info!("{} Begin Volume::Find {}", self.drive, query.as_str());
 
         if query.is_empty() || cancel.load(Ordering::Relaxed) {
             let _ = sender.send(None);
             return;
         }
 
-        if self.last_query != query {
-            self.last_search_num = 0;
-            self.last_query = query.clone();
+        if paged && self.last_query != query.as_str() {
+            self.last_result_num = 0;
+            self.last_query = query.as_str().to_string();
         }
+        let last_result_num = if paged { self.last_result_num } else { 0 };
 
         if self.file_map.is_empty() {
             self.serialization_read().unwrap_or_else(|e| {
@@ -307,14 +333,15 @@ impl Volume {
             });
         };
 
-        let (result, search_num) =
-            self.file_map
-                .search(&query, self.last_search_num, batch, &cancel);
+        let result = self
+            .file_map
+            .search(&query, &boosts, last_result_num, batch, &cancel);
 
         #[cfg(debug_assertions)]
         log::info!(
-            "{} End Volume::Find {query}, use time: {:?} ms",
+            "{} End Volume::Find {}, use time: {:?} ms",
             self.drive,
+            query.as_str(),
             sys_time.elapsed().unwrap_or_default().as_millis()
         );
 
@@ -323,8 +350,8 @@ impl Volume {
             return;
         }
 
-        if result.is_some() {
-            self.last_search_num += search_num;
+        if let (true, Some(result)) = (paged, &result) {
+            self.last_result_num += result.len();
         }
 
         let _ = sender.send(result);
@@ -348,7 +375,7 @@ impl Volume {
 
     pub fn release_index_without_save(&mut self) {
         self.last_query = String::new();
-        self.last_search_num = 0;
+        self.last_result_num = 0;
         self.file_map.clear();
     }
 
@@ -423,7 +450,10 @@ impl Volume {
     }
 
     fn is_ignored_event_path(&self, path: &std::path::Path, action: FileAction) -> bool {
-        if has_hidden_component(path) {
+        // Only components below the root count, so a root inside a hidden
+        // directory (e.g. `~/.local/share/applications`) still receives events.
+        let relative_path = path.strip_prefix(&self.drive).unwrap_or(path);
+        if has_hidden_component(relative_path) {
             return true;
         }
 
@@ -447,7 +477,7 @@ fn is_ignored_walk_entry(entry: &DirEntry, excluded_dirs: &ExcludedDirs) -> bool
         return false;
     };
 
-    if file_name.starts_with('.') {
+    if entry.depth() > 0 && file_name.starts_with('.') {
         return true;
     }
 
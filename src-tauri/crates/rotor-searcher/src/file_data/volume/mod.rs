@@ -1,11 +1,15 @@
-#[cfg(any(target_os = "macos", test))]
+#[cfg(any(target_os = "macos", target_os = "linux", test))]
 pub mod default_file_map;
-#[cfg(target_os = "macos")]
+#[cfg(any(target_os = "macos", target_os = "linux"))]
 pub mod default_volume;
-mod search_match;
+pub(crate) mod search_match;
+pub(crate) mod search_query;
+mod top_results;
 
-use std::fs::Metadata;
-use std::io::{self, Read};
+use std::collections::HashMap;
+use std::fs::{self, Metadata};
+use std::io::{self, Read, Write};
+use std::path::Path;
 use std::time::UNIX_EPOCH;
 
 #[cfg(target_os = "windows")]
@@ -13,6 +17,10 @@ pub mod ntfs_file_map;
 #[cfg(target_os = "windows")]
 pub mod ntfs_volume;
 
+const ATTRIBUTE_IS_DIR: u8 = 1;
+const ATTRIBUTE_HAS_SIZE: u8 = 1 << 1;
+const ATTRIBUTE_HAS_MODIFIED_AT: u8 = 1 << 2;
+
 #[derive(serde::Serialize)]
 pub struct SearchResultItem {
     pub path: String,
@@ -21,6 +29,9 @@ pub struct SearchResultItem {
     pub rank: i8,
     pub icon_data: Option<String>, // Base64 encoded icon data
     pub alias: Option<String>,
+    pub size: Option<u64>,
+    pub modified_at: Option<u64>, // Unix timestamp in milliseconds
+    pub is_dir: bool,
 }
 
 impl Clone for SearchResultItem {
@@ -32,6 +43,9 @@ impl Clone for SearchResultItem {
             rank: self.rank,
             icon_data: self.icon_data.clone(),
             alias: self.alias.clone(),
+            size: self.size,
+            modified_at: self.modified_at,
+            is_dir: self.is_dir,
         }
     }
 }
@@ -43,6 +57,155 @@ impl SearchResultItem {
     }
 }
 
+// Extra rank of launched files, applied while a volume picks its results so a
+// frequently launched file is not cut off by higher ranked matches.
+#[derive(Default)]
+pub struct RankBoosts {
+    // Boosted paths by file name, so only candidates with a boosted name need
+    // their path built.
+    by_name: HashMap<String, Vec<(String, i32)>>,
+}
+
+impl RankBoosts {
+    pub fn new(boosts: impl IntoIterator<Item = (String, i32)>) -> RankBoosts {
+        let mut by_name: HashMap<String, Vec<(String, i32)>> = HashMap::new();
+        for (file_path, boost) in boosts {
+            let Some(file_name) = Path::new(&file_path).file_name() else {
+                continue;
+            };
+            by_name
+                .entry(file_name.to_string_lossy().into_owned())
+                .or_default()
+                .push((file_path, boost));
+        }
+        RankBoosts { by_name }
+    }
+
+    pub(super) fn get(&self, file_name: &str, file_path: impl FnOnce() -> Option<String>) -> i32 {
+        let Some(boosts) = self.by_name.get(file_name) else {
+            return 0;
+        };
+        let Some(file_path) = file_path() else {
+            return 0;
+        };
+        boosts
+            .iter()
+            .find(|(path, _)| *path == file_path)
+            .map_or(0, |(_, boost)| *boost)
+    }
+
+    // The largest boost a candidate of each static rank can still get, given
+    // the rank `static_rank` assigns to a file name.
+    pub(super) fn ceilings(&self, static_rank: impl Fn(&str) -> i8) -> BoostCeilings {
+        let mut ranks = self
+            .by_name
+            .iter()
+            .flat_map(|(file_name, boosts)| {
+                let rank = static_rank(file_name);
+                boosts.iter().map(move |(_, boost)| (rank, *boost))
+            })
+            .collect::<Vec<_>>();
+        ranks.sort_by_key(|(rank, _)| std::cmp::Reverse(*rank));
+
+        // Files are visited in descending static rank, so a candidate can only
+        // be one of the boosted files of its own rank or below.
+        let mut ceiling = 0;
+        for (_, boost) in ranks.iter_mut().rev() {
+            ceiling = ceiling.max(*boost);
+            *boost = ceiling;
+        }
+        BoostCeilings { ranks }
+    }
+}
+
+pub(super) struct BoostCeilings {
+    // Static ranks in descending order with the largest boost at or below them.
+    ranks: Vec<(i8, i32)>,
+}
+
+impl BoostCeilings {
+    pub(super) fn at(&self, static_rank: i8) -> i32 {
+        let index = self.ranks.partition_point(|(rank, _)| *rank > static_rank);
+        self.ranks.get(index).map_or(0, |(_, ceiling)| *ceiling)
+    }
+}
+
+// Size, modification time and kind of an indexed entry. `None` means the index
+// has no value yet, e.g. the NTFS MFT enumeration does not report sizes.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FileAttributes {
+    pub size: Option<u64>,
+    pub modified_at: Option<u64>,
+    pub is_dir: bool,
+}
+
+impl FileAttributes {
+    pub fn from_metadata(metadata: &Metadata) -> FileAttributes {
+        FileAttributes {
+            size: Some(metadata.len()),
+            modified_at: metadata_modified_at(metadata),
+            is_dir: metadata.is_dir(),
+        }
+    }
+
+    pub fn read(path: &Path) -> Option<FileAttributes> {
+        fs::symlink_metadata(path)
+            .ok()
+            .map(|metadata| Self::from_metadata(&metadata))
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.size.is_some() && self.modified_at.is_some()
+    }
+
+    // Fills the missing values from the file system, keeping the indexed ones.
+    pub fn complete_from(self, path: &Path) -> FileAttributes {
+        if self.is_complete() {
+            return self;
+        }
+
+        match Self::read(path) {
+            Some(read) => FileAttributes {
+                size: self.size.or(read.size),
+                modified_at: self.modified_at.or(read.modified_at),
+                is_dir: self.is_dir || read.is_dir,
+            },
+            None => self,
+        }
+    }
+
+    // Index record layout: a flag byte followed by the size and the
+    // modification time, unknown values are written as zero.
+    pub(super) fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        let mut flags = 0u8;
+        if self.is_dir {
+            flags |= ATTRIBUTE_IS_DIR;
+        }
+        if self.size.is_some() {
+            flags |= ATTRIBUTE_HAS_SIZE;
+        }
+        if self.modified_at.is_some() {
+            flags |= ATTRIBUTE_HAS_MODIFIED_AT;
+        }
+
+        writer.write_all(&flags.to_be_bytes())?;
+        writer.write_all(&self.size.unwrap_or(0).to_be_bytes())?;
+        writer.write_all(&self.modified_at.unwrap_or(0).to_be_bytes())
+    }
+
+    pub(super) fn read_from(reader: &mut impl Read) -> io::Result<FileAttributes> {
+        let flags = read_u8(reader)?;
+        let size = read_u64(reader)?;
+        let modified_at = read_u64(reader)?;
+
+        Ok(FileAttributes {
+            size: (flags & ATTRIBUTE_HAS_SIZE != 0).then_some(size),
+            modified_at: (flags & ATTRIBUTE_HAS_MODIFIED_AT != 0).then_some(modified_at),
+            is_dir: flags & ATTRIBUTE_IS_DIR != 0,
+        })
+    }
+}
+
 #[derive(Clone, Debug, serde::Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct VolumeIndexStatus {
@@ -91,14 +254,12 @@ pub(super) fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
     Ok(u32::from_be_bytes(bytes))
 }
 
-#[cfg(any(target_os = "macos", test))]
 pub(super) fn read_u8(reader: &mut impl Read) -> io::Result<u8> {
     let mut bytes = [0u8; 1];
     reader.read_exact(&mut bytes)?;
     Ok(u8::from_be_bytes(bytes))
 }
 
-#[cfg(target_os = "windows")]
 pub(super) fn read_u64_or_eof(reader: &mut impl Read) -> io::Result<Option<u64>> {
     let mut bytes = [0u8; 8];
     match reader.read_exact(&mut bytes) {
@@ -108,7 +269,6 @@ pub(super) fn read_u64_or_eof(reader: &mut impl Read) -> io::Result<Option<u64>>
     }
 }
 
-#[cfg(target_os = "windows")]
 pub(super) fn read_u64(reader: &mut impl Read) -> io::Result<u64> {
     read_u64_or_eof(reader)?
         .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "Unexpected end of index"))
@@ -1,21 +1,28 @@
 use std::collections::{BTreeMap, HashMap};
 use std::error::Error;
 use std::fs;
-use std::io::{self, Write};
+use std::io::{self, Read, Write};
 use std::sync::atomic::{AtomicBool, Ordering};
 
 use super::super::excluded_dirs::ExcludedDirs;
-use super::search_match::{make_filter, match_indexed_name, prepare_search_name, SearchAlias};
+use super::search_match::{prepare_search_name, SearchAlias};
+use super::search_query::{QueryCandidate, SearchQuery};
+use super::top_results::TopResults;
 use super::{
-    read_i64, read_i8, read_string, read_u16, read_u32, read_u64, read_u64_or_eof, SearchResultItem,
+    read_i64, read_i8, read_string, read_u16, read_u32, read_u64, read_u64_or_eof, read_u8,
+    FileAttributes, RankBoosts, SearchResultItem,
 };
 
+const INDEX_MAGIC: [u8; 4] = *b"RNFM";
+const INDEX_VERSION: u8 = 2;
+
 pub struct FileView {
     pub parent_index: u64,
     pub file_name: String,
     pub filter: u32,
     pub rank: i8,
     pub search_aliases: Option<Box<[SearchAlias]>>,
+    pub attributes: FileAttributes,
 }
 
 #[derive(PartialEq, Eq, PartialOrd, Ord)]
@@ -40,7 +47,13 @@ impl FileMap {
     }
 
     // insert a file to the database by index, file name and parent index
-    pub fn insert(&mut self, index: u64, file_name: String, parent_index: u64) {
+    pub fn insert(
+        &mut self,
+        index: u64,
+        file_name: String,
+        parent_index: u64,
+        attributes: FileAttributes,
+    ) {
         let prepared = prepare_search_name(&file_name, None);
         let rank = Self::get_file_rank(&file_name);
         self.insert_simple(
@@ -51,10 +64,27 @@ impl FileMap {
                 filter: prepared.filter,
                 rank,
                 search_aliases: prepared.aliases,
+                attributes,
             },
         );
     }
 
+    // Stores the size and modification time read after a content or metadata
+    // change.
+    pub fn set_attributes(&mut self, index: &u64, attributes: FileAttributes) {
+        if let Some(file) = self.get_mut(index) {
+            file.attributes = attributes;
+        }
+    }
+
+    // Records a change of a file that could not be read, keeping the indexed
+    // size until the next change.
+    pub fn touch(&mut self, index: &u64, modified_at: Option<u64>) {
+        if let Some(file) = self.get_mut(index) {
+            file.attributes.modified_at = modified_at.or(file.attributes.modified_at);
+        }
+    }
+
     // insert a file to the database by index and file struct
     fn insert_simple(&mut self, index: u64, file: FileView) {
         let key = FileKey {
@@ -77,65 +107,98 @@ impl FileMap {
         }
     }
 
-    // search for files by query
+    // search for files by query, returning the matches after the first
+    // `offset` ones by rank including the launch boosts
     pub fn search(
         &self,
-        query: &str,
-        last_search_num: usize,
+        query: &SearchQuery,
+        boosts: &RankBoosts,
+        offset: usize,
         batch: u8,
         cancel: &AtomicBool,
         excluded_dirs: &ExcludedDirs,
-    ) -> (Option<Vec<SearchResultItem>>, usize) {
-        let mut result = Vec::new();
-        let mut find_num = 0;
-        let mut search_num: usize = 0;
-        let query_lower = query.to_lowercase();
-        let query_filter = make_filter(&query_lower);
-
-        let file_map_iter = self.iter().rev().skip(last_search_num);
-        for (_, file) in file_map_iter {
+    ) -> Option<Vec<SearchResultItem>> {
+        let mut results = TopResults::new(offset, batch);
+        let ceilings = boosts.ceilings(Self::get_file_rank);
+
+        for (_, file) in self.iter().rev() {
             if cancel.load(Ordering::Relaxed) {
-                return (None, 0);
+                return None;
             }
-            search_num += 1;
-            if match_indexed_name(
-                &file.file_name,
-                None,
-                file.search_aliases.as_deref(),
-                file.filter,
-                &query_lower,
-                query_filter,
-            )
-            .is_some()
-            {
-                if let Some(path) = self.get_path(&file.parent_index) {
-                    let full_path = format!("{}{}", path, file.file_name);
-                    if excluded_dirs.is_excluded_path(std::path::Path::new(&full_path)) {
-                        continue;
-                    }
-                    result.push(SearchResultItem {
-                        path,
-                        file_path: full_path,
-                        file_name: file.file_name.clone(),
-                        rank: file.rank,
-                        icon_data: None,
-                        alias: None,
-                    });
-                    find_num += 1;
-                    if find_num >= batch {
-                        break;
-                    }
-                }
+            if results.is_complete(file.rank as i32 + ceilings.at(file.rank)) {
+                break;
             }
+
+            let candidate = QueryCandidate {
+                file_name: &file.file_name,
+                display_aliases: None,
+                search_aliases: file.search_aliases.as_deref(),
+                filter: file.filter,
+                dir_filter: if query.matches_paths() {
+                    self.get_dir_filter(&file.parent_index)
+                } else {
+                    0
+                },
+                attributes: file.attributes,
+            };
+            let Some(name_match) = query.matches(&candidate, || self.get_path(&file.parent_index))
+            else {
+                continue;
+            };
+            let rank = file.rank.saturating_add(name_match.score);
+            let boost = boosts.get(&file.file_name, || {
+                self.get_path(&file.parent_index)
+                    .map(|path| format!("{}{}", path, file.file_name))
+            });
+            if !results.accepts(rank as i32 + boost) {
+                continue;
+            }
+            let Some(path) = self.get_path(&file.parent_index) else {
+                continue;
+            };
+            let full_path = format!("{}{}", path, file.file_name);
+            if excluded_dirs.is_excluded_path(std::path::Path::new(&full_path)) {
+                continue;
+            }
+
+            results.push(
+                rank as i32 + boost,
+                SearchResultItem {
+                    path,
+                    file_path: full_path,
+                    file_name: file.file_name.clone(),
+                    rank,
+                    icon_data: None,
+                    alias: None,
+                    size: file.attributes.size,
+                    modified_at: file.attributes.modified_at,
+                    is_dir: file.attributes.is_dir,
+                },
+            );
         }
 
-        (Some(result), search_num)
+        // Only the shown results are worth reading from the disk.
+        let mut result = results.into_page();
+        for item in &mut result {
+            let attributes = FileAttributes {
+                size: item.size,
+                modified_at: item.modified_at,
+                is_dir: item.is_dir,
+            }
+            .complete_from(std::path::Path::new(&item.file_path));
+            item.size = attributes.size;
+            item.modified_at = attributes.modified_at;
+            item.is_dir = attributes.is_dir;
+        }
+        Some(result)
     }
 
     pub fn save(&self, path: &str) -> Result<(), std::io::Error> {
         let save_file = fs::File::create(path)?;
         let mut writer = io::BufWriter::new(save_file);
 
+        writer.write_all(&INDEX_MAGIC)?;
+        writer.write_all(&INDEX_VERSION.to_be_bytes())?;
         writer.write_all(&self.start_usn.to_be_bytes())?;
         for (file_key, file) in self.iter() {
             writer.write_all(&file_key.index.to_be_bytes())?;
@@ -144,6 +207,7 @@ impl FileMap {
             writer.write_all(file.file_name.as_bytes())?;
             writer.write_all(&file.filter.to_be_bytes())?;
             writer.write_all(&file.rank.to_be_bytes())?;
+            file.attributes.write_to(&mut writer)?;
         }
         writer.flush()?;
 
@@ -154,6 +218,25 @@ impl FileMap {
         let save_file = fs::File::open(path)?;
         let mut reader = io::BufReader::new(save_file);
 
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if magic != INDEX_MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Unsupported NTFS file index format",
+            )
+            .into());
+        }
+
+        let version = read_u8(&mut reader)?;
+        if version != INDEX_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Unsupported NTFS file index version",
+            )
+            .into());
+        }
+
         self.start_usn = read_i64(&mut reader)?;
 
         while let Some(index) = read_u64_or_eof(&mut reader)? {
@@ -162,6 +245,7 @@ impl FileMap {
             let file_name = read_string(&mut reader, file_name_len as usize)?;
             let _stored_filter = read_u32(&mut reader)?;
             let rank = read_i8(&mut reader)?;
+            let attributes = FileAttributes::read_from(&mut reader)?;
             let prepared = prepare_search_name(&file_name, None);
             self.insert_simple(
                 index,
@@ -171,6 +255,7 @@ impl FileMap {
                     filter: prepared.filter,
                     rank,
                     search_aliases: prepared.aliases,
+                    attributes,
                 },
             );
         }
@@ -207,6 +292,14 @@ impl FileMap {
         None
     }
 
+    fn get_mut(&mut self, index: &u64) -> Option<&mut FileView> {
+        let rank = *self.rank_map.get(index)?;
+        self.main_map.get_mut(&FileKey {
+            rank,
+            index: *index,
+        })
+    }
+
     fn iter(&self) -> std::collections::btree_map::Iter<'_, FileKey, FileView> {
         self.main_map.iter()
     }
@@ -229,6 +322,20 @@ impl FileMap {
         rank
     }
 
+    // Combines the filters of a directory and all of its ancestors
+    fn get_dir_filter(&self, index: &u64) -> u32 {
+        let mut filter = 0;
+        let mut loop_index = *index;
+        while loop_index != 0 {
+            let Some(file) = self.get(&loop_index) else {
+                break;
+            };
+            filter |= file.filter;
+            loop_index = file.parent_index;
+        }
+        filter
+    }
+
     // Constructs a path for a directory
     fn get_path(&self, index: &u64) -> Option<String> {
         let mut segments = Vec::new();
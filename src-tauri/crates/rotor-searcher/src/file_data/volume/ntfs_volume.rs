@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
 use std::ffi::{c_void, CString};
 use std::sync::{
@@ -15,7 +15,11 @@ use windows::Win32::System::{Ioctl, IO};
 
 use super::super::excluded_dirs::ExcludedDirs;
 use super::ntfs_file_map::FileMap;
-use super::{index_file_stem, metadata_modified_at, SearchResultItem, VolumeIndexStatus};
+use super::search_query::SearchQuery;
+use super::{
+    index_file_stem, metadata_modified_at, FileAttributes, RankBoosts, SearchResultItem,
+    VolumeIndexStatus,
+};
 use rotor_platform::file_util;
 
 pub struct Volume {
@@ -24,7 +28,7 @@ pub struct Volume {
     ujd: Ioctl::USN_JOURNAL_DATA_V0,
     file_map: FileMap,
     last_query: String,
-    last_search_num: usize,
+    last_result_num: usize,
     saved_item_count: usize,
     excluded_dirs: ExcludedDirs,
 }
@@ -45,7 +49,7 @@ impl Volume {
                 AllocationDelta: 0x0,
             },
             last_query: String::new(),
-            last_search_num: 0,
+            last_result_num: 0,
             saved_item_count: 0,
             excluded_dirs: ExcludedDirs::from_config(),
         }
@@ -136,7 +140,15 @@ impl Volume {
 
         // add the root directory
         let sz_root = format!("{}:", self.drive);
-        self.file_map.insert(self.drive_frn, sz_root, 0);
+        self.file_map.insert(
+            self.drive_frn,
+            sz_root,
+            0,
+            FileAttributes {
+                is_dir: true,
+                ..FileAttributes::default()
+            },
+        );
 
         let mut med: Ioctl::MFT_ENUM_DATA_V0 = Ioctl::MFT_ENUM_DATA_V0 {
             StartFileReferenceNumber: 0,
@@ -194,6 +206,10 @@ impl Volume {
                             record.FileReferenceNumber,
                             file_name,
                             record.ParentFileReferenceNumber,
+                            // Opening every file here would make a full index far
+                            // slower; size and mtime are filled in for the results
+                            // that are shown.
+                            record_attributes(record),
                         );
                     }
                     record_ptr = (record_ptr as usize + record.RecordLength as usize)
@@ -224,7 +240,7 @@ impl Volume {
         }
 
         self.last_query = String::new();
-        self.last_search_num = 0;
+        self.last_result_num = 0;
 
         #[cfg(debug_assertions)]
         log::info!("{} Begin Volume::release_index", self.drive);
@@ -235,8 +251,10 @@ impl Volume {
     // searching
     pub fn find(
         &mut self,
-        query: String,
+        query: Arc<SearchQuery>,
+        boosts: Arc<RankBoosts>,
         batch: u8,
+        paged: bool,
         cancel: Arc<AtomicBool>,
         sender: mpsc::Sender<Option<Vec<SearchResultItem>>>,
     ) {
@@ -244,17 +262,18 @@ impl Volume {
         let sys_time = SystemTime::now();
 
         #[cfg(debug_assertions)]
-        log::info!("{} Begin Volume::Find {query}", self.drive);
+        log::info!("{} Begin Volume::Find {}", self.drive, query.as_str());
 
         if query.is_empty() || cancel.load(Ordering::Relaxed) {
             let _ = sender.send(None);
             return;
         }
 
-        if self.last_query != query {
-            self.last_search_num = 0;
-            self.last_query = query.clone();
+        if paged && self.last_query != query.as_str() {
+            self.last_result_num = 0;
+            self.last_query = query.as_str().to_string();
         }
+        let last_result_num = if paged { self.last_result_num } else { 0 };
 
         if self.file_map.is_empty() {
             self.serialization_read().unwrap_or_else(|e| {
@@ -263,9 +282,10 @@ impl Volume {
             });
         };
 
-        let (result, search_num) = self.file_map.search(
+        let result = self.file_map.search(
             &query,
-            self.last_search_num,
+            &boosts,
+            last_result_num,
             batch,
             &cancel,
             &self.excluded_dirs,
@@ -273,8 +293,9 @@ impl Volume {
 
         #[cfg(debug_assertions)]
         log::info!(
-            "{} End Volume::Find {query}, use time: {:?} ms",
+            "{} End Volume::Find {}, use time: {:?} ms",
             self.drive,
+            query.as_str(),
             sys_time.elapsed().unwrap_or_default().as_millis()
         );
 
@@ -283,8 +304,8 @@ impl Volume {
             return;
         }
 
-        if result.is_some() {
-            self.last_search_num += search_num;
+        if let (true, Some(result)) = (paged, &result) {
+            self.last_result_num += result.len();
         }
 
         let _ = sender.send(result);
@@ -310,7 +331,11 @@ impl Volume {
             ReasonMask: Ioctl::USN_REASON_FILE_CREATE
                 | Ioctl::USN_REASON_FILE_DELETE
                 | Ioctl::USN_REASON_RENAME_NEW_NAME
-                | Ioctl::USN_REASON_RENAME_OLD_NAME,
+                | Ioctl::USN_REASON_RENAME_OLD_NAME
+                | Ioctl::USN_REASON_DATA_OVERWRITE
+                | Ioctl::USN_REASON_DATA_EXTEND
+                | Ioctl::USN_REASON_DATA_TRUNCATION
+                | Ioctl::USN_REASON_BASIC_INFO_CHANGE,
             ReturnOnlyOnClose: 0,
             Timeout: 0,
             BytesToWaitFor: 0,
@@ -318,6 +343,9 @@ impl Volume {
         };
 
         let h_vol = Self::open_drive(&self.drive);
+        // Files whose size or modification time changed, with the time of their
+        // last journal record. They are read once after the journal is drained.
+        let mut changed: HashMap<u64, Option<u64>> = HashMap::new();
 
         unsafe {
             while IO::DeviceIoControl(
@@ -360,11 +388,25 @@ impl Volume {
                                 record.FileReferenceNumber,
                                 file_name,
                                 record.ParentFileReferenceNumber,
+                                record_attributes(record),
+                            );
+                            changed.insert(
+                                record.FileReferenceNumber,
+                                record_attributes(record).modified_at,
                             );
                         }
-                    } else {
-                        // Ioctl::USN_REASON_FILE_DELETE | Ioctl::USN_REASON_RENAME_OLD_NAME
+                    } else if record.Reason
+                        & (Ioctl::USN_REASON_FILE_DELETE | Ioctl::USN_REASON_RENAME_OLD_NAME)
+                        != 0
+                    {
                         self.file_map.remove(&record.FileReferenceNumber);
+                        changed.remove(&record.FileReferenceNumber);
+                    } else {
+                        // Data or basic info change
+                        changed.insert(
+                            record.FileReferenceNumber,
+                            record_attributes(record).modified_at,
+                        );
                     }
 
                     record_ptr = (record_ptr as usize + record.RecordLength as usize)
@@ -374,6 +416,18 @@ impl Volume {
                 rujd.StartUsn = data[0];
             }
         }
+
+        for (index, journal_modified_at) in changed {
+            if !self.file_map.contains_index(&index) {
+                continue;
+            }
+            match read_file_attributes(h_vol, index) {
+                Some(attributes) => self.file_map.set_attributes(&index, attributes),
+                // Gone again or not readable, the journal time is all there is.
+                None => self.file_map.touch(&index, journal_modified_at),
+            }
+        }
+
         self.file_map.start_usn = rujd.StartUsn;
         Self::close_drive(h_vol);
     }
@@ -444,3 +498,60 @@ impl Volume {
         !self.file_map.contains_index(&parent_index)
     }
 }
+
+// Milliseconds between 1601-01-01 (FILETIME epoch) and 1970-01-01.
+const FILETIME_UNIX_EPOCH_MS: i64 = 11_644_473_600_000;
+
+// The record time stamp is the time of the last journal change, which is used
+// as modification time. MFT enumeration leaves it zero, so a full index only
+// knows the kind of each file.
+fn record_attributes(record: &Ioctl::USN_RECORD_V2) -> FileAttributes {
+    FileAttributes {
+        size: None,
+        modified_at: filetime_to_unix_ms(record.TimeStamp),
+        is_dir: record.FileAttributes & FileSystem::FILE_ATTRIBUTE_DIRECTORY.0 != 0,
+    }
+}
+
+// Reads the size and modification time of a file by its reference number,
+// without resolving its path.
+fn read_file_attributes(h_vol: HANDLE, index: u64) -> Option<FileAttributes> {
+    let file_id = FileSystem::FILE_ID_DESCRIPTOR {
+        dwSize: std::mem::size_of::<FileSystem::FILE_ID_DESCRIPTOR>() as u32,
+        Type: FileSystem::FileIdType,
+        Anonymous: FileSystem::FILE_ID_DESCRIPTOR_0 {
+            FileId: index as i64,
+        },
+    };
+    let mut info = FileSystem::BY_HANDLE_FILE_INFORMATION::default();
+    unsafe {
+        let h_file = FileSystem::OpenFileById(
+            h_vol,
+            &file_id,
+            FileSystem::FILE_READ_ATTRIBUTES.0,
+            FileSystem::FILE_SHARE_READ
+                | FileSystem::FILE_SHARE_WRITE
+                | FileSystem::FILE_SHARE_DELETE,
+            None,
+            FileSystem::FILE_FLAG_BACKUP_SEMANTICS,
+        )
+        .ok()?;
+        let read = FileSystem::GetFileInformationByHandle(h_file, &mut info);
+        Volume::close_drive(h_file);
+        read.ok()?;
+    }
+
+    let last_write_time = (i64::from(info.ftLastWriteTime.dwHighDateTime) << 32)
+        | i64::from(info.ftLastWriteTime.dwLowDateTime);
+    Some(FileAttributes {
+        size: Some((u64::from(info.nFileSizeHigh) << 32) | u64::from(info.nFileSizeLow)),
+        modified_at: filetime_to_unix_ms(last_write_time),
+        is_dir: info.dwFileAttributes & FileSystem::FILE_ATTRIBUTE_DIRECTORY.0 != 0,
+    })
+}
+
+// FILETIME counts 100 ns intervals since 1601; zero means unknown.
+fn filetime_to_unix_ms(filetime: i64) -> Option<u64> {
+    let unix_ms = filetime / 10_000 - FILETIME_UNIX_EPOCH_MS;
+    (filetime > 0 && unix_ms >= 0).then_some(unix_ms as u64)
+}
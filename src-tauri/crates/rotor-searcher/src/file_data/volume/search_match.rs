@@ -1,4 +1,70 @@
+use std::cell::RefCell;
+
 use pinyin::ToPinyin;
+use regex_syntax::hir::{Hir, HirKind};
+use rotor_common::AppConfig;
+
+pub const SEARCH_FUZZY_MATCH_CONFIG_KEY: &str = "search_fuzzy_match";
+
+// Penalties subtracted from the rank of fuzzy matches, so they always sort
+// below verbatim matches of the same file.
+const FUZZY_BASE_PENALTY: i32 = 5;
+const FUZZY_MAX_GAP_PENALTY: i32 = 30;
+const FUZZY_TYPO_PENALTY: i32 = 20;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum MatchMode {
+    #[default]
+    Exact,
+    Fuzzy,
+}
+
+impl MatchMode {
+    pub(crate) fn from_config() -> MatchMode {
+        let fuzzy = AppConfig::lock_global()
+            .get(SEARCH_FUZZY_MATCH_CONFIG_KEY)
+            .is_some_and(|value| value == "true");
+        if fuzzy {
+            MatchMode::Fuzzy
+        } else {
+            MatchMode::Exact
+        }
+    }
+
+    // Number of query characters that may be missing from a candidate, which
+    // also relaxes the `make_filter` prefilter by as many bits.
+    pub(super) fn typo_allowance(self, query_lower: &str) -> u32 {
+        if self == MatchMode::Exact || query_lower.contains('*') {
+            return 0;
+        }
+
+        match query_lower.chars().count() {
+            0..=3 => 0,
+            4..=7 => 1,
+            _ => 2,
+        }
+    }
+}
+
+// Scratch space reused for every candidate a search thread matches, so
+// scoring a name does not allocate.
+#[derive(Default)]
+struct MatchBuffers {
+    text_lower: String,
+    contain: Vec<char>,
+    query: Vec<char>,
+    rows: [Vec<usize>; 3],
+}
+
+thread_local! {
+    static MATCH_BUFFERS: RefCell<MatchBuffers> = RefCell::new(MatchBuffers::default());
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct NameMatch {
+    pub alias: Option<String>,
+    pub score: i8,
+}
 
 #[derive(Debug, PartialEq, Eq)]
 pub(crate) struct SearchAlias {
@@ -36,6 +102,10 @@ pub(super) fn prepare_search_name(
     }
 }
 
+pub(super) fn filter_allows(filter: u32, query_filter: u32, typo_allowance: u32) -> bool {
+    (query_filter & !filter).count_ones() <= typo_allowance
+}
+
 pub(super) fn match_indexed_name(
     file_name: &str,
     display_aliases: Option<&[String]>,
@@ -43,36 +113,68 @@ pub(super) fn match_indexed_name(
     filter: u32,
     query_lower: &str,
     query_filter: u32,
-) -> Option<Option<String>> {
-    if (filter & query_filter) != query_filter {
+    mode: MatchMode,
+) -> Option<NameMatch> {
+    let typo_allowance = mode.typo_allowance(query_lower);
+    if !filter_allows(filter, query_filter, typo_allowance) {
         return None;
     }
 
-    if match_str(file_name, query_lower) {
-        return Some(None);
-    }
+    let display_alias_at = |index: Option<usize>| {
+        index
+            .and_then(|index| display_aliases.and_then(|aliases| aliases.get(index)))
+            .cloned()
+    };
+    let texts = std::iter::once((file_name, None))
+        .chain(
+            display_aliases
+                .into_iter()
+                .flatten()
+                .enumerate()
+                .map(|(index, alias)| (alias.as_str(), Some(index))),
+        )
+        .chain(
+            search_aliases
+                .into_iter()
+                .flatten()
+                .map(|alias| (alias.text.as_str(), alias.display_alias_index)),
+        );
 
-    if let Some(display_aliases) = display_aliases {
-        for alias in display_aliases {
-            if match_str(alias, query_lower) {
-                return Some(Some(alias.clone()));
-            }
-        }
-    }
+    MATCH_BUFFERS.with_borrow_mut(|buffers| {
+        // A verbatim match wins over any fuzzy one.
+        let mut best: Option<(i8, Option<usize>)> = None;
+        for (text, alias_index) in texts {
+            buffers.text_lower.clear();
+            buffers
+                .text_lower
+                .extend(text.chars().flat_map(char::to_lowercase));
+
+            let score = if match_lower(&buffers.text_lower, query_lower) {
+                Some(0)
+            } else if mode == MatchMode::Fuzzy && !query_lower.contains('*') {
+                fuzzy_match_score(buffers, query_lower, typo_allowance)
+            } else {
+                None
+            };
 
-    if let Some(search_aliases) = search_aliases {
-        for alias in search_aliases {
-            if match_str(&alias.text, query_lower) {
-                let display_alias = alias
-                    .display_alias_index
-                    .and_then(|index| display_aliases.and_then(|aliases| aliases.get(index)))
-                    .cloned();
-                return Some(display_alias);
+            if let Some(score) = score {
+                if score == 0 {
+                    return Some(NameMatch {
+                        alias: display_alias_at(alias_index),
+                        score,
+                    });
+                }
+                if best.is_none_or(|(best_score, _)| score > best_score) {
+                    best = Some((score, alias_index));
+                }
             }
         }
-    }
 
-    None
+        best.map(|(score, alias_index)| NameMatch {
+            alias: display_alias_at(alias_index),
+            score,
+        })
+    })
 }
 
 // Calculates a 32bit value that is used to filter out many files before comparing their filenames.
@@ -108,6 +210,33 @@ pub(super) fn make_filter(str: &str) -> u32 {
     address
 }
 
+// Filter bits every match of `pattern` must contain. Only literals that are
+// required on every path through the pattern contribute, so the filter never
+// rejects a name the regex would match.
+pub(super) fn regex_filter(pattern: &str) -> u32 {
+    match regex_syntax::Parser::new().parse(pattern) {
+        Ok(hir) => hir_filter(&hir),
+        Err(_) => 0,
+    }
+}
+
+fn hir_filter(hir: &Hir) -> u32 {
+    match hir.kind() {
+        HirKind::Literal(literal) => std::str::from_utf8(&literal.0)
+            .map(make_filter)
+            .unwrap_or(0),
+        HirKind::Capture(capture) => hir_filter(&capture.sub),
+        HirKind::Repetition(repetition) if repetition.min > 0 => hir_filter(&repetition.sub),
+        HirKind::Concat(hirs) => hirs.iter().fold(0, |filter, hir| filter | hir_filter(hir)),
+        HirKind::Alternation(hirs) => hirs
+            .iter()
+            .map(hir_filter)
+            .reduce(|filter, branch| filter & branch)
+            .unwrap_or(0),
+        _ => 0,
+    }
+}
+
 fn push_pinyin_aliases(
     source: &str,
     display_alias_index: Option<usize>,
@@ -154,8 +283,11 @@ fn push_unique_alias(
 }
 
 // Return true if contain query.
-fn match_str(contain: &str, query_lower: &str) -> bool {
-    let lower_contain = contain.to_lowercase();
+pub(super) fn match_str(contain: &str, query_lower: &str) -> bool {
+    match_lower(&contain.to_lowercase(), query_lower)
+}
+
+fn match_lower(lower_contain: &str, query_lower: &str) -> bool {
     let mut offset = 0;
     for s in query_lower.split('*') {
         // for wildcard
@@ -168,15 +300,118 @@ fn match_str(contain: &str, query_lower: &str) -> bool {
     true
 }
 
+// Scores the lowercased candidate in `buffers` when it does not contain the
+// query verbatim: the query may be a subsequence of the candidate (penalised by
+// the characters skipped between the first and the last match) or within
+// `typo_allowance` edits of a substring.
+fn fuzzy_match_score(
+    buffers: &mut MatchBuffers,
+    query_lower: &str,
+    typo_allowance: u32,
+) -> Option<i8> {
+    let MatchBuffers {
+        text_lower,
+        contain,
+        query,
+        rows,
+    } = buffers;
+    contain.clear();
+    contain.extend(text_lower.chars());
+    query.clear();
+    query.extend(query_lower.chars());
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let penalty = match subsequence_gaps(contain, query) {
+        Some(gaps) => FUZZY_BASE_PENALTY + (gaps as i32).min(FUZZY_MAX_GAP_PENALTY),
+        None => {
+            let typos = substring_edit_distance(contain, query, rows);
+            if typos == 0 || typos > typo_allowance as usize {
+                return None;
+            }
+            FUZZY_BASE_PENALTY + FUZZY_TYPO_PENALTY * typos as i32
+        }
+    };
+
+    Some((-penalty).max(i8::MIN as i32) as i8)
+}
+
+// Smallest number of skipped characters over all placements of `query` as a
+// subsequence of `contain`.
+fn subsequence_gaps(contain: &[char], query: &[char]) -> Option<usize> {
+    let mut best = None;
+    for (start, _) in contain
+        .iter()
+        .enumerate()
+        .filter(|(_, ch)| **ch == query[0])
+    {
+        let mut position = start;
+        let mut gaps = 0;
+        let mut matched = true;
+        for ch in &query[1..] {
+            match contain[position + 1..].iter().position(|c| c == ch) {
+                Some(offset) => {
+                    gaps += offset;
+                    position += offset + 1;
+                }
+                None => {
+                    matched = false;
+                    break;
+                }
+            }
+        }
+
+        if !matched {
+            // Later starts only leave fewer characters to match.
+            break;
+        }
+        if best.is_none_or(|best| gaps < best) {
+            best = Some(gaps);
+        }
+    }
+    best
+}
+
+// Optimal string alignment distance between `query` and the closest substring
+// of `contain`, so adjacent transpositions count as a single edit. Only the
+// last three rows of the matrix are kept.
+fn substring_edit_distance(contain: &[char], query: &[char], rows: &mut [Vec<usize>; 3]) -> usize {
+    let width = contain.len() + 1;
+    for row in rows.iter_mut() {
+        row.clear();
+        row.resize(width, 0);
+    }
+
+    for i in 1..=query.len() {
+        rows.rotate_left(1);
+        let [two_back, previous, current] = &mut *rows;
+        current[0] = i;
+        for j in 1..width {
+            let cost = usize::from(query[i - 1] != contain[j - 1]);
+            let mut distance = (previous[j] + 1)
+                .min(current[j - 1] + 1)
+                .min(previous[j - 1] + cost);
+            if i > 1 && j > 1 && query[i - 1] == contain[j - 2] && query[i - 2] == contain[j - 1] {
+                distance = distance.min(two_back[j - 2] + 1);
+            }
+            current[j] = distance;
+        }
+    }
+
+    rows[2].iter().copied().min().unwrap_or(query.len())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    fn match_query(
+    fn match_query_with(
         file_name: &str,
         display_aliases: Option<&[String]>,
         query: &str,
-    ) -> Option<Option<String>> {
+        mode: MatchMode,
+    ) -> Option<NameMatch> {
         let prepared = prepare_search_name(file_name, display_aliases);
         let query_lower = query.to_lowercase();
         let query_filter = make_filter(&query_lower);
@@ -188,9 +423,24 @@ mod tests {
             prepared.filter,
             &query_lower,
             query_filter,
+            mode,
         )
     }
 
+    fn match_query(
+        file_name: &str,
+        display_aliases: Option<&[String]>,
+        query: &str,
+    ) -> Option<Option<String>> {
+        match_query_with(file_name, display_aliases, query, MatchMode::Exact)
+            .map(|name_match| name_match.alias)
+    }
+
+    fn fuzzy_score(file_name: &str, query: &str) -> Option<i8> {
+        match_query_with(file_name, None, query, MatchMode::Fuzzy)
+            .map(|name_match| name_match.score)
+    }
+
     #[test]
     fn matches_full_pinyin_alias() {
         assert_eq!(match_query("微信.app", None, "weixin"), Some(None));
@@ -214,4 +464,41 @@ mod tests {
             Some(Some("微信".to_string()))
         );
     }
+
+    #[test]
+    fn fuzzy_mode_matches_subsequences_and_typos() {
+        assert_eq!(match_query("Google Chrome.app", None, "chrme"), None);
+
+        let subsequence = fuzzy_score("Google Chrome.app", "chrme").expect("subsequence match");
+        let typo = fuzzy_score("vscode.lnk", "vscdoe").expect("typo match");
+        assert!(subsequence < 0);
+        assert!(typo < subsequence);
+        assert_eq!(fuzzy_score("Google Chrome.app", "chrome"), Some(0));
+    }
+
+    #[test]
+    fn fuzzy_mode_rejects_distant_queries() {
+        assert_eq!(fuzzy_score("Google Chrome.app", "firefox"), None);
+        assert_eq!(fuzzy_score("notes.txt", "nzt"), None);
+        assert_eq!(fuzzy_score("abc.txt", "abd"), None);
+    }
+
+    #[test]
+    fn regex_filter_keeps_only_required_literals() {
+        assert_eq!(
+            regex_filter(r"^IMG_\d{4}\.(jpe?g|heic)$"),
+            make_filter("img_.")
+        );
+        assert_eq!(regex_filter("report(s)?"), make_filter("report"));
+        assert_eq!(regex_filter("(?i)abc"), 0);
+        assert_eq!(regex_filter("(foo|for)bar"), make_filter("fobar"));
+        assert_eq!(regex_filter("foo|bar"), 0);
+    }
+
+    #[test]
+    fn prefers_tighter_subsequences() {
+        let tight = fuzzy_score("report_final.pdf", "rprt").expect("tight match");
+        let loose = fuzzy_score("r_e_p_o_r_t.pdf", "rprt").expect("loose match");
+        assert!(tight > loose);
+    }
 }
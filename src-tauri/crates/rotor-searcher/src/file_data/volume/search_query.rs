@@ -0,0 +1,925 @@
+use std::cell::OnceCell;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use regex::{Regex, RegexBuilder};
+
+use super::search_match::{
+    filter_allows, make_filter, match_indexed_name, match_str, regex_filter, MatchMode, NameMatch,
+    SearchAlias,
+};
+use super::FileAttributes;
+
+const SECOND_MS: u64 = 1000;
+const MINUTE_MS: u64 = 60 * SECOND_MS;
+const HOUR_MS: u64 = 60 * MINUTE_MS;
+const DAY_MS: u64 = 24 * HOUR_MS;
+const REGEX_PREFIX: &str = "regex:";
+const REGEX_SIZE_LIMIT: usize = 1 << 20;
+// Rank penalty for a term found in the directory path rather than the name.
+const DIR_MATCH_PENALTY: i8 = 5;
+
+/*
+Query syntax, terms are separated by whitespace and must all match:
+    foo            file name (or alias) contains `foo`, `*` is a wildcard
+    "foo bar"      quoted term, may contain whitespace
+    src/main       term with a separator, matched against the full path
+    ext:pdf;docx   extension is one of the listed values, `ext:` means no extension
+    path:src/app   full path contains the value, `/` and `\` are interchangeable
+    size:>10mb     size compared with <, <=, >, >=, = or a `1mb..10mb` range
+    modified:<7d   modified less than 7 days ago, `modified:7d` too; also takes
+                   YYYY-MM-DD dates as whole days, `>2024-01-31` starts in February
+    type:dir       only directories, `type:file` only files
+    !term          negates any of the above
+
+With several name terms, or any term containing a separator, each name term may
+match either the file name or its full path.
+
+A query starting with `regex:` is instead a single case-insensitive regular
+expression matched against the file name (or alias), e.g. `regex:^IMG_\d{4}\.jpe?g$`.
+*/
+#[derive(Debug)]
+pub(crate) struct SearchQuery {
+    raw: String,
+    nodes: Vec<QueryNode>,
+    filter: u32,
+    mode: MatchMode,
+    typo_allowance: u32,
+    path_terms: bool,
+    error: Option<String>,
+}
+
+#[derive(Debug)]
+struct RegexTerm {
+    regex: Regex,
+    filter: u32,
+}
+
+impl PartialEq for RegexTerm {
+    fn eq(&self, other: &Self) -> bool {
+        self.regex.as_str() == other.regex.as_str()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum QueryNode {
+    Name { text: String, filter: u32 },
+    Ext(Vec<String>),
+    Path(String),
+    Size(Comparison),
+    Modified(Comparison),
+    Kind(FileKind),
+    Regex(RegexTerm),
+    Not(Box<QueryNode>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FileKind {
+    Dir,
+    File,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Comparison {
+    Lt(u64),
+    Le(u64),
+    Gt(u64),
+    Ge(u64),
+    Eq(u64),
+    Range(u64, u64),
+}
+
+impl Comparison {
+    fn matches(self, value: u64) -> bool {
+        match self {
+            Comparison::Lt(bound) => value < bound,
+            Comparison::Le(bound) => value <= bound,
+            Comparison::Gt(bound) => value > bound,
+            Comparison::Ge(bound) => value >= bound,
+            Comparison::Eq(bound) => value == bound,
+            Comparison::Range(low, high) => low <= value && value <= high,
+        }
+    }
+}
+
+pub(crate) struct QueryCandidate<'a> {
+    pub file_name: &'a str,
+    pub display_aliases: Option<&'a [String]>,
+    pub search_aliases: Option<&'a [SearchAlias]>,
+    pub filter: u32,
+    // Filter of the parent directory path, only needed when the query matches
+    // full paths (see `SearchQuery::matches_paths`).
+    pub dir_filter: u32,
+    pub attributes: FileAttributes,
+}
+
+struct EvalContext<'a, 'b, P: Fn() -> Option<String>> {
+    candidate: &'b QueryCandidate<'a>,
+    mode: MatchMode,
+    path_terms: bool,
+    dir_path: P,
+    full_path: OnceCell<Option<String>>,
+    full_path_lower: OnceCell<Option<String>>,
+    attributes: OnceCell<FileAttributes>,
+}
+
+impl<P: Fn() -> Option<String>> EvalContext<'_, '_, P> {
+    fn full_path(&self) -> Option<&str> {
+        self.full_path
+            .get_or_init(|| {
+                let dir_path = (self.dir_path)()?;
+                Some(
+                    Path::new(&dir_path)
+                        .join(self.candidate.file_name)
+                        .to_string_lossy()
+                        .into_owned(),
+                )
+            })
+            .as_deref()
+    }
+
+    fn full_path_lower(&self) -> Option<&str> {
+        self.full_path_lower
+            .get_or_init(|| {
+                self.full_path()
+                    .map(|path| normalize_separators(&path.to_lowercase()))
+            })
+            .as_deref()
+    }
+
+    // Indexed attributes, completed from the file system only when the index
+    // lacks a value.
+    fn attributes(&self) -> FileAttributes {
+        let attributes = self.candidate.attributes;
+        if attributes.is_complete() {
+            return attributes;
+        }
+
+        *self.attributes.get_or_init(|| match self.full_path() {
+            Some(path) => attributes.complete_from(Path::new(path)),
+            None => attributes,
+        })
+    }
+}
+
+impl SearchQuery {
+    pub(crate) fn parse(query: &str) -> SearchQuery {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        Self::parse_at(query, now)
+    }
+
+    fn parse_at(query: &str, now: Duration) -> SearchQuery {
+        if let Some(pattern) = query.trim_start().strip_prefix(REGEX_PREFIX) {
+            return Self::parse_regex(query, pattern);
+        }
+
+        let mut nodes = tokenize(query)
+            .into_iter()
+            .map(|(token, quoted)| parse_token(&token, quoted, now))
+            .collect::<Vec<_>>();
+        // Cheap name checks first, metadata lookups last.
+        nodes.sort_by_key(QueryNode::cost);
+
+        let filter = nodes
+            .iter()
+            .map(QueryNode::required_filter)
+            .fold(0, |filter, node_filter| filter | node_filter);
+
+        let name_terms = nodes
+            .iter()
+            .filter_map(QueryNode::name_text)
+            .collect::<Vec<_>>();
+        let path_terms = name_terms.len() > 1 || name_terms.iter().any(|text| text.contains('/'));
+
+        SearchQuery {
+            raw: query.to_string(),
+            nodes,
+            filter,
+            mode: MatchMode::Exact,
+            typo_allowance: 0,
+            path_terms,
+            error: None,
+        }
+    }
+
+    // The pattern is compiled once here and shared by every volume and page.
+    fn parse_regex(query: &str, pattern: &str) -> SearchQuery {
+        let mut search_query = SearchQuery {
+            raw: query.to_string(),
+            nodes: Vec::new(),
+            filter: 0,
+            mode: MatchMode::Exact,
+            typo_allowance: 0,
+            path_terms: false,
+            error: None,
+        };
+        if pattern.is_empty() {
+            return search_query;
+        }
+
+        match RegexBuilder::new(pattern)
+            .case_insensitive(true)
+            .size_limit(REGEX_SIZE_LIMIT)
+            .build()
+        {
+            Ok(regex) => {
+                let filter = regex_filter(pattern);
+                search_query.filter = filter;
+                search_query
+                    .nodes
+                    .push(QueryNode::Regex(RegexTerm { regex, filter }));
+            }
+            Err(error) => search_query.error = Some(error.to_string()),
+        }
+        search_query
+    }
+
+    pub(crate) fn with_mode(mut self, mode: MatchMode) -> SearchQuery {
+        self.mode = mode;
+        self.typo_allowance = self
+            .nodes
+            .iter()
+            .map(|node| match node {
+                QueryNode::Name { text, .. } => mode.typo_allowance(text),
+                _ => 0,
+            })
+            .sum();
+        self
+    }
+
+    pub(crate) fn as_str(&self) -> &str {
+        &self.raw
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    // Whether name terms are also matched against the full path, in which case
+    // candidates must carry the filter of their directory path.
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    pub(crate) fn matches_paths(&self) -> bool {
+        self.path_terms
+    }
+
+    // Why the query cannot be searched, e.g. an invalid regular expression.
+    pub(crate) fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    // Returns the display alias that satisfied a name term, if any, and the
+    // summed match score of all name terms when the candidate matches.
+    pub(crate) fn matches(
+        &self,
+        candidate: &QueryCandidate,
+        dir_path: impl Fn() -> Option<String>,
+    ) -> Option<NameMatch> {
+        let filter = if self.path_terms {
+            candidate.filter | candidate.dir_filter
+        } else {
+            candidate.filter
+        };
+        if !filter_allows(filter, self.filter, self.typo_allowance) {
+            return None;
+        }
+
+        let context = EvalContext {
+            candidate,
+            mode: self.mode,
+            path_terms: self.path_terms,
+            dir_path,
+            full_path: OnceCell::new(),
+            full_path_lower: OnceCell::new(),
+            attributes: OnceCell::new(),
+        };
+
+        let mut result = NameMatch {
+            alias: None,
+            score: 0,
+        };
+        for node in &self.nodes {
+            let node_match = node.eval(&context)?;
+            if result.alias.is_none() {
+                result.alias = node_match.alias;
+            }
+            result.score = result.score.saturating_add(node_match.score);
+        }
+        Some(result)
+    }
+}
+
+impl QueryNode {
+    fn eval<P: Fn() -> Option<String>>(
+        &self,
+        context: &EvalContext<'_, '_, P>,
+    ) -> Option<NameMatch> {
+        let candidate = context.candidate;
+        let matched = match self {
+            QueryNode::Name { text, filter } => {
+                if !text.contains('/') {
+                    let name_match = match_indexed_name(
+                        candidate.file_name,
+                        candidate.display_aliases,
+                        candidate.search_aliases,
+                        candidate.filter,
+                        text,
+                        *filter,
+                        context.mode,
+                    );
+                    if name_match.is_some() || !context.path_terms {
+                        return name_match;
+                    }
+                }
+
+                if !filter_allows(candidate.filter | candidate.dir_filter, *filter, 0) {
+                    return None;
+                }
+                return context
+                    .full_path_lower()
+                    .is_some_and(|path| match_str(path, text))
+                    .then_some(NameMatch {
+                        alias: None,
+                        score: -DIR_MATCH_PENALTY,
+                    });
+            }
+            QueryNode::Regex(RegexTerm { regex, .. }) => {
+                if regex.is_match(candidate.file_name) {
+                    return Some(NameMatch {
+                        alias: None,
+                        score: 0,
+                    });
+                }
+                return candidate
+                    .display_aliases
+                    .and_then(|aliases| aliases.iter().find(|alias| regex.is_match(alias)))
+                    .map(|alias| NameMatch {
+                        alias: Some(alias.clone()),
+                        score: 0,
+                    });
+            }
+            QueryNode::Ext(extensions) => {
+                let extension = file_extension(candidate.file_name).to_lowercase();
+                extensions.contains(&extension)
+            }
+            QueryNode::Path(text) => context
+                .full_path()
+                .is_some_and(|path| normalize_separators(&path.to_lowercase()).contains(text)),
+            QueryNode::Size(comparison) => {
+                !candidate.attributes.is_dir
+                    && context
+                        .attributes()
+                        .size
+                        .is_some_and(|size| comparison.matches(size))
+            }
+            QueryNode::Modified(comparison) => context
+                .attributes()
+                .modified_at
+                .is_some_and(|modified_at| comparison.matches(modified_at)),
+            QueryNode::Kind(kind) => match kind {
+                FileKind::Dir => candidate.attributes.is_dir,
+                FileKind::File => !candidate.attributes.is_dir,
+            },
+            QueryNode::Not(node) => node.eval(context).is_none(),
+        };
+
+        matched.then_some(NameMatch {
+            alias: None,
+            score: 0,
+        })
+    }
+
+    fn cost(&self) -> u8 {
+        match self {
+            QueryNode::Name { .. } | QueryNode::Ext(_) | QueryNode::Kind(_) => 0,
+            QueryNode::Regex(_) => 1,
+            QueryNode::Path(_) => 1,
+            QueryNode::Size(_) | QueryNode::Modified(_) => 2,
+            QueryNode::Not(node) => node.cost(),
+        }
+    }
+
+    fn name_text(&self) -> Option<&str> {
+        match self {
+            QueryNode::Name { text, .. } => Some(text),
+            QueryNode::Not(node) => node.name_text(),
+            _ => None,
+        }
+    }
+
+    fn required_filter(&self) -> u32 {
+        match self {
+            QueryNode::Name { filter, .. } => *filter,
+            QueryNode::Regex(RegexTerm { filter, .. }) => *filter,
+            QueryNode::Ext(extensions) if extensions.len() == 1 => make_filter(&extensions[0]),
+            _ => 0,
+        }
+    }
+}
+
+// Splits on whitespace outside of quotes. Quoted tokens are always name terms.
+fn tokenize(query: &str) -> Vec<(String, bool)> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut in_quotes = false;
+
+    for ch in query.chars() {
+        match ch {
+            '"' => {
+                in_quotes = !in_quotes;
+                quoted = true;
+            }
+            ch if ch.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    tokens.push((std::mem::take(&mut current), quoted));
+                }
+                quoted = false;
+            }
+            ch => current.push(ch),
+        }
+    }
+
+    if !current.is_empty() {
+        tokens.push((current, quoted));
+    }
+    tokens
+}
+
+fn parse_token(token: &str, quoted: bool, now: Duration) -> QueryNode {
+    if let Some(rest) = token.strip_prefix('!').filter(|rest| !rest.is_empty()) {
+        return QueryNode::Not(Box::new(parse_token(rest, quoted, now)));
+    }
+
+    let node = token
+        .split_once(':')
+        .filter(|_| !quoted)
+        .and_then(|(key, value)| {
+            let value = value.to_lowercase();
+            match key.to_lowercase().as_str() {
+                "ext" => Some(QueryNode::Ext(parse_extensions(&value))),
+                "path" => {
+                    (!value.is_empty()).then(|| QueryNode::Path(normalize_separators(&value)))
+                }
+                "size" => parse_comparison(&value, parse_size).map(QueryNode::Size),
+                "modified" | "dm" => parse_modified(&value, now).map(QueryNode::Modified),
+                "type" => match value.as_str() {
+                    "dir" | "folder" => Some(QueryNode::Kind(FileKind::Dir)),
+                    "file" => Some(QueryNode::Kind(FileKind::File)),
+                    _ => None,
+                },
+                _ => None,
+            }
+        });
+
+    node.unwrap_or_else(|| {
+        let text = normalize_separators(&token.to_lowercase());
+        // Separators are not part of any indexed name, so leave them out of
+        // the filter to let path terms through the directory filter.
+        QueryNode::Name {
+            filter: make_filter(&text.replace('/', "")),
+            text,
+        }
+    })
+}
+
+fn parse_extensions(value: &str) -> Vec<String> {
+    value
+        .split([';', ','])
+        .map(|ext| ext.trim().trim_start_matches('.').to_string())
+        .collect()
+}
+
+fn parse_comparison(value: &str, parse_value: impl Fn(&str) -> Option<u64>) -> Option<Comparison> {
+    if let Some((low, high)) = value.split_once("..") {
+        let (low, high) = (parse_value(low)?, parse_value(high)?);
+        return Some(Comparison::Range(low.min(high), low.max(high)));
+    }
+
+    if let Some(rest) = value.strip_prefix("<=") {
+        return parse_value(rest).map(Comparison::Le);
+    }
+    if let Some(rest) = value.strip_prefix(">=") {
+        return parse_value(rest).map(Comparison::Ge);
+    }
+    if let Some(rest) = value.strip_prefix('<') {
+        return parse_value(rest).map(Comparison::Lt);
+    }
+    if let Some(rest) = value.strip_prefix('>') {
+        return parse_value(rest).map(Comparison::Gt);
+    }
+    let value = value.strip_prefix('=').unwrap_or(value);
+    parse_value(value).map(Comparison::Eq)
+}
+
+fn parse_size(value: &str) -> Option<u64> {
+    let (number, unit) = split_number(value)?;
+    let multiplier: u64 = match unit {
+        "" | "b" => 1,
+        "k" | "kb" => 1 << 10,
+        "m" | "mb" => 1 << 20,
+        "g" | "gb" => 1 << 30,
+        "t" | "tb" => 1 << 40,
+        _ => return None,
+    };
+    Some((number * multiplier as f64) as u64)
+}
+
+// Relative ages are converted into timestamps, so `<7d` (younger than seven
+// days) becomes "modified after now - 7d".
+fn parse_modified(value: &str, now: Duration) -> Option<Comparison> {
+    let bound = value.trim_start_matches(['<', '>', '=']);
+    if parse_date(bound.split("..").next().unwrap_or(bound)).is_some() {
+        // Dates stand for whole days: a bare date means "on that day", `>` is
+        // after its end and `<` before its start.
+        let day_end = |day_start: u64| day_start + DAY_MS - 1;
+        return Some(match parse_comparison(value, parse_date)? {
+            Comparison::Eq(day_start) => Comparison::Range(day_start, day_end(day_start)),
+            Comparison::Gt(day_start) => Comparison::Gt(day_end(day_start)),
+            Comparison::Le(day_start) => Comparison::Le(day_end(day_start)),
+            Comparison::Range(low, high) => Comparison::Range(low, day_end(high)),
+            comparison => comparison,
+        });
+    }
+
+    let now_ms = now.as_millis() as u64;
+    let comparison = parse_comparison(value, |value| {
+        parse_age(value).map(|age| now_ms.saturating_sub(age))
+    })?;
+    Some(match comparison {
+        Comparison::Lt(bound) => Comparison::Gt(bound),
+        Comparison::Le(bound) => Comparison::Ge(bound),
+        Comparison::Gt(bound) => Comparison::Lt(bound),
+        Comparison::Ge(bound) => Comparison::Le(bound),
+        // A bare age reads as "within", so `7d` is the same as `<=7d`.
+        Comparison::Eq(bound) => Comparison::Ge(bound),
+        Comparison::Range(low, high) => Comparison::Range(low, high),
+    })
+}
+
+fn parse_age(value: &str) -> Option<u64> {
+    let (number, unit) = split_number(value)?;
+    let unit_ms = match unit {
+        "s" => SECOND_MS,
+        "m" | "min" => MINUTE_MS,
+        "h" => HOUR_MS,
+        "" | "d" => DAY_MS,
+        "w" => 7 * DAY_MS,
+        "mo" => 30 * DAY_MS,
+        "y" => 365 * DAY_MS,
+        _ => return None,
+    };
+    Some((number * unit_ms as f64) as u64)
+}
+
+// Parses `YYYY-MM-DD` as UTC midnight in milliseconds since the epoch.
+fn parse_date(value: &str) -> Option<u64> {
+    let mut parts = value.splitn(3, '-');
+    let year = parts.next()?.parse::<i64>().ok()?;
+    let month = parts.next()?.parse::<i64>().ok()?;
+    let day = parts.next()?.parse::<i64>().ok()?;
+    if !(1970..=9999).contains(&year) || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    // Days from civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = (month + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146097 + day_of_era - 719468;
+
+    u64::try_from(days).ok().map(|days| days * DAY_MS)
+}
+
+fn split_number(value: &str) -> Option<(f64, &str)> {
+    let split = value
+        .find(|ch: char| !ch.is_ascii_digit() && ch != '.')
+        .unwrap_or(value.len());
+    let number = value[..split].parse::<f64>().ok()?;
+    Some((number, value[split..].trim()))
+}
+
+fn file_extension(file_name: &str) -> &str {
+    match file_name.rfind('.') {
+        Some(index) if index > 0 => &file_name[index + 1..],
+        _ => "",
+    }
+}
+
+fn normalize_separators(value: &str) -> String {
+    value.replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::search_match::prepare_search_name;
+    use super::*;
+    use std::fs;
+
+    const NOW: Duration = Duration::from_secs(1_700_000_000);
+
+    fn matches(query: &str, file_name: &str, dir_path: &str) -> bool {
+        let query = SearchQuery::parse_at(query, NOW);
+        let prepared = prepare_search_name(file_name, None);
+        let candidate = QueryCandidate {
+            file_name,
+            display_aliases: None,
+            search_aliases: prepared.aliases.as_deref(),
+            filter: prepared.filter,
+            dir_filter: make_filter(dir_path),
+            attributes: FileAttributes::default(),
+        };
+        query
+            .matches(&candidate, || Some(dir_path.to_string()))
+            .is_some()
+    }
+
+    #[test]
+    fn parses_operators_and_negation() {
+        let query = SearchQuery::parse_at("report ext:pdf;DOCX !draft type:dir", NOW);
+
+        assert!(query
+            .nodes
+            .contains(&QueryNode::Ext(vec!["pdf".to_string(), "docx".to_string()])));
+        assert!(query.nodes.contains(&QueryNode::Kind(FileKind::Dir)));
+        assert!(query.nodes.iter().any(|node| matches!(
+            node,
+            QueryNode::Not(inner) if matches!(inner.as_ref(), QueryNode::Name { text, .. } if text == "draft")
+        )));
+    }
+
+    #[test]
+    fn parses_size_and_modified_comparisons() {
+        assert_eq!(
+            parse_comparison(">10mb", parse_size),
+            Some(Comparison::Gt(10 << 20))
+        );
+        assert_eq!(
+            parse_comparison("1k..2k", parse_size),
+            Some(Comparison::Range(1024, 2048))
+        );
+
+        let now_ms = NOW.as_millis() as u64;
+        assert_eq!(
+            parse_modified("<7d", NOW),
+            Some(Comparison::Gt(now_ms - 7 * DAY_MS))
+        );
+        assert_eq!(
+            parse_modified("7d", NOW),
+            Some(Comparison::Ge(now_ms - 7 * DAY_MS))
+        );
+        assert_eq!(
+            parse_modified(">7d", NOW),
+            Some(Comparison::Lt(now_ms - 7 * DAY_MS))
+        );
+
+        let day_start = 1_704_067_200_000;
+        let day_end = day_start + DAY_MS - 1;
+        assert_eq!(
+            parse_modified(">2024-01-01", NOW),
+            Some(Comparison::Gt(day_end))
+        );
+        assert_eq!(
+            parse_modified(">=2024-01-01", NOW),
+            Some(Comparison::Ge(day_start))
+        );
+        assert_eq!(
+            parse_modified("<2024-01-01", NOW),
+            Some(Comparison::Lt(day_start))
+        );
+        assert_eq!(
+            parse_modified("<=2024-01-01", NOW),
+            Some(Comparison::Le(day_end))
+        );
+        assert_eq!(
+            parse_modified("2024-01-01", NOW),
+            Some(Comparison::Range(day_start, day_end))
+        );
+        assert_eq!(
+            parse_modified("2023-12-31..2024-01-01", NOW),
+            Some(Comparison::Range(day_start - DAY_MS, day_end))
+        );
+    }
+
+    #[test]
+    fn unknown_or_invalid_operators_fall_back_to_name_terms() {
+        let query = SearchQuery::parse_at("size:huge", NOW);
+        assert!(matches!(
+            query.nodes.as_slice(),
+            [QueryNode::Name { text, .. }] if text == "size:huge"
+        ));
+    }
+
+    #[test]
+    fn every_name_term_must_match() {
+        assert!(matches(
+            "google chrome",
+            "Google Chrome.app",
+            "/Applications"
+        ));
+        assert!(!matches(
+            "google firefox",
+            "Google Chrome.app",
+            "/Applications"
+        ));
+        assert!(matches("\"e c\"", "Google Chrome.app", "/Applications"));
+        assert!(matches("\"ext:pdf\"", "ext:pdf notes.txt", "/docs"));
+    }
+
+    #[test]
+    fn path_terms_match_directories() {
+        assert!(matches("src/main", "main.rs", "/repo/src"));
+        assert!(matches("src\\main", "main.rs", "/repo/src"));
+        assert!(!matches("src/main", "main.rs", "/repo/lib"));
+        assert!(matches(
+            "documents report",
+            "report.pdf",
+            "/home/a/Documents"
+        ));
+        assert!(!matches(
+            "documents report",
+            "report.pdf",
+            "/home/a/Downloads"
+        ));
+        assert!(!matches("documents", "report.pdf", "/home/a/Documents"));
+        assert!(!matches(
+            "report !downloads",
+            "report.pdf",
+            "/home/a/Downloads"
+        ));
+    }
+
+    #[test]
+    fn directory_matches_rank_below_name_matches() {
+        let query = SearchQuery::parse_at("rotor main", NOW);
+        let rank = |file_name: &str, dir_path: &str| {
+            let prepared = prepare_search_name(file_name, None);
+            let candidate = QueryCandidate {
+                file_name,
+                display_aliases: None,
+                search_aliases: prepared.aliases.as_deref(),
+                filter: prepared.filter,
+                dir_filter: make_filter(dir_path),
+                attributes: FileAttributes::default(),
+            };
+            query
+                .matches(&candidate, || Some(dir_path.to_string()))
+                .map(|name_match| name_match.score)
+        };
+
+        assert_eq!(rank("rotor_main.rs", "/src"), Some(0));
+        assert_eq!(rank("main.rs", "/home/rotor/src"), Some(-DIR_MATCH_PENALTY));
+        assert_eq!(rank("main.rs", "/home/other/src"), None);
+    }
+
+    #[test]
+    fn matches_extension_path_and_negation() {
+        assert!(matches("ext:pdf", "report.PDF", "/docs"));
+        assert!(!matches("ext:pdf", "report.docx", "/docs"));
+        assert!(matches("ext:", "Makefile", "/repo"));
+        assert!(matches(
+            "path:projects/rotor",
+            "main.rs",
+            "/home/a/projects/rotor/src"
+        ));
+        assert!(matches(
+            "path:rotor\\src\\main",
+            "main.rs",
+            "/home/a/projects/rotor/src"
+        ));
+        assert!(!matches(
+            "main !path:node_modules",
+            "main.js",
+            "/repo/node_modules/x"
+        ));
+        assert!(matches("main !path:node_modules", "main.js", "/repo/src"));
+    }
+
+    #[test]
+    fn regex_queries_match_names_and_report_invalid_patterns() {
+        let query = SearchQuery::parse_at(r"regex:^IMG_\d{4}\.(jpe?g|heic)$", NOW);
+        assert!(query.error().is_none());
+        assert_eq!(query.filter, make_filter("img_."));
+        assert!(matches(
+            r"regex:^IMG_\d{4}\.(jpe?g|heic)$",
+            "img_0042.JPG",
+            "/photos"
+        ));
+        assert!(matches(
+            r"regex:^IMG_\d{4}\.(jpe?g|heic)$",
+            "IMG_1234.heic",
+            "/photos"
+        ));
+        assert!(!matches(
+            r"regex:^IMG_\d{4}\.(jpe?g|heic)$",
+            "IMG_12.jpg",
+            "/photos"
+        ));
+        assert!(matches(
+            "regex:report final",
+            "my report final.pdf",
+            "/docs"
+        ));
+
+        let invalid = SearchQuery::parse_at("regex:(unclosed", NOW);
+        assert!(invalid.is_empty());
+        assert!(invalid.error().is_some());
+    }
+
+    #[test]
+    fn fuzzy_mode_relaxes_prefilter_and_scores_terms() {
+        let prepared = prepare_search_name("Google Chrome.app", None);
+        let candidate = QueryCandidate {
+            file_name: "Google Chrome.app",
+            display_aliases: None,
+            search_aliases: prepared.aliases.as_deref(),
+            filter: prepared.filter,
+            dir_filter: 0,
+            attributes: FileAttributes::default(),
+        };
+        let fuzzy_match = |query: &str| {
+            SearchQuery::parse_at(query, NOW)
+                .with_mode(MatchMode::Fuzzy)
+                .matches(&candidate, || None)
+        };
+
+        assert!(SearchQuery::parse_at("chrme", NOW)
+            .matches(&candidate, || None)
+            .is_none());
+        assert!(fuzzy_match("chrme").is_some_and(|name_match| name_match.score < 0));
+        assert!(fuzzy_match("chrome").is_some_and(|name_match| name_match.score == 0));
+        assert!(fuzzy_match("gogle chrone").is_some());
+        assert!(fuzzy_match("chrome ext:exe").is_none());
+    }
+
+    #[test]
+    fn metadata_terms_use_indexed_attributes() {
+        let now_ms = NOW.as_millis() as u64;
+        let matches_attributes = |query: &str, attributes: FileAttributes| {
+            let query = SearchQuery::parse_at(query, NOW);
+            let prepared = prepare_search_name("small.txt", None);
+            let candidate = QueryCandidate {
+                file_name: "small.txt",
+                display_aliases: None,
+                search_aliases: None,
+                filter: prepared.filter,
+                dir_filter: 0,
+                attributes,
+            };
+            query.matches(&candidate, || None).is_some()
+        };
+        let file = FileAttributes {
+            size: Some(5),
+            modified_at: Some(now_ms - HOUR_MS),
+            is_dir: false,
+        };
+        let dir = FileAttributes {
+            is_dir: true,
+            ..file
+        };
+
+        assert!(matches_attributes("small size:<1kb", file));
+        assert!(!matches_attributes("small size:>1kb", file));
+        assert!(matches_attributes("small modified:<1d type:file", file));
+        assert!(!matches_attributes("small modified:>1d", file));
+        assert!(!matches_attributes("small type:dir", file));
+        assert!(matches_attributes("small type:dir", dir));
+        assert!(!matches_attributes("small size:<1kb", dir));
+    }
+
+    #[test]
+    fn missing_attributes_are_read_from_file_system() {
+        let dir = std::env::temp_dir().join(format!("rotor-search-query-{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("create test dir");
+        fs::write(dir.join("small.txt"), b"hello").expect("write test file");
+        let dir_path = dir.to_string_lossy().into_owned();
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let matches_now = |query: &str| {
+            let query = SearchQuery::parse_at(query, now);
+            let prepared = prepare_search_name("small.txt", None);
+            let candidate = QueryCandidate {
+                file_name: "small.txt",
+                display_aliases: None,
+                search_aliases: None,
+                filter: prepared.filter,
+                dir_filter: 0,
+                attributes: FileAttributes::default(),
+            };
+            query
+                .matches(&candidate, || Some(dir_path.clone()))
+                .is_some()
+        };
+
+        assert!(matches_now("small size:<1kb"));
+        assert!(!matches_now("small size:>1kb"));
+        assert!(matches_now("small modified:<1d"));
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
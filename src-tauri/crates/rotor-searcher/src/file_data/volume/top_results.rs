@@ -0,0 +1,97 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+use super::SearchResultItem;
+
+// Keeps the best `offset + batch` matches of a volume by rank and returns the
+// `batch` after the first `offset`. Candidates are visited in descending
+// static rank and only a launch boost can raise a match above it, so once it
+// is full a candidate that cannot beat the worst kept match ends the search.
+pub(super) struct TopResults {
+    offset: usize,
+    limit: usize,
+    pushed: usize,
+    heap: BinaryHeap<Reverse<Ranked>>,
+}
+
+struct Ranked {
+    rank: i32,
+    order: usize,
+    item: SearchResultItem,
+}
+
+// Higher ranks first, ties go to the match visited first.
+impl Ord for Ranked {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.rank
+            .cmp(&other.rank)
+            .then_with(|| other.order.cmp(&self.order))
+    }
+}
+
+impl PartialOrd for Ranked {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Ranked {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Ranked {}
+
+impl TopResults {
+    pub(super) fn new(offset: usize, batch: u8) -> TopResults {
+        let limit = offset + batch as usize;
+        TopResults {
+            offset,
+            limit,
+            pushed: 0,
+            heap: BinaryHeap::with_capacity(limit + 1),
+        }
+    }
+
+    // Whether no candidate that can reach at most `best_rank` can be kept
+    // anymore.
+    pub(super) fn is_complete(&self, best_rank: i32) -> bool {
+        self.heap.len() >= self.limit && self.worst().is_none_or(|worst| best_rank <= worst)
+    }
+
+    // Checked before building the result item of a match.
+    pub(super) fn accepts(&self, rank: i32) -> bool {
+        self.heap.len() < self.limit || self.worst().is_some_and(|worst| rank > worst)
+    }
+
+    pub(super) fn push(&mut self, rank: i32, item: SearchResultItem) {
+        if !self.accepts(rank) {
+            return;
+        }
+
+        self.heap.push(Reverse(Ranked {
+            rank,
+            order: self.pushed,
+            item,
+        }));
+        self.pushed += 1;
+        if self.heap.len() > self.limit {
+            self.heap.pop();
+        }
+    }
+
+    pub(super) fn into_page(self) -> Vec<SearchResultItem> {
+        let mut ranked = self.heap.into_vec();
+        ranked.sort();
+        ranked
+            .into_iter()
+            .skip(self.offset)
+            .map(|Reverse(ranked)| ranked.item)
+            .collect()
+    }
+
+    fn worst(&self) -> Option<i32> {
+        self.heap.peek().map(|Reverse(ranked)| ranked.rank)
+    }
+}
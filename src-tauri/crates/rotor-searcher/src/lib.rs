@@ -8,10 +8,14 @@ use tauri::{Manager, WebviewUrl, WebviewWindowBuilder};
 use tauri_plugin_global_shortcut::Shortcut;
 
 use file_data::{
-    FileData, FileState, SearchIndexStatus, SearchResultItem, SearcherMessage, SharedFileState,
+    FileData, FileState, SearchIndexStatus, SearchRequest, SearchResultItem, SearcherMessage,
+    SharedFileState,
 };
 use rotor_common::AppConfig;
 
+// Loading a released index before the first query can take a while.
+const SEARCH_QUERY_TIMEOUT: Duration = Duration::from_secs(30);
+
 pub struct Searcher {
     app_hander: Option<tauri::AppHandle>,
     searcher_msg_sender: mpsc::Sender<SearcherMessage>,
@@ -54,6 +58,38 @@ impl SearchIndexStatusReader {
     }
 }
 
+// Searcher access for callers outside the searcher window, e.g. local IPC.
+#[derive(Clone)]
+pub struct SearchClient {
+    searcher_msg_sender: mpsc::Sender<SearcherMessage>,
+    status_reader: SearchIndexStatusReader,
+}
+
+impl SearchClient {
+    pub fn find(&self, query: String, limit: u8) -> Result<Vec<SearchResultItem>, String> {
+        let (reply, receiver) = mpsc::channel();
+        self.searcher_msg_sender
+            .send(SearcherMessage::Query(SearchRequest {
+                query,
+                limit,
+                reply,
+            }))
+            .map_err(|_| "Searcher is unavailable".to_string())?;
+
+        receiver
+            .recv_timeout(SEARCH_QUERY_TIMEOUT)
+            .map_err(|error| format!("Failed to receive search result: {error}"))?
+    }
+
+    pub fn index_status(&self) -> SearchIndexStatus {
+        self.status_reader.index_status()
+    }
+
+    pub fn rebuild_index(&self) {
+        let _ = self.searcher_msg_sender.send(SearcherMessage::Init);
+    }
+}
+
 impl Searcher {
     pub fn flag(&self) -> &str {
         "searcher"
@@ -102,7 +138,7 @@ impl Searcher {
         state_change_callback: Option<Box<dyn Fn(String) + Send>>,
     ) -> Searcher
     where
-        F: Fn(String, Vec<SearchResultItem>, bool) + Send + 'static,
+        F: Fn(String, Vec<SearchResultItem>, bool, Option<String>) + Send + 'static,
     {
         let (searcher_msg_sender, searcher_msg_receiver) = mpsc::channel::<SearcherMessage>();
         let search_index_state = Arc::new(Mutex::new(FileState::Unbuild));
@@ -156,6 +192,12 @@ impl Searcher {
             .send(SearcherMessage::Find(filename));
     }
 
+    pub fn record_launch(&self, file_path: String) {
+        let _ = self
+            .searcher_msg_sender
+            .send(SearcherMessage::Launch(file_path));
+    }
+
     pub fn release(&self) {
         let _ = self.searcher_msg_sender.send(SearcherMessage::Release);
     }
@@ -170,4 +212,11 @@ impl Searcher {
             search_index_state: self.search_index_state.clone(),
         }
     }
+
+    pub fn client(&self) -> SearchClient {
+        SearchClient {
+            searcher_msg_sender: self.searcher_msg_sender.clone(),
+            status_reader: self.index_status_reader(),
+        }
+    }
 }
@@ -0,0 +1,323 @@
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rotor_common::{file_path, AppConfig};
+
+use crate::engine::{TranslateRequest, TranslateResult, TranslationEngine};
+
+const DEFAULT_CACHE_LIMIT: usize = 1000;
+const DEFAULT_CACHE_TTL_HOURS: u64 = 24 * 7;
+// Longer texts are translated every time instead of bloating the cache file.
+const MAX_CACHED_TEXT_LEN: usize = 20_000;
+
+static INSTANCE: LazyLock<TranslationCache> = LazyLock::new(TranslationCache::new);
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheKey {
+    pub engine: String,
+    pub model: String,
+    pub text: String,
+    pub to: String,
+    // The LLM prompt preset, empty for the built-in prompt.
+    #[serde(default)]
+    pub prompt: String,
+}
+
+impl CacheKey {
+    pub fn new(engine: &dyn TranslationEngine, request: &TranslateRequest) -> CacheKey {
+        CacheKey {
+            engine: engine.id().to_string(),
+            model: engine.model().to_string(),
+            text: request.text.clone(),
+            to: request.to.clone(),
+            prompt: request
+                .prompt
+                .as_ref()
+                .map(|preset| preset.id.clone())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CacheSettings {
+    pub enabled: bool,
+    pub limit: usize,
+    // `None` keeps entries until they are evicted by the limit.
+    pub ttl: Option<Duration>,
+}
+
+impl CacheSettings {
+    pub fn from_app_config() -> CacheSettings {
+        let config = AppConfig::lock_global();
+        let limit = config
+            .get("translator_cache_limit")
+            .and_then(|value| value.parse::<usize>().ok())
+            .unwrap_or(DEFAULT_CACHE_LIMIT);
+        let ttl_hours = config
+            .get("translator_cache_ttl_hours")
+            .and_then(|value| value.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_CACHE_TTL_HOURS);
+        CacheSettings {
+            enabled: config
+                .get("translator_cache")
+                .is_none_or(|value| value == "true")
+                && limit > 0,
+            limit,
+            ttl: (ttl_hours > 0).then(|| Duration::from_secs(ttl_hours * 60 * 60)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct CacheEntry {
+    #[serde(flatten)]
+    key: CacheKey,
+    translated: String,
+    from: String,
+    // Seconds since the Unix epoch.
+    created: u64,
+}
+
+// Entries ordered from least to most recently used.
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct CacheIndex {
+    #[serde(default)]
+    entries: Vec<CacheEntry>,
+}
+
+// Translation results kept in the user data directory so repeated lookups of the
+// same text skip the network.
+pub struct TranslationCache {
+    path: Option<PathBuf>,
+    index: Mutex<CacheIndex>,
+}
+
+impl TranslationCache {
+    pub fn new() -> TranslationCache {
+        match file_path::get_userdata_path() {
+            Some(path) => TranslationCache::open(path.join("translation_cache.json")),
+            None => {
+                log::error!("Unable to resolve user data path for translation cache");
+                TranslationCache {
+                    path: None,
+                    index: Mutex::default(),
+                }
+            }
+        }
+    }
+
+    pub fn open(path: PathBuf) -> TranslationCache {
+        let index = match fs::read_to_string(&path) {
+            Ok(index_str) => serde_json::from_str(&index_str).unwrap_or_else(|error| {
+                log::warn!("Failed to parse translation cache, starting empty: {error}");
+                CacheIndex::default()
+            }),
+            Err(_) => CacheIndex::default(),
+        };
+
+        TranslationCache {
+            path: Some(path),
+            index: Mutex::new(index),
+        }
+    }
+
+    pub fn global() -> &'static TranslationCache {
+        &INSTANCE
+    }
+
+    // Returns the cached result and marks it as recently used. Expired entries
+    // are dropped on the way. Only dropping entries rewrites the file; the new
+    // recency order is saved with the next insert.
+    pub fn get(&self, key: &CacheKey, ttl: Option<Duration>) -> Option<TranslateResult> {
+        let mut index = self.lock_index();
+        let now = now_secs();
+        let len = index.entries.len();
+        if let Some(ttl) = ttl {
+            index
+                .entries
+                .retain(|entry| now.saturating_sub(entry.created) < ttl.as_secs());
+        }
+        if index.entries.len() != len {
+            self.save(&index);
+        }
+
+        let position = index.entries.iter().position(|entry| entry.key == *key);
+        position.map(|position| {
+            let entry = index.entries.remove(position);
+            let result = TranslateResult {
+                text: entry.key.text.clone(),
+                translated: entry.translated.clone(),
+                from: entry.from.clone(),
+                to: entry.key.to.clone(),
+                engine: entry.key.engine.clone(),
+            };
+            index.entries.push(entry);
+            result
+        })
+    }
+
+    pub fn insert(&self, key: CacheKey, result: &TranslateResult, limit: usize) {
+        if key.text.len() > MAX_CACHED_TEXT_LEN || result.translated.is_empty() {
+            return;
+        }
+
+        let mut index = self.lock_index();
+        index.entries.retain(|entry| entry.key != key);
+        index.entries.push(CacheEntry {
+            key,
+            translated: result.translated.clone(),
+            from: result.from.clone(),
+            created: now_secs(),
+        });
+
+        let overflow = index.entries.len().saturating_sub(limit);
+        index.entries.drain(..overflow);
+        self.save(&index);
+    }
+
+    pub fn clear(&self) -> Result<(), Box<dyn Error>> {
+        let path = self
+            .path
+            .as_deref()
+            .ok_or("Translation cache path is unavailable")?;
+        let mut index = self.lock_index();
+        index.entries.clear();
+        write_index(path, &index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock_index().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock_index(&self) -> MutexGuard<'_, CacheIndex> {
+        self.index
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    // A cache that cannot be written only costs a repeated request, so failures
+    // are logged rather than surfaced to the translation.
+    fn save(&self, index: &CacheIndex) {
+        let Some(path) = self.path.as_deref() else {
+            return;
+        };
+        if let Err(error) = write_index(path, index) {
+            log::warn!("Failed to save translation cache: {error}");
+        }
+    }
+}
+
+impl Default for TranslationCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn write_index(path: &Path, index: &CacheIndex) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string(index)?)?;
+    Ok(())
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|timestamp| timestamp.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "rotor-translation-cache-{name}-{}.json",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    fn key(text: &str) -> CacheKey {
+        CacheKey {
+            engine: "deepseek".to_string(),
+            model: "deepseek-v4-flash".to_string(),
+            text: text.to_string(),
+            to: "zh-CN".to_string(),
+            prompt: String::new(),
+        }
+    }
+
+    fn result(text: &str, translated: &str) -> TranslateResult {
+        TranslateResult {
+            text: text.to_string(),
+            translated: translated.to_string(),
+            from: "en".to_string(),
+            to: "zh-CN".to_string(),
+            engine: "deepseek".to_string(),
+        }
+    }
+
+    #[test]
+    fn evicts_least_recently_used_and_persists() {
+        let path = temp_path("lru");
+        let cache = TranslationCache::open(path.clone());
+        cache.insert(key("one"), &result("one", "一"), 2);
+        cache.insert(key("two"), &result("two", "二"), 2);
+        assert!(cache.get(&key("one"), None).is_some());
+        cache.insert(key("three"), &result("three", "三"), 2);
+
+        let reopened = TranslationCache::open(path);
+        assert!(reopened.get(&key("two"), None).is_none());
+        assert_eq!(reopened.get(&key("one"), None).unwrap().translated, "一");
+        assert_eq!(reopened.get(&key("three"), None).unwrap().from, "en");
+
+        let mut other_model = key("one");
+        other_model.model = "deepseek-v4-pro".to_string();
+        assert!(reopened.get(&other_model, None).is_none());
+    }
+
+    #[test]
+    fn hits_do_not_rewrite_the_file() {
+        let path = temp_path("hit");
+        let cache = TranslationCache::open(path.clone());
+        cache.insert(key("one"), &result("one", "一"), 10);
+        cache.insert(key("two"), &result("two", "二"), 10);
+        let saved = fs::read_to_string(&path).unwrap();
+
+        assert!(cache.get(&key("one"), None).is_some());
+        assert_eq!(fs::read_to_string(&path).unwrap(), saved);
+        assert_eq!(cache.lock_index().entries[1].key, key("one"));
+    }
+
+    #[test]
+    fn drops_expired_entries_and_clears() {
+        let cache = TranslationCache::open(temp_path("ttl"));
+        cache.insert(key("old"), &result("old", "旧"), 10);
+        cache.lock_index().entries[0].created -= 120;
+        cache.insert(key("new"), &result("new", "新"), 10);
+
+        assert!(cache
+            .get(&key("old"), Some(Duration::from_secs(60)))
+            .is_none());
+        assert!(cache
+            .get(&key("new"), Some(Duration::from_secs(60)))
+            .is_some());
+        assert_eq!(cache.len(), 1);
+
+        cache.clear().unwrap();
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,292 @@
+use std::collections::HashMap;
+
+// Detections below this confidence are treated as unknown.
+pub const MIN_CONFIDENCE: f32 = 0.3;
+// Share of CJK characters that must be kana for the text to count as Japanese;
+// Japanese mixes kanji with kana while Chinese has none.
+const MIN_KANA_RATIO: f32 = 0.05;
+// Trigram counts at which a Latin-script detection reaches full confidence.
+const CONFIDENT_TRIGRAMS: f32 = 40.0;
+
+// Most frequent trigrams per language, most frequent first. Spaces mark word
+// boundaries, and shorter entries match any trigram containing them.
+const LATIN_PROFILES: &[(&str, &[&str])] = &[
+    (
+        "en",
+        &[
+            " th", "the", "he ", "ed ", " an", "and", "nd ", "ing", "ng ", " of", "of ", " to",
+            "to ", "ion", " in", "in ", "er ", "tio", "ent", "is ", " is", "es ", "re ", "on ",
+            "at ", " co", "hat", "tha", "for", " fo", "or ", "ter", "it ", " wh", "you", " yo",
+            "ou ", "as ", " be", "ly ",
+        ],
+    ),
+    (
+        "de",
+        &[
+            "en ", "er ", " de", "der", "ie ", " di", "die", "ich", "ein", "sch", "che", "nd ",
+            "und", " un", "ch ", "den", "in ", " ei", "cht", "gen", "te ", "ine", "ung", "es ",
+            " da", "ten", "das", "ist", " is", "st ", "nde", " zu", "zu ", " ge", "ber", "auf",
+            " au", "nic", "mit", " mi",
+        ],
+    ),
+    (
+        "fr",
+        &[
+            "es ", " de", "de ", "le ", " le", "ent", "nt ", "la ", " la", "re ", "les", "ion",
+            "on ", " co", "ne ", "que", " qu", "ue ", "des", " pa", "est", " et", "et ", "men",
+            "ait", "our", " po", "ons", "ur ", "lle", " un", "une", "eur", "ous", " en", "dan",
+            "ans", " d'", "é ", "ét",
+        ],
+    ),
+    (
+        "es",
+        &[
+            "de ", " de", "os ", " la", "la ", "el ", " el", "es ", "en ", " qu", "que", "ue ",
+            "ent", " en", "as ", "on ", " co", "ión", "ado", "ara", "par", " pa", "los", " lo",
+            "ien", "con", "del", "est", "nte", "ra ", "por", " po", " se", "aci", "ció", "una",
+            " un", " es", "ás", " y ",
+        ],
+    ),
+    (
+        "it",
+        &[
+            " di", "di ", "che", " ch", "la ", " la", "to ", "del", "re ", "he ", "ell", "lla",
+            " co", "one", "ion", "ent", " il", "il ", "no ", "per", " pe", "are", "ere", "ato",
+            "nte", "zio", "ne ", "con", "gli", "lle", "ono", "non", " no", "ta ", " un", "una",
+            "è ", " è", "ti ", "ssi",
+        ],
+    ),
+    (
+        "pt",
+        &[
+            "de ", " de", "os ", "ão ", "ção", " qu", "que", "ue ", " co", "do ", "da ", " da",
+            " do", "ent", "as ", "es ", "em ", " em", "com", "nte", "ara", "par", "ado", " pa",
+            "men", "est", "uma", " um", "ões", "não", " nã", "ra ", " se", "con", "dos", "mos",
+            "ica", " po", "ç", "ã",
+        ],
+    ),
+    (
+        "nl",
+        &[
+            "en ", "de ", " de", "et ", "het", " he", "an ", "van", " va", "een", " ee", "ij ",
+            "in ", "er ", "aar", "nde", "ver", " ve", "ing", "oor", " in", "ond", "cht", "te ",
+            "den", "dat", " da", "ijk", "lij", "ie ", "zij", " zi", "ge ", "sch", "ten", "and",
+            "eer", "met", " me", "oe",
+        ],
+    ),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Detection {
+    pub lang: &'static str,
+    // From 0 to 1.
+    pub confidence: f32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Script {
+    Latin,
+    Cyrillic,
+    Greek,
+    Arabic,
+    Hebrew,
+    Devanagari,
+    Thai,
+    Hangul,
+    Kana,
+    Han,
+}
+
+// Detects the language of `text` offline: the writing system settles most
+// languages, and Latin-script text is told apart by its trigrams.
+pub fn detect(text: &str) -> Option<Detection> {
+    let mut scripts = HashMap::<Script, usize>::new();
+    for c in text.chars() {
+        if let Some(script) = script_of(c) {
+            *scripts.entry(script).or_default() += 1;
+        }
+    }
+    let count = |script| scripts.get(&script).copied().unwrap_or(0);
+    let total = scripts.values().sum::<usize>();
+    if total == 0 {
+        return None;
+    }
+
+    let kana = count(Script::Kana);
+    let cjk = kana + count(Script::Han);
+    if cjk > 0 && kana as f32 / cjk as f32 >= MIN_KANA_RATIO {
+        return Some(script_detection("ja", cjk, total));
+    }
+
+    let (&script, &script_count) = scripts
+        .iter()
+        .max_by_key(|(_, count)| **count)
+        .expect("scripts is not empty");
+    let lang = match script {
+        Script::Latin => return detect_latin(text, script_count as f32 / total as f32),
+        Script::Cyrillic if text.contains(['і', 'ї', 'є', 'ґ', 'І', 'Ї', 'Є', 'Ґ']) => "uk",
+        Script::Cyrillic => "ru",
+        Script::Greek => "el",
+        Script::Arabic if text.contains(['پ', 'چ', 'ژ', 'گ']) => "fa",
+        Script::Arabic => "ar",
+        Script::Hebrew => "he",
+        Script::Devanagari => "hi",
+        Script::Thai => "th",
+        Script::Hangul => "ko",
+        Script::Kana => "ja",
+        Script::Han => "zh-CN",
+    };
+    Some(script_detection(lang, script_count, total))
+}
+
+// Whether two language codes name the same language, ignoring the region.
+pub fn same_language(a: &str, b: &str) -> bool {
+    let base = |code: &str| {
+        code.split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase()
+    };
+    base(a) == base(b)
+}
+
+fn script_detection(lang: &'static str, count: usize, total: usize) -> Detection {
+    Detection {
+        lang,
+        confidence: count as f32 / total as f32,
+    }
+}
+
+fn script_of(c: char) -> Option<Script> {
+    let script = match c {
+        'a'..='z' | 'A'..='Z' | '\u{C0}'..='\u{24F}' if c.is_alphabetic() => Script::Latin,
+        '\u{370}'..='\u{3FF}' => Script::Greek,
+        '\u{400}'..='\u{4FF}' => Script::Cyrillic,
+        '\u{590}'..='\u{5FF}' => Script::Hebrew,
+        '\u{600}'..='\u{6FF}' => Script::Arabic,
+        '\u{900}'..='\u{97F}' => Script::Devanagari,
+        '\u{E00}'..='\u{E7F}' => Script::Thai,
+        '\u{1100}'..='\u{11FF}' | '\u{3130}'..='\u{318F}' | '\u{AC00}'..='\u{D7AF}' => {
+            Script::Hangul
+        }
+        '\u{3040}'..='\u{30FF}' | '\u{31F0}'..='\u{31FF}' => Script::Kana,
+        '\u{3400}'..='\u{4DBF}' | '\u{4E00}'..='\u{9FFF}' | '\u{F900}'..='\u{FAFF}' => Script::Han,
+        _ => return None,
+    };
+    Some(script)
+}
+
+fn detect_latin(text: &str, script_share: f32) -> Option<Detection> {
+    let trigrams = trigrams(text);
+    if trigrams.is_empty() {
+        return None;
+    }
+
+    let mut scores = LATIN_PROFILES
+        .iter()
+        .map(|(lang, profile)| (*lang, profile_score(profile, &trigrams)))
+        .collect::<Vec<_>>();
+    scores.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let (lang, best) = scores[0];
+    if best <= 0.0 {
+        return None;
+    }
+
+    let margin = (best - scores[1].1) / best;
+    let length = (trigrams.len() as f32 / CONFIDENT_TRIGRAMS).min(1.0);
+    Some(Detection {
+        lang,
+        confidence: (margin.sqrt() * length.sqrt() * script_share).clamp(0.0, 1.0),
+    })
+}
+
+// Sums the text's trigrams that appear in the profile, weighting frequent
+// profile entries higher.
+fn profile_score(profile: &[&str], trigrams: &[String]) -> f32 {
+    trigrams
+        .iter()
+        .filter_map(|trigram| {
+            profile
+                .iter()
+                .position(|entry| trigram.contains(entry))
+                .map(|rank| (profile.len() - rank) as f32)
+        })
+        .sum()
+}
+
+fn trigrams(text: &str) -> Vec<String> {
+    let mut normalized = vec![' '];
+    for c in text.chars().flat_map(char::to_lowercase) {
+        let c = if c.is_alphabetic() || c == '\'' {
+            c
+        } else {
+            ' '
+        };
+        if c != ' ' || normalized.last() != Some(&' ') {
+            normalized.push(c);
+        }
+    }
+    if normalized.last() != Some(&' ') {
+        normalized.push(' ');
+    }
+
+    normalized
+        .windows(3)
+        .map(|window| window.iter().collect::<String>())
+        .filter(|trigram| trigram.trim().chars().count() > 0)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lang(text: &str) -> Option<&'static str> {
+        detect(text).map(|detection| detection.lang)
+    }
+
+    #[test]
+    fn detects_languages_by_script() {
+        assert_eq!(lang("今日はいい天気ですね"), Some("ja"));
+        assert_eq!(lang("今天天气很好"), Some("zh-CN"));
+        assert_eq!(lang("오늘 날씨가 좋네요"), Some("ko"));
+        assert_eq!(lang("Сегодня хорошая погода"), Some("ru"));
+        assert_eq!(lang("Сьогодні гарна погода, і я їду"), Some("uk"));
+        assert_eq!(lang("Ο καιρός είναι καλός"), Some("el"));
+        assert!(detect("今天天气很好").unwrap().confidence > 0.9);
+        assert_eq!(detect("12345 !?"), None);
+    }
+
+    #[test]
+    fn detects_latin_languages_by_trigrams() {
+        let samples = [
+            ("en", "The weather is nice today and I think that we should go for a walk in the park."),
+            ("de", "Das Wetter ist heute schön und ich denke, dass wir in den Park gehen sollten."),
+            ("fr", "Le temps est beau aujourd'hui et je pense que nous devrions aller nous promener dans le parc."),
+            ("es", "El tiempo es agradable hoy y creo que deberíamos dar un paseo por el parque con los niños."),
+            ("it", "Il tempo è bello oggi e penso che dovremmo fare una passeggiata nel parco con gli amici."),
+            ("pt", "O tempo está bom hoje e acho que devemos dar um passeio no parque com os nossos amigos."),
+            ("nl", "Het weer is vandaag mooi en ik denk dat we een wandeling in het park moeten maken."),
+        ];
+        for (expected, text) in samples {
+            let detection = detect(text).unwrap();
+            assert_eq!(detection.lang, expected, "{text}");
+            assert!(
+                detection.confidence >= MIN_CONFIDENCE,
+                "{text}: {detection:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn short_latin_text_has_low_confidence() {
+        assert!(detect("ok").is_none_or(|detection| detection.confidence < MIN_CONFIDENCE));
+    }
+
+    #[test]
+    fn compares_languages_without_region() {
+        assert!(same_language("zh-CN", "zh-TW"));
+        assert!(same_language("en", "en-US"));
+        assert!(!same_language("ja", "ko"));
+    }
+}
@@ -0,0 +1,656 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::engine::{self, TranslateError};
+use crate::placeholder::ProtectedText;
+
+const BOM: char = '\u{feff}';
+// HTML elements whose content is never translated.
+const RAW_TAGS: [&str; 5] = ["script", "style", "pre", "code", "textarea"];
+// HTML elements that sit inside a sentence and so do not split the text.
+const INLINE_TAGS: [&str; 16] = [
+    "a", "abbr", "b", "br", "cite", "em", "i", "kbd", "mark", "q", "s", "small", "span", "strong",
+    "sub", "sup",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DocumentFormat {
+    Markdown,
+    // SRT and WebVTT, which share their cue layout.
+    Subtitles,
+    Html,
+    Text,
+}
+
+impl DocumentFormat {
+    pub fn from_path(path: &Path) -> Option<DocumentFormat> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "md" | "markdown" => Some(DocumentFormat::Markdown),
+            "srt" | "vtt" => Some(DocumentFormat::Subtitles),
+            "html" | "htm" | "xhtml" => Some(DocumentFormat::Html),
+            "txt" => Some(DocumentFormat::Text),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TranslatedDocument {
+    pub text: String,
+    pub to: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Segment {
+    Translate(String),
+    // Markup, code, timestamps and whitespace, copied as is.
+    Keep(String),
+}
+
+// Translates the human text in `source` and leaves its structure untouched.
+pub async fn translate_document(
+    source: &str,
+    format: DocumentFormat,
+) -> Result<TranslatedDocument, TranslateError> {
+    let segments = segments(source, format);
+    let texts = segments
+        .iter()
+        .filter_map(|segment| match segment {
+            Segment::Translate(text) => Some(protect_markup(text)),
+            Segment::Keep(_) => None,
+        })
+        .collect::<Vec<_>>();
+    if texts.is_empty() {
+        return Err("Nothing to translate".into());
+    }
+
+    let results = engine::translate_all(
+        &texts
+            .iter()
+            .map(|text| text.text.clone())
+            .collect::<Vec<_>>(),
+    )
+    .await?;
+    let to = results
+        .first()
+        .map(|result| result.to.clone())
+        .unwrap_or_default();
+    let translations = texts
+        .iter()
+        .zip(&results)
+        .map(|(text, result)| text.restore(&result.translated));
+    Ok(TranslatedDocument {
+        text: assemble(segments, translations),
+        to,
+    })
+}
+
+// Translates the document at `path` and writes the result to `output`, by
+// default next to it as e.g. `README.zh-CN.md`, which is never overwritten.
+// Returns the written path.
+pub async fn translate_file(path: &Path, output: Option<&Path>) -> Result<PathBuf, TranslateError> {
+    let format = DocumentFormat::from_path(path)
+        .ok_or_else(|| format!("Unsupported document type: {}", path.display()))?;
+    let source = fs::read_to_string(path)
+        .map_err(|error| format!("Failed to read {}: {error}", path.display()))?;
+    let bom = source.starts_with(BOM);
+
+    let translated = translate_document(source.trim_start_matches(BOM), format).await?;
+    let text = if bom {
+        format!("{BOM}{}", translated.text)
+    } else {
+        translated.text
+    };
+    let output = match output {
+        Some(output) => {
+            fs::write(output, text).map_err(|error| write_error(output, error))?;
+            output.to_path_buf()
+        }
+        None => {
+            let output = translated_path(path, &translated.to);
+            OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&output)
+                .and_then(|mut file| file.write_all(text.as_bytes()))
+                .map_err(|error| write_error(&output, error))?;
+            output
+        }
+    };
+    Ok(output)
+}
+
+fn write_error(output: &Path, error: io::Error) -> TranslateError {
+    if error.kind() == io::ErrorKind::AlreadyExists {
+        format!(
+            "{} already exists, choose an output path to replace it",
+            output.display()
+        )
+    } else {
+        format!("Failed to write {}: {error}", output.display())
+    }
+    .into()
+}
+
+fn translated_path(path: &Path, to: &str) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy())
+        .unwrap_or_default();
+    let name = match path.extension() {
+        Some(extension) => format!("{stem}.{to}.{}", extension.to_string_lossy()),
+        None => format!("{stem}.{to}"),
+    };
+    path.with_file_name(name)
+}
+
+fn assemble(segments: Vec<Segment>, translations: impl IntoIterator<Item = String>) -> String {
+    let mut translations = translations.into_iter();
+    segments
+        .into_iter()
+        .map(|segment| match segment {
+            Segment::Keep(text) => text,
+            Segment::Translate(text) => translations.next().unwrap_or(text),
+        })
+        .collect()
+}
+
+fn segments(source: &str, format: DocumentFormat) -> Vec<Segment> {
+    match format {
+        DocumentFormat::Markdown => markdown_segments(source),
+        DocumentFormat::Subtitles => subtitle_segments(source),
+        DocumentFormat::Html => html_segments(source),
+        DocumentFormat::Text => {
+            let mut segments = Segments::default();
+            segments.translate(source);
+            segments.0
+        }
+    }
+}
+
+#[derive(Default)]
+struct Segments(Vec<Segment>);
+
+impl Segments {
+    fn keep(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        match self.0.last_mut() {
+            Some(Segment::Keep(last)) => last.push_str(text),
+            _ => self.0.push(Segment::Keep(text.to_string())),
+        }
+    }
+
+    // Surrounding whitespace and text without any letters outside of markup
+    // are kept.
+    fn translate(&mut self, text: &str) {
+        let trimmed = text.trim();
+        if !protect_markup(trimmed)
+            .text
+            .chars()
+            .any(char::is_alphabetic)
+        {
+            self.keep(text);
+            return;
+        }
+        let start = text.len() - text.trim_start().len();
+        self.keep(&text[..start]);
+        self.0.push(Segment::Translate(trimmed.to_string()));
+        self.keep(&text[start + trimmed.len()..]);
+    }
+
+    // Adds `text` as the next line of the previous translatable text, so a
+    // paragraph or cue is translated as a whole.
+    fn continue_translate(&mut self, text: &str) {
+        if let [.., Segment::Translate(previous), Segment::Keep(between)] = self.0.as_mut_slice() {
+            let trimmed = text.trim();
+            if between.trim().is_empty() && !trimmed.is_empty() {
+                previous.push_str(between);
+                previous.push_str(trimmed);
+                self.0.pop();
+                self.keep(&text[text.trim_end().len()..]);
+                return;
+            }
+        }
+        self.translate(text);
+    }
+}
+
+fn markdown_segments(source: &str) -> Vec<Segment> {
+    let mut segments = Segments::default();
+    let mut front_matter = false;
+    let mut fence: Option<(char, usize)> = None;
+    let mut in_paragraph = false;
+    // Where the text of the list item being continued starts.
+    let mut list_indent: Option<usize> = None;
+
+    for (index, line) in source.split_inclusive('\n').enumerate() {
+        let content = line.trim_end_matches(['\n', '\r']);
+        let ending = &line[content.len()..];
+
+        if index == 0 && content == "---" {
+            front_matter = true;
+            segments.keep(line);
+            continue;
+        }
+        if front_matter {
+            front_matter = content != "---" && content != "...";
+            segments.keep(line);
+            continue;
+        }
+        if let Some((marker, len)) = fence {
+            if fence_marker(content).is_some_and(|(c, n)| c == marker && n >= len) {
+                fence = None;
+            }
+            segments.keep(line);
+            continue;
+        }
+        if let Some(marker) = fence_marker(content) {
+            fence = Some(marker);
+            in_paragraph = false;
+            segments.keep(line);
+            continue;
+        }
+
+        let trimmed = content.trim_start();
+        // Blank lines, rules, setext underlines and table separators have no
+        // text; reference definitions only hold a URL.
+        if !content.chars().any(char::is_alphanumeric) || is_link_definition(trimmed) {
+            in_paragraph = false;
+            segments.keep(line);
+            continue;
+        }
+        let indent = indent_width(content);
+        let list_item = list_item_indent(content);
+        if indent == 0 && !in_paragraph && list_item.is_none() {
+            list_indent = None;
+        }
+        // Indented code blocks, which need four more columns than the list
+        // item they are in and cannot interrupt a paragraph.
+        if !in_paragraph && indent >= list_indent.unwrap_or(0) + 4 {
+            segments.keep(line);
+            continue;
+        }
+        if list_item.is_some() {
+            list_indent = list_item;
+        }
+        if trimmed.starts_with('<') && !trimmed.starts_with("<http") {
+            in_paragraph = false;
+            segments.0.extend(html_segments(line));
+            continue;
+        }
+        if trimmed.starts_with('|') {
+            in_paragraph = false;
+            for (index, cell) in content.split('|').enumerate() {
+                if index > 0 {
+                    segments.keep("|");
+                }
+                segments.translate(cell);
+            }
+            segments.keep(ending);
+            continue;
+        }
+
+        let (prefix, text) = content.split_at(markdown_prefix_len(content));
+        if in_paragraph && prefix.trim().is_empty() {
+            segments.continue_translate(content);
+        } else {
+            segments.keep(prefix);
+            segments.translate(text);
+        }
+        segments.keep(ending);
+        in_paragraph = !prefix.trim_start().starts_with('#');
+    }
+    segments.0
+}
+
+// The fence character and its count for a line opening or closing a fenced
+// code block.
+fn fence_marker(line: &str) -> Option<(char, usize)> {
+    let trimmed = line.trim_start();
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    let marker = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let len = trimmed.chars().take_while(|c| *c == marker).count();
+    (len >= 3).then_some((marker, len))
+}
+
+// Columns of leading whitespace, with tabs stopping every four columns.
+fn indent_width(line: &str) -> usize {
+    let mut width = 0;
+    for c in line.chars() {
+        match c {
+            ' ' => width += 1,
+            '\t' => width += 4 - width % 4,
+            _ => break,
+        }
+    }
+    width
+}
+
+// The column where the text of a list item starts, after its bullet or number.
+fn list_item_indent(line: &str) -> Option<usize> {
+    let trimmed = line.trim_start();
+    let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+    let marker_len = if ["- ", "* ", "+ "]
+        .iter()
+        .any(|marker| trimmed.starts_with(marker))
+    {
+        2
+    } else if (1..=9).contains(&digits)
+        && (trimmed[digits..].starts_with(". ") || trimmed[digits..].starts_with(") "))
+    {
+        digits + 2
+    } else {
+        return None;
+    };
+    Some(indent_width(line) + marker_len)
+}
+
+// `[id]: https://example.com "Title"`
+fn is_link_definition(line: &str) -> bool {
+    line.starts_with('[')
+        && line
+            .find("]:")
+            .is_some_and(|end| !line[1..end].contains(']'))
+}
+
+// Length of the indentation, quote markers, heading, list marker and task box
+// in front of a line's text.
+fn markdown_prefix_len(line: &str) -> usize {
+    let mut rest = line;
+    loop {
+        let trimmed = rest.trim_start();
+        let hashes = trimmed.chars().take_while(|c| *c == '#').count();
+        let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+        let next = if let Some(after) = trimmed.strip_prefix('>') {
+            after
+        } else if (1..=6).contains(&hashes) && trimmed[hashes..].starts_with(' ') {
+            &trimmed[hashes + 1..]
+        } else if let Some(after) = ["- ", "* ", "+ ", "[ ] ", "[x] ", "[X] "]
+            .iter()
+            .find_map(|marker| trimmed.strip_prefix(marker))
+        {
+            after
+        } else if (1..=9).contains(&digits)
+            && (trimmed[digits..].starts_with(". ") || trimmed[digits..].starts_with(") "))
+        {
+            &trimmed[digits + 2..]
+        } else {
+            return line.len() - trimmed.len();
+        };
+        rest = next;
+    }
+}
+
+fn subtitle_segments(source: &str) -> Vec<Segment> {
+    let mut segments = Segments::default();
+    let mut in_cue = false;
+    let mut previous_text = false;
+
+    for line in source.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\n', '\r']);
+        if content.trim().is_empty() {
+            in_cue = false;
+            previous_text = false;
+            segments.keep(line);
+        } else if content.contains("-->") {
+            // Everything in a block up to its timing line is an index or id;
+            // headers, NOTE and STYLE blocks have no timing line at all.
+            in_cue = true;
+            segments.keep(line);
+        } else if in_cue {
+            if previous_text {
+                segments.continue_translate(content);
+            } else {
+                segments.translate(content);
+            }
+            segments.keep(&line[content.len()..]);
+            previous_text = true;
+        } else {
+            segments.keep(line);
+        }
+    }
+    segments.0
+}
+
+fn html_segments(source: &str) -> Vec<Segment> {
+    let mut segments = Segments::default();
+    let mut position = 0;
+    let mut text_start = 0;
+
+    while position < source.len() {
+        let rest = &source[position..];
+        let Some(tag_len) = html_tag_len(rest) else {
+            position += rest.chars().next().map_or(1, char::len_utf8);
+            continue;
+        };
+
+        let tag = &rest[..tag_len];
+        let name = html_tag_name(tag);
+        if INLINE_TAGS.contains(&name.as_str()) {
+            position += tag_len;
+            continue;
+        }
+
+        segments.translate(&source[text_start..position]);
+        segments.keep(tag);
+        position += tag_len;
+        if RAW_TAGS.contains(&name.as_str()) && !tag.starts_with("</") && !tag.ends_with("/>") {
+            let close = format!("</{name}");
+            let end = source[position..]
+                .to_ascii_lowercase()
+                .find(&close)
+                .map_or(source.len(), |end| position + end);
+            segments.keep(&source[position..end]);
+            position = end;
+        }
+        text_start = position;
+    }
+    segments.translate(&source[text_start..]);
+    segments.0
+}
+
+// Length of the tag, comment or doctype at the start of `text`.
+fn html_tag_len(text: &str) -> Option<usize> {
+    if text.starts_with("<!--") {
+        return Some(text.find("-->").map_or(text.len(), |end| end + 3));
+    }
+    let mut chars = text.chars();
+    if chars.next() != Some('<') {
+        return None;
+    }
+    let next = chars.next()?;
+    if !(next.is_ascii_alphabetic() || next == '/' || next == '!' || next == '?') {
+        return None;
+    }
+    Some(text.find('>').map_or(text.len(), |end| end + 1))
+}
+
+fn html_tag_name(tag: &str) -> String {
+    tag.trim_start_matches(['<', '/', '!', '?'])
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+// Swaps inline code, link targets, URLs and tags for placeholders so engines
+// cannot rewrite them.
+fn protect_markup(text: &str) -> ProtectedText {
+    ProtectedText::new(text, ('⟪', '⟫'), |text, position| {
+        let rest = &text[position..];
+        inline_markup_len(rest).map(|len| (len, rest[..len].to_string()))
+    })
+}
+
+// Length of the markup that must not be translated at the start of `text`:
+// a code span, image, link target, URL, tag or subtitle override.
+fn inline_markup_len(text: &str) -> Option<usize> {
+    if text.starts_with('`') {
+        let ticks = text.chars().take_while(|c| *c == '`').count();
+        let fence = &text[..ticks];
+        return text[ticks..].find(fence).map(|end| ticks + end + ticks);
+    }
+    if text.starts_with("![") {
+        let end = text.find("](")?;
+        return bracketed_len(&text[end + 1..]).map(|len| end + 1 + len);
+    }
+    if text.starts_with("](") || text.starts_with("][") {
+        return bracketed_len(&text[1..]).map(|len| 1 + len);
+    }
+    if text.starts_with("http://") || text.starts_with("https://") {
+        return Some(
+            text.find(|c: char| c.is_whitespace() || c == ')' || c == '>')
+                .unwrap_or(text.len()),
+        );
+    }
+    if text.starts_with("{\\") {
+        return text.find('}').map(|end| end + 1);
+    }
+    if text.starts_with('<') {
+        return html_tag_len(text).or_else(|| {
+            // Markdown autolinks such as <https://example.com>.
+            let end = text.find('>')?;
+            text[1..end].contains("://").then_some(end + 1)
+        });
+    }
+    None
+}
+
+// Length of the `(...)` or `[...]` group at the start of `text`, counting
+// nested brackets.
+fn bracketed_len(text: &str) -> Option<usize> {
+    let open = text.chars().next()?;
+    let close = match open {
+        '(' => ')',
+        '[' => ']',
+        _ => return None,
+    };
+    let mut depth = 0;
+    for (index, c) in text.char_indices() {
+        if c == open {
+            depth += 1;
+        } else if c == close {
+            depth -= 1;
+            if depth == 0 {
+                return Some(index + 1);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn translated(source: &str, format: DocumentFormat) -> String {
+        let segments = segments(source, format);
+        let translations = segments
+            .iter()
+            .filter_map(|segment| match segment {
+                Segment::Translate(text) => {
+                    let protected = protect_markup(text);
+                    Some(protected.restore(&protected.text.to_uppercase()))
+                }
+                Segment::Keep(_) => None,
+            })
+            .collect::<Vec<_>>();
+        assemble(segments, translations)
+    }
+
+    fn texts(source: &str, format: DocumentFormat) -> Vec<String> {
+        segments(source, format)
+            .into_iter()
+            .filter_map(|segment| match segment {
+                Segment::Translate(text) => Some(text),
+                Segment::Keep(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn translates_markdown_text_only() {
+        let source = "---\ntitle: Intro\n---\n# Getting started\n\nRun `cargo build` and see [the docs](https://example.com/a_(b)).\nIt is fast.\n\n```rust\nfn main() {}\n```\n\n- [ ] check <kbd>Ctrl</kbd>\n> quoted\n\n| Key | Action |\n| --- | --- |\n| a | open |\n\n[docs]: https://example.com\n";
+
+        assert_eq!(
+            translated(source, DocumentFormat::Markdown),
+            "---\ntitle: Intro\n---\n# GETTING STARTED\n\nRUN `cargo build` AND SEE [THE DOCS](https://example.com/a_(b)).\nIT IS FAST.\n\n```rust\nfn main() {}\n```\n\n- [ ] CHECK <kbd>CTRL</kbd>\n> QUOTED\n\n| KEY | ACTION |\n| --- | --- |\n| A | OPEN |\n\n[docs]: https://example.com\n"
+        );
+        assert_eq!(
+            texts(source, DocumentFormat::Markdown)[1],
+            "Run `cargo build` and see [the docs](https://example.com/a_(b)).\nIt is fast."
+        );
+    }
+
+    #[test]
+    fn keeps_indented_code_blocks() {
+        let source = "Build it:\n\n    cargo build --release\n\tcargo test\n\nA long line\n    that wraps.\n\n- Install\n\n    Then run it.\n\n        rotor --help\n";
+
+        assert_eq!(
+            translated(source, DocumentFormat::Markdown),
+            "BUILD IT:\n\n    cargo build --release\n\tcargo test\n\nA LONG LINE\nTHAT WRAPS.\n\n- INSTALL\n\n    THEN RUN IT.\n\n        rotor --help\n"
+        );
+    }
+
+    #[test]
+    fn translates_subtitle_cues_only() {
+        let srt = "1\r\n00:00:01,000 --> 00:00:02,500\r\nHello there.\r\n<i>General Kenobi!</i>\r\n\r\n2\r\n00:00:03,000 --> 00:00:04,000\r\nBye\r\n";
+        assert_eq!(
+            translated(srt, DocumentFormat::Subtitles),
+            "1\r\n00:00:01,000 --> 00:00:02,500\r\nHELLO THERE.\r\n<i>GENERAL KENOBI!</i>\r\n\r\n2\r\n00:00:03,000 --> 00:00:04,000\r\nBYE\r\n"
+        );
+
+        let vtt = "WEBVTT\n\nNOTE made by hand\n\nintro\n00:01.000 --> 00:02.000 align:start\n{\\an8}Welcome\n";
+        assert_eq!(
+            translated(vtt, DocumentFormat::Subtitles),
+            "WEBVTT\n\nNOTE made by hand\n\nintro\n00:01.000 --> 00:02.000 align:start\n{\\an8}WELCOME\n"
+        );
+    }
+
+    #[test]
+    fn translates_html_text_only() {
+        let source = "<!DOCTYPE html>\n<html><head><title>Home</title><style>p { color: red; }</style></head>\n<body><p>Hello <b>world</b>!</p><pre>let x = 1;</pre><!-- note --></body></html>";
+
+        assert_eq!(
+            translated(source, DocumentFormat::Html),
+            "<!DOCTYPE html>\n<html><head><title>HOME</title><style>p { color: red; }</style></head>\n<body><p>HELLO <b>WORLD</b>!</p><pre>let x = 1;</pre><!-- note --></body></html>"
+        );
+        assert_eq!(
+            texts(source, DocumentFormat::Html),
+            vec!["Home", "Hello <b>world</b>!"]
+        );
+    }
+
+    #[test]
+    fn restores_padded_placeholders() {
+        let protected = protect_markup("Press <kbd>Esc</kbd> or see https://example.com");
+        assert_eq!(protected.text, "Press ⟪0⟫Esc⟪1⟫ or see ⟪2⟫");
+        assert_eq!(
+            protected.restore("按 ⟪ 0 ⟫Esc⟪1⟫ 或查看 ⟪2⟫"),
+            "按 <kbd>Esc</kbd> 或查看 https://example.com"
+        );
+    }
+
+    #[test]
+    fn picks_format_and_output_path() {
+        assert_eq!(
+            DocumentFormat::from_path(Path::new("README.MD")),
+            Some(DocumentFormat::Markdown)
+        );
+        assert_eq!(
+            DocumentFormat::from_path(Path::new("a.vtt")),
+            Some(DocumentFormat::Subtitles)
+        );
+        assert_eq!(DocumentFormat::from_path(Path::new("a.pdf")), None);
+        assert_eq!(
+            translated_path(Path::new("/docs/README.md"), "zh-CN"),
+            PathBuf::from("/docs/README.zh-CN.md")
+        );
+    }
+}
@@ -0,0 +1,464 @@
+use reqwest::RequestBuilder;
+use serde_json::Value;
+
+use super::dictionary::{self, DictionaryResult};
+use super::retry::status_error;
+use super::{
+    chat_messages, for_each_line, http_client, llm_messages, BoxFuture, EngineConfig, OnEvent,
+    TranslateError, TranslateRequest, TranslateResult, TranslateStreamEvent, TranslationEngine,
+    LLM_REQUEST_TIMEOUT, REQUEST_TIMEOUT,
+};
+
+const DEEPSEEK_BASE_URL: &str = "https://api.deepseek.com";
+
+// Any server speaking the OpenAI chat completions API. DeepSeek is one with a
+// fixed base URL, a required key and thinking turned off.
+pub(super) struct ChatCompletionsEngine {
+    id: &'static str,
+    name: &'static str,
+    base_url: String,
+    api_key: String,
+    model: String,
+    key_required: bool,
+    disable_thinking: bool,
+}
+
+impl ChatCompletionsEngine {
+    pub fn deepseek(engine_config: &EngineConfig) -> ChatCompletionsEngine {
+        ChatCompletionsEngine {
+            id: "deepseek",
+            name: "DeepSeek",
+            base_url: DEEPSEEK_BASE_URL.to_string(),
+            api_key: engine_config.deepseek_api_key.trim().to_string(),
+            model: resolve_deepseek_model(&engine_config.deepseek_model).to_string(),
+            key_required: true,
+            disable_thinking: true,
+        }
+    }
+
+    pub fn openai_compatible(engine_config: &EngineConfig) -> ChatCompletionsEngine {
+        ChatCompletionsEngine {
+            id: "openai",
+            name: "OpenAI-compatible",
+            base_url: engine_config
+                .openai_base_url
+                .trim()
+                .trim_end_matches('/')
+                .to_string(),
+            api_key: engine_config.openai_api_key.trim().to_string(),
+            model: engine_config.openai_model.trim().to_string(),
+            key_required: false,
+            disable_thinking: false,
+        }
+    }
+
+    fn check_config(&self) -> Result<(), TranslateError> {
+        if self.base_url.is_empty() {
+            return Err(format!("{} base URL is not configured", self.name).into());
+        }
+        if self.key_required && self.api_key.is_empty() {
+            return Err(format!("{} API key is not configured", self.name).into());
+        }
+        if self.model.is_empty() {
+            return Err(format!("{} model is not configured", self.name).into());
+        }
+        Ok(())
+    }
+
+    // Self-hosted servers often run without authentication.
+    fn authorize(&self, builder: RequestBuilder) -> RequestBuilder {
+        if self.api_key.is_empty() {
+            builder
+        } else {
+            builder.bearer_auth(&self.api_key)
+        }
+    }
+
+    async fn post_chat(
+        &self,
+        system_prompt: String,
+        text: &str,
+        extra: Value,
+    ) -> Result<reqwest::Response, TranslateError> {
+        self.check_config()?;
+
+        let mut request_body = serde_json::json!({
+            "model": self.model,
+            "messages": chat_messages(system_prompt, text)
+        });
+        if self.disable_thinking {
+            request_body["thinking"] = serde_json::json!({ "type": "disabled" });
+        }
+        if let (Some(body), Value::Object(extra)) = (request_body.as_object_mut(), extra) {
+            body.extend(extra);
+        }
+
+        let client = http_client(LLM_REQUEST_TIMEOUT)?;
+        let response = self
+            .authorize(client.post(format!("{}/chat/completions", self.base_url)))
+            .json(&request_body)
+            .send()
+            .await?;
+        let status = response.status();
+
+        if !status.is_success() {
+            let headers = response.headers().clone();
+            let body = response.text().await?;
+            let detail = parse_error(&body)
+                .map(|message| format!(": {message}"))
+                .unwrap_or_default();
+            let message = format!("{} translate request failed: {status}{detail}", self.name);
+            return Err(status_error(status, &headers, message));
+        }
+        Ok(response)
+    }
+
+    async fn stream_chat(
+        &self,
+        request: &TranslateRequest,
+        on_event: &OnEvent<'_>,
+    ) -> Result<TranslateResult, TranslateError> {
+        let (system_prompt, user_message) = llm_messages(request);
+        let response = self
+            .post_chat(
+                system_prompt,
+                &user_message,
+                serde_json::json!({ "stream": true }),
+            )
+            .await?;
+
+        on_event(TranslateStreamEvent::Started {
+            text: request.text.clone(),
+            from: request.from.clone(),
+            to: request.to.clone(),
+        });
+
+        let mut translated = String::new();
+        for_each_line(response, |line| {
+            consume_stream_line(self.name, line, &mut translated, on_event)
+        })
+        .await?;
+
+        let translated = translated.trim().to_string();
+        if translated.is_empty() {
+            return Err(format!("Unexpected {} response format", self.name).into());
+        }
+
+        Ok(request.result(translated, &request.from))
+    }
+
+    async fn lookup_word(
+        &self,
+        request: &TranslateRequest,
+    ) -> Result<Option<DictionaryResult>, TranslateError> {
+        let response = self
+            .post_chat(
+                dictionary::llm_prompt(request),
+                &request.text,
+                serde_json::json!({
+                    "stream": false,
+                    "response_format": { "type": "json_object" }
+                }),
+            )
+            .await?;
+
+        let body: Value = response.json().await?;
+        let content = body
+            .pointer("/choices/0/message/content")
+            .and_then(Value::as_str)
+            .ok_or_else(|| format!("Unexpected {} response format", self.name))?;
+        dictionary::parse_llm_response(request, content)
+    }
+
+    async fn check_models(&self) -> Result<(), TranslateError> {
+        self.check_config()?;
+
+        let client = http_client(REQUEST_TIMEOUT)?;
+        let response = self
+            .authorize(client.get(format!("{}/models", self.base_url)))
+            .send()
+            .await?;
+        let status = response.status();
+        let body = response.text().await?;
+
+        if !status.is_success() {
+            let detail = parse_error(&body)
+                .map(|message| format!(": {message}"))
+                .unwrap_or_default();
+            return Err(format!("{} is not reachable: {status}{detail}", self.name).into());
+        }
+
+        let models = serde_json::from_str::<Value>(&body)
+            .ok()
+            .and_then(|body| body.get("data")?.as_array().cloned())
+            .unwrap_or_default();
+        let listed = models
+            .iter()
+            .any(|model| model.get("id").and_then(Value::as_str) == Some(self.model.as_str()));
+        if !models.is_empty() && !listed {
+            return Err(format!("{} does not serve model {}", self.name, self.model).into());
+        }
+        Ok(())
+    }
+}
+
+impl TranslationEngine for ChatCompletionsEngine {
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn stream<'a>(
+        &'a self,
+        request: &'a TranslateRequest,
+        on_event: &'a OnEvent<'a>,
+    ) -> BoxFuture<'a, Result<TranslateResult, TranslateError>> {
+        Box::pin(self.stream_chat(request, on_event))
+    }
+
+    fn lookup<'a>(
+        &'a self,
+        request: &'a TranslateRequest,
+    ) -> BoxFuture<'a, Result<Option<DictionaryResult>, TranslateError>> {
+        Box::pin(self.lookup_word(request))
+    }
+
+    fn health_check(&self) -> BoxFuture<'_, Result<(), TranslateError>> {
+        Box::pin(self.check_models())
+    }
+}
+
+fn resolve_deepseek_model(configured_model: &str) -> &str {
+    let configured_model = configured_model.trim();
+    if configured_model.is_empty() {
+        rotor_common::config::DEFAULT_TRANSLATOR_DEEPSEEK_MODEL
+    } else {
+        configured_model
+    }
+}
+
+fn consume_stream_line(
+    name: &str,
+    line: &[u8],
+    translated: &mut String,
+    on_event: &OnEvent<'_>,
+) -> Result<bool, TranslateError> {
+    let line = std::str::from_utf8(line)?.trim_end_matches(['\r', '\n']);
+    let Some(data) = line.strip_prefix("data:").map(str::trim) else {
+        return Ok(false);
+    };
+
+    if data == "[DONE]" {
+        return Ok(true);
+    }
+
+    let payload: Value = serde_json::from_str(data)?;
+    if let Some(message) = payload
+        .pointer("/error/message")
+        .and_then(|message| message.as_str())
+    {
+        return Err(format!("{name} translate stream failed: {message}").into());
+    }
+
+    if let Some(content) = payload
+        .pointer("/choices/0/delta/content")
+        .and_then(|content| content.as_str())
+        .filter(|content| !content.is_empty())
+    {
+        translated.push_str(content);
+        on_event(TranslateStreamEvent::Delta {
+            content: content.to_string(),
+        });
+    }
+
+    Ok(false)
+}
+
+fn parse_error(body: &str) -> Option<String> {
+    serde_json::from_str::<Value>(body)
+        .ok()?
+        .pointer("/error/message")?
+        .as_str()
+        .map(str::trim)
+        .filter(|message| !message.is_empty())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::mock_server::{MockResponse, MockServer};
+    use crate::engine::tests::engine_config;
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    fn openai_engine(base_url: &str, api_key: &str, model: &str) -> ChatCompletionsEngine {
+        ChatCompletionsEngine {
+            id: "openai",
+            name: "OpenAI-compatible",
+            base_url: base_url.to_string(),
+            api_key: api_key.to_string(),
+            model: model.to_string(),
+            key_required: false,
+            disable_thinking: false,
+        }
+    }
+
+    fn request(text: &str) -> TranslateRequest {
+        TranslateRequest {
+            text: text.to_string(),
+            from: "auto".to_string(),
+            to: "zh-CN".to_string(),
+            glossary: Vec::new(),
+            prompt: None,
+        }
+    }
+
+    #[test]
+    fn resolve_deepseek_model_uses_default_for_empty_value() {
+        assert_eq!(
+            resolve_deepseek_model("  "),
+            rotor_common::config::DEFAULT_TRANSLATOR_DEEPSEEK_MODEL
+        );
+        assert_eq!(resolve_deepseek_model(" custom-model "), "custom-model");
+    }
+
+    #[test]
+    fn consumes_stream_delta() {
+        let mut translated = String::new();
+        let events = Mutex::new(Vec::new());
+
+        let done = consume_stream_line(
+            "DeepSeek",
+            b"data: {\"choices\":[{\"delta\":{\"content\":\"Hello\"}}]}\n",
+            &mut translated,
+            &|event| events.lock().unwrap().push(event),
+        )
+        .unwrap();
+
+        assert!(!done);
+        assert_eq!(translated, "Hello");
+        let events = events.lock().unwrap();
+        assert!(matches!(
+            events.as_slice(),
+            [TranslateStreamEvent::Delta { content }] if content == "Hello"
+        ));
+    }
+
+    #[test]
+    fn recognizes_stream_end() {
+        let mut translated = String::new();
+        let done =
+            consume_stream_line("DeepSeek", b"data: [DONE]\r\n", &mut translated, &|_| {}).unwrap();
+        assert!(done);
+    }
+
+    #[test]
+    fn parse_error_reads_api_message() {
+        let body = r#"{"error":{"message":"Invalid API key"}}"#;
+        assert_eq!(parse_error(body).as_deref(), Some("Invalid API key"));
+    }
+
+    #[test]
+    fn deepseek_requires_api_key() {
+        let engine = ChatCompletionsEngine::deepseek(&engine_config("deepseek"));
+
+        let error = engine.check_config().unwrap_err();
+
+        assert_eq!(error.to_string(), "DeepSeek API key is not configured");
+    }
+
+    #[tokio::test]
+    async fn streams_chat_completion_from_openai_compatible_server() {
+        let server = MockServer::start(vec![MockResponse::new(
+            200,
+            "text/event-stream",
+            concat!(
+                "data: {\"choices\":[{\"delta\":{\"role\":\"assistant\"}}]}\n\n",
+                "data: {\"choices\":[{\"delta\":{\"content\":\"你好\"}}]}\n\n",
+                "data: {\"choices\":[{\"delta\":{\"content\":\"，世界\"}}]}\n\n",
+                "data: [DONE]\n\n",
+            ),
+        )]);
+        let engine = openai_engine(&format!("{}/v1", server.url()), "secret", "qwen2.5");
+        let events = Mutex::new(Vec::new());
+
+        let result = engine
+            .stream(&request("Hello, world"), &|event| {
+                events.lock().unwrap().push(event)
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(result.translated, "你好，世界");
+        assert_eq!(result.to, "zh-CN");
+        assert_eq!(events.lock().unwrap().len(), 3);
+
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].path, "/v1/chat/completions");
+        assert_eq!(requests[0].header("authorization"), Some("Bearer secret"));
+        let body = requests[0].json();
+        assert_eq!(body["model"], json!("qwen2.5"));
+        assert_eq!(body["stream"], json!(true));
+        assert_eq!(body["messages"][1]["content"], json!("Hello, world"));
+        assert!(body.get("thinking").is_none());
+    }
+
+    #[tokio::test]
+    async fn reports_server_error_message() {
+        let server = MockServer::start(vec![MockResponse::json(
+            401,
+            json!({ "error": { "message": "Invalid API key" } }),
+        )]);
+        let engine = openai_engine(server.url(), "", "gpt-4o-mini");
+
+        let error = engine.translate(&request("Hello")).await.unwrap_err();
+
+        assert!(error.to_string().contains("Invalid API key"));
+        assert_eq!(server.requests()[0].header("authorization"), None);
+    }
+
+    #[tokio::test]
+    async fn health_check_lists_configured_model() {
+        let models = json!({ "data": [{ "id": "qwen2.5" }, { "id": "llama3" }] });
+        let server = MockServer::start(vec![
+            MockResponse::json(200, models.clone()),
+            MockResponse::json(200, models),
+        ]);
+
+        openai_engine(server.url(), "", "llama3")
+            .health_check()
+            .await
+            .unwrap();
+        let error = openai_engine(server.url(), "", "mistral")
+            .health_check()
+            .await
+            .unwrap_err();
+
+        assert!(error.to_string().contains("does not serve model mistral"));
+        assert_eq!(server.requests()[0].path, "/models");
+    }
+
+    #[tokio::test]
+    async fn looks_up_words_with_json_output() {
+        let content = r#"{"phonetic": "/rʌn/", "entries": [{"partOfSpeech": "verb", "translations": ["跑"]}]}"#;
+        let server = MockServer::start(vec![MockResponse::json(
+            200,
+            json!({ "choices": [{ "message": { "content": content } }] }),
+        )]);
+        let engine = openai_engine(server.url(), "", "gpt-test");
+
+        let result = engine.lookup(&request("run")).await.unwrap().unwrap();
+
+        assert_eq!(result.phonetic, "/rʌn/");
+        assert_eq!(result.entries[0].translations, vec!["跑"]);
+        let body = server.requests()[0].json();
+        assert_eq!(body["stream"], json!(false));
+        assert_eq!(body["response_format"], json!({ "type": "json_object" }));
+        assert_eq!(body["messages"][1]["content"], json!("run"));
+    }
+}
@@ -0,0 +1,448 @@
+use std::sync::Mutex;
+
+use futures_util::stream::{self, StreamExt, TryStreamExt};
+
+use super::{
+    OnEvent, TranslateError, TranslateRequest, TranslateResult, TranslateStreamEvent,
+    TranslationEngine,
+};
+use crate::glossary;
+
+// Chunks translated at the same time.
+pub(super) const MAX_PARALLEL_CHUNKS: usize = 3;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Chunk {
+    Translate(String),
+    // Code blocks and the whitespace between chunks, copied as is.
+    Keep(String),
+}
+
+// Translates `request` in chunks of at most `engine.max_chunk_len()` chars.
+// Chunks run concurrently but their output is streamed in order: the first
+// unfinished chunk streams live, later ones are held back until it is done.
+pub(super) async fn stream_chunked(
+    engine: &dyn TranslationEngine,
+    request: &TranslateRequest,
+    on_event: &OnEvent<'_>,
+) -> Result<TranslateResult, TranslateError> {
+    let chunks = split(&request.text, engine.max_chunk_len());
+    if let [Chunk::Translate(text)] = chunks.as_slice() {
+        if *text == request.text {
+            return engine.stream(request, on_event).await;
+        }
+    }
+
+    on_event(TranslateStreamEvent::Started {
+        text: request.text.clone(),
+        from: request.from.clone(),
+        to: request.to.clone(),
+    });
+
+    let emitter = OrderedEmitter::new(chunks.len(), on_event);
+    let emitter = &emitter;
+    let results = stream::iter(chunks.into_iter().enumerate())
+        .map(|(index, chunk)| async move {
+            let result = match chunk {
+                Chunk::Keep(text) => (text, None),
+                Chunk::Translate(text) => {
+                    let chunk_request = TranslateRequest {
+                        glossary: glossary::matching(&request.glossary, &text),
+                        text,
+                        from: request.from.clone(),
+                        to: request.to.clone(),
+                        prompt: request.prompt.clone(),
+                    };
+                    let on_chunk_event = |event| {
+                        if let TranslateStreamEvent::Delta { content } = event {
+                            emitter.delta(index, content);
+                        }
+                    };
+                    let result = engine.stream(&chunk_request, &on_chunk_event).await?;
+                    (result.translated.clone(), Some(result))
+                }
+            };
+            Ok::<_, TranslateError>((index, result))
+        })
+        .buffered(MAX_PARALLEL_CHUNKS)
+        .and_then(|(index, (text, result))| async move {
+            emitter.finish(index, text);
+            Ok(result)
+        })
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    let translated = emitter.output();
+    let from = results
+        .iter()
+        .flatten()
+        .map(|result| result.from.as_str())
+        .find(|from| *from != "auto")
+        .unwrap_or(&request.from);
+    Ok(request.result(translated, from))
+}
+
+// Splits text at paragraph boundaries, then sentences, then characters, so that
+// every `Translate` chunk is at most `max_len` chars. Fenced code blocks and the
+// whitespace around chunks become `Keep` chunks.
+fn split(text: &str, max_len: usize) -> Vec<Chunk> {
+    let max_len = max_len.max(1);
+    let mut chunks = Vec::new();
+
+    for (block, is_code) in code_blocks(text) {
+        if is_code {
+            push_keep(&mut chunks, block);
+            continue;
+        }
+
+        let mut current = String::new();
+        for piece in paragraphs(block)
+            .into_iter()
+            .flat_map(|paragraph| split_long(paragraph, max_len))
+        {
+            if !current.is_empty() && char_len(&current) + char_len(piece) > max_len {
+                push_translate(&mut chunks, &current);
+                current.clear();
+            }
+            current.push_str(piece);
+        }
+        push_translate(&mut chunks, &current);
+    }
+    chunks
+}
+
+// Alternating prose and ``` fenced ``` blocks. An unclosed fence runs to the end.
+fn code_blocks(text: &str) -> Vec<(&str, bool)> {
+    let mut blocks = Vec::new();
+    let mut start = 0;
+    let mut code_start = None;
+    let mut line_start = 0;
+
+    for line in text.split_inclusive('\n') {
+        let line_end = line_start + line.len();
+        if line.trim_start().starts_with("```") {
+            match code_start {
+                None => {
+                    blocks.push((&text[start..line_start], false));
+                    code_start = Some(line_start);
+                }
+                Some(code) => {
+                    blocks.push((&text[code..line_end], true));
+                    code_start = None;
+                    start = line_end;
+                }
+            }
+        }
+        line_start = line_end;
+    }
+
+    match code_start {
+        Some(code) => blocks.push((&text[code..], true)),
+        None => blocks.push((&text[start..], false)),
+    }
+    blocks.retain(|(block, _)| !block.is_empty());
+    blocks
+}
+
+// Paragraphs with the blank lines that follow them.
+fn paragraphs(text: &str) -> Vec<&str> {
+    let mut paragraphs = Vec::new();
+    let mut start = 0;
+    let mut previous_blank = false;
+    let mut line_start = 0;
+
+    for line in text.split_inclusive('\n') {
+        let blank = line.trim().is_empty();
+        if previous_blank && !blank && line_start > start {
+            paragraphs.push(&text[start..line_start]);
+            start = line_start;
+        }
+        previous_blank = blank;
+        line_start += line.len();
+    }
+    if start < text.len() {
+        paragraphs.push(&text[start..]);
+    }
+    paragraphs
+}
+
+fn split_long(text: &str, max_len: usize) -> Vec<&str> {
+    if char_len(text) <= max_len {
+        return vec![text];
+    }
+
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        let at_end = chars.peek().is_none_or(|(_, next)| next.is_whitespace());
+        let boundary = matches!(c, '。' | '！' | '？' | '；' | '\n')
+            || (matches!(c, '.' | '!' | '?' | ';') && at_end);
+        if boundary {
+            let end = index + c.len_utf8();
+            pieces.push(&text[start..end]);
+            start = end;
+        }
+    }
+    if start < text.len() {
+        pieces.push(&text[start..]);
+    }
+
+    pieces
+        .into_iter()
+        .flat_map(|sentence| split_chars(sentence, max_len))
+        .collect()
+}
+
+fn split_chars(text: &str, max_len: usize) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    for (count, (index, _)) in text.char_indices().enumerate() {
+        if count > 0 && count % max_len == 0 {
+            pieces.push(&text[start..index]);
+            start = index;
+        }
+    }
+    pieces.push(&text[start..]);
+    pieces
+}
+
+// Keeps leading and trailing whitespace out of the engine's hands.
+fn push_translate(chunks: &mut Vec<Chunk>, text: &str) {
+    let trimmed_start = text.trim_start();
+    let leading = &text[..text.len() - trimmed_start.len()];
+    let trimmed = trimmed_start.trim_end();
+    let trailing = &trimmed_start[trimmed.len()..];
+
+    push_keep(chunks, leading);
+    if !trimmed.is_empty() {
+        chunks.push(Chunk::Translate(trimmed.to_string()));
+    }
+    push_keep(chunks, trailing);
+}
+
+fn push_keep(chunks: &mut Vec<Chunk>, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    match chunks.last_mut() {
+        Some(Chunk::Keep(kept)) => kept.push_str(text),
+        _ => chunks.push(Chunk::Keep(text.to_string())),
+    }
+}
+
+fn char_len(text: &str) -> usize {
+    text.chars().count()
+}
+
+struct OrderedEmitter<'a> {
+    state: Mutex<EmitterState>,
+    on_event: &'a OnEvent<'a>,
+}
+
+struct EmitterState {
+    // Index of the chunk currently streaming live.
+    current: usize,
+    // Deltas of later chunks, held until their turn.
+    pending: Vec<Vec<String>>,
+    // Whether the chunk has streamed any delta.
+    streamed: Vec<bool>,
+    output: Vec<String>,
+}
+
+impl<'a> OrderedEmitter<'a> {
+    fn new(len: usize, on_event: &'a OnEvent<'a>) -> OrderedEmitter<'a> {
+        OrderedEmitter {
+            state: Mutex::new(EmitterState {
+                current: 0,
+                pending: vec![Vec::new(); len],
+                streamed: vec![false; len],
+                output: vec![String::new(); len],
+            }),
+            on_event,
+        }
+    }
+
+    fn delta(&self, index: usize, content: String) {
+        let mut state = self
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        state.streamed[index] = true;
+        if index == state.current {
+            (self.on_event)(TranslateStreamEvent::Delta { content });
+        } else {
+            state.pending[index].push(content);
+        }
+    }
+
+    // Called in chunk order. Emits whatever of the chunk was not streamed, then
+    // flushes the next chunk's held back deltas and lets it stream live.
+    fn finish(&self, index: usize, text: String) {
+        let mut state = self
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if !state.streamed[index] && !text.is_empty() {
+            (self.on_event)(TranslateStreamEvent::Delta {
+                content: text.clone(),
+            });
+        }
+        state.output[index] = text;
+
+        state.current = index + 1;
+        if let Some(pending) = state.pending.get_mut(index + 1) {
+            for content in pending.drain(..) {
+                (self.on_event)(TranslateStreamEvent::Delta { content });
+            }
+        }
+    }
+
+    fn output(&self) -> String {
+        let state = self
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        state.output.concat()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{BoxFuture, TranslateResult};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn translate(text: &str) -> Chunk {
+        Chunk::Translate(text.to_string())
+    }
+
+    fn keep(text: &str) -> Chunk {
+        Chunk::Keep(text.to_string())
+    }
+
+    #[test]
+    fn short_text_is_a_single_chunk() {
+        assert_eq!(split("Hello world", 100), vec![translate("Hello world")]);
+    }
+
+    #[test]
+    fn packs_paragraphs_and_keeps_line_breaks() {
+        let text = "First paragraph.\n\nSecond one.\n\n\nThird paragraph here.\n";
+
+        assert_eq!(
+            split(text, 32),
+            vec![
+                translate("First paragraph.\n\nSecond one."),
+                keep("\n\n\n"),
+                translate("Third paragraph here."),
+                keep("\n"),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_fenced_code_blocks() {
+        let text = "Run this:\n```sh\nls -la\n```\nThen check.";
+
+        assert_eq!(
+            split(text, 100),
+            vec![
+                translate("Run this:"),
+                keep("\n```sh\nls -la\n```\n"),
+                translate("Then check."),
+            ]
+        );
+    }
+
+    #[test]
+    fn splits_long_paragraphs_at_sentences() {
+        let text = "One two three. Four five six. 七八九。十";
+
+        assert_eq!(
+            split(text, 16),
+            vec![
+                translate("One two three."),
+                keep(" "),
+                translate("Four five six."),
+                keep(" "),
+                translate("七八九。十"),
+            ]
+        );
+        assert_eq!(
+            split("abcdefghij", 4),
+            vec![translate("abcd"), translate("efgh"), translate("ij")]
+        );
+    }
+
+    // Upper-cases every chunk, finishing later chunks first.
+    struct UpperEngine {
+        calls: AtomicUsize,
+    }
+
+    impl TranslationEngine for UpperEngine {
+        fn id(&self) -> &'static str {
+            "upper"
+        }
+
+        fn max_chunk_len(&self) -> usize {
+            12
+        }
+
+        fn stream<'a>(
+            &'a self,
+            request: &'a TranslateRequest,
+            on_event: &'a OnEvent<'a>,
+        ) -> BoxFuture<'a, Result<TranslateResult, TranslateError>> {
+            Box::pin(async move {
+                let call = self.calls.fetch_add(1, Ordering::SeqCst);
+                for _ in 0..(3 - call.min(3)) {
+                    tokio::task::yield_now().await;
+                }
+                let translated = request.text.to_uppercase();
+                on_event(TranslateStreamEvent::Delta {
+                    content: translated.clone(),
+                });
+                Ok(request.result(translated, "en"))
+            })
+        }
+
+        fn health_check(&self) -> BoxFuture<'_, Result<(), TranslateError>> {
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    #[tokio::test]
+    async fn streams_chunks_in_order() {
+        let engine = UpperEngine {
+            calls: AtomicUsize::new(0),
+        };
+        let request = TranslateRequest {
+            text: "first line\n\nsecond line\n\nthird line".to_string(),
+            from: "auto".to_string(),
+            to: "en".to_string(),
+            glossary: Vec::new(),
+            prompt: None,
+        };
+        let events = Mutex::new(Vec::new());
+
+        let result = stream_chunked(&engine, &request, &|event| {
+            events.lock().unwrap().push(event)
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(result.translated, "FIRST LINE\n\nSECOND LINE\n\nTHIRD LINE");
+        assert_eq!(result.from, "en");
+        let events = events.lock().unwrap();
+        assert!(matches!(events[0], TranslateStreamEvent::Started { .. }));
+        let streamed = events[1..]
+            .iter()
+            .map(|event| match event {
+                TranslateStreamEvent::Delta { content } => content.as_str(),
+                TranslateStreamEvent::Started { .. } => panic!("started twice"),
+            })
+            .collect::<String>();
+        assert_eq!(streamed, result.translated);
+    }
+}
@@ -0,0 +1,364 @@
+use reqwest::Method;
+use serde_json::Value;
+
+use crate::glossary;
+
+use super::retry::status_error;
+use super::{
+    http_client, BoxFuture, EngineConfig, OnEvent, TranslateError, TranslateRequest,
+    TranslateResult, TranslationEngine, REQUEST_TIMEOUT,
+};
+
+// An HTTP endpoint described by templates. The URL, headers and JSON body may
+// use {text} {from} {to} {key} placeholders, and `result` is a JSON pointer to
+// the translation in the response.
+pub(super) struct CustomEngine {
+    url: String,
+    key: String,
+    method: String,
+    headers: String,
+    body: String,
+    result: String,
+    // The url and a hash of the other templates, so editing any of them
+    // leaves the cached translations of the old request behind.
+    model: String,
+}
+
+impl CustomEngine {
+    pub fn new(engine_config: &EngineConfig) -> CustomEngine {
+        let url = engine_config.custom_url.trim().to_string();
+        let method = engine_config.custom_method.trim().to_uppercase();
+        let headers = engine_config.custom_headers.clone();
+        let body = engine_config.custom_body.trim().to_string();
+        let result = engine_config.custom_result.trim().to_string();
+        let model = format!(
+            "{url}#{:016x}",
+            template_hash(&[&method, &headers, &body, &result])
+        );
+
+        CustomEngine {
+            url,
+            key: engine_config.custom_key.trim().to_string(),
+            method,
+            headers,
+            body,
+            result,
+            model,
+        }
+    }
+
+    async fn request(&self, request: &TranslateRequest) -> Result<TranslateResult, TranslateError> {
+        if self.url.is_empty() {
+            return Err("Custom translator url is not configured".into());
+        }
+
+        let protected = glossary::protect(&request.text, &request.glossary);
+        let fill = |template: &str, encode: fn(&str) -> String| {
+            template
+                .replace("{text}", &encode(&protected.text))
+                .replace("{from}", &encode(&request.from))
+                .replace("{to}", &encode(&request.to))
+                .replace("{key}", &encode(&self.key))
+        };
+
+        let method = match self.method.as_str() {
+            "" => Method::GET,
+            method => Method::from_bytes(method.as_bytes())
+                .map_err(|_| format!("Invalid custom translator method {method}"))?,
+        };
+        let client = http_client(REQUEST_TIMEOUT)?;
+        let mut builder = client.request(method, fill(&self.url, urlencoding_encode));
+        for line in self.headers.lines().filter(|line| !line.trim().is_empty()) {
+            let (name, value) = line
+                .split_once(':')
+                .ok_or_else(|| format!("Invalid custom translator header: {line}"))?;
+            builder = builder.header(name.trim(), fill(value.trim(), str::to_string));
+        }
+        if !self.body.is_empty() {
+            let mut body = serde_json::from_str::<Value>(&self.body)
+                .map_err(|error| format!("Invalid custom translator body template: {error}"))?;
+            fill_json(&mut body, &|template| fill(template, str::to_string));
+            builder = builder.json(&body);
+        }
+
+        let response = builder.send().await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let message = format!("Custom translate request failed: {status}");
+            return Err(status_error(status, response.headers(), message));
+        }
+
+        let body = response.text().await?;
+        let translated = if self.result.is_empty() {
+            parse_custom_response(&body)
+        } else {
+            serde_json::from_str::<Value>(&body)
+                .ok()
+                .and_then(|json| pointer_text(&json, &self.result))
+        };
+        let translated = translated
+            .filter(|translated| !translated.is_empty())
+            .ok_or("Unexpected custom translate response format")?;
+        let translated = protected.restore(&translated);
+
+        Ok(request.result(translated, &request.from))
+    }
+
+    async fn ping(&self) -> Result<(), TranslateError> {
+        let request = TranslateRequest {
+            text: "hello".to_string(),
+            from: "en".to_string(),
+            to: "zh-CN".to_string(),
+            glossary: Vec::new(),
+            prompt: None,
+        };
+        self.request(&request).await.map(|_| ())
+    }
+}
+
+impl TranslationEngine for CustomEngine {
+    fn id(&self) -> &'static str {
+        "custom"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn max_chunk_len(&self) -> usize {
+        1500
+    }
+
+    fn stream<'a>(
+        &'a self,
+        request: &'a TranslateRequest,
+        _on_event: &'a OnEvent<'a>,
+    ) -> BoxFuture<'a, Result<TranslateResult, TranslateError>> {
+        Box::pin(self.request(request))
+    }
+
+    fn health_check(&self) -> BoxFuture<'_, Result<(), TranslateError>> {
+        Box::pin(self.ping())
+    }
+}
+
+// FNV-1a, which unlike the std hasher stays the same across builds, as the
+// translation cache outlives them.
+fn template_hash(parts: &[&str]) -> u64 {
+    parts.iter().fold(0xcbf2_9ce4_8422_2325, |hash, part| {
+        part.bytes().chain([0]).fold(hash, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x100_0000_01b3)
+        })
+    })
+}
+
+// Fills the placeholders in every string of a JSON body template, so values
+// are escaped by serialization rather than spliced into JSON text.
+fn fill_json(value: &mut Value, fill: &dyn Fn(&str) -> String) {
+    match value {
+        Value::String(text) => *text = fill(text),
+        Value::Array(items) => items.iter_mut().for_each(|item| fill_json(item, fill)),
+        Value::Object(fields) => fields.values_mut().for_each(|field| fill_json(field, fill)),
+        _ => {}
+    }
+}
+
+// The text at `pointer`, joining arrays of strings such as ["Hallo", "Welt"].
+fn pointer_text(json: &Value, pointer: &str) -> Option<String> {
+    match json.pointer(pointer)? {
+        Value::String(text) => Some(text.clone()),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| item.as_str())
+            .collect::<Option<Vec<_>>>()
+            .map(|items| items.join("\n")),
+        _ => None,
+    }
+}
+
+fn parse_custom_response(body: &str) -> Option<String> {
+    if let Ok(json) = serde_json::from_str::<Value>(body) {
+        for key in [
+            "translated",
+            "translatedText",
+            "translation",
+            "text",
+            "result",
+        ] {
+            if let Some(value) = json.get(key).and_then(|value| value.as_str()) {
+                return Some(value.to_string());
+            }
+        }
+        return json.as_str().map(|value| value.to_string());
+    }
+
+    let trimmed = body.trim();
+    if trimmed.is_empty() {
+        None
+    } else {
+        Some(trimmed.to_string())
+    }
+}
+
+fn urlencoding_encode(value: &str) -> String {
+    let mut encoded = String::new();
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::mock_server::{MockResponse, MockServer};
+    use crate::engine::tests::engine_config;
+    use crate::glossary::GlossaryEntry;
+
+    fn engine(url: String, key: &str) -> CustomEngine {
+        let mut config = engine_config("custom");
+        config.custom_url = url;
+        config.custom_key = key.to_string();
+        CustomEngine::new(&config)
+    }
+
+    #[test]
+    fn parse_custom_response_reads_known_fields() {
+        let body = r#"{"translated":"你好"}"#;
+        assert_eq!(parse_custom_response(body).as_deref(), Some("你好"));
+    }
+
+    #[test]
+    fn parse_custom_response_falls_back_to_plain_text() {
+        assert_eq!(parse_custom_response("  hello  ").as_deref(), Some("hello"));
+    }
+
+    #[test]
+    fn model_changes_with_any_request_template() {
+        let url = "http://localhost/translate".to_string();
+        let base = engine(url.clone(), "key").model().to_string();
+        assert!(base.starts_with("http://localhost/translate#"));
+        assert_eq!(engine(url.clone(), "other key").model(), base);
+
+        let mut config = engine_config("custom");
+        config.custom_url = url;
+        for edit in [
+            |config: &mut EngineConfig| config.custom_method = "PUT".to_string(),
+            |config: &mut EngineConfig| config.custom_headers = "X-Mode: fast".to_string(),
+            |config: &mut EngineConfig| config.custom_body = r#"{"q":"{text}"}"#.to_string(),
+            |config: &mut EngineConfig| config.custom_result = "/data/0".to_string(),
+        ] {
+            let mut edited = config.clone();
+            edit(&mut edited);
+            assert_ne!(CustomEngine::new(&edited).model(), base);
+        }
+    }
+
+    #[test]
+    fn urlencoding_encodes_non_ascii() {
+        assert_eq!(urlencoding_encode("a b"), "a%20b");
+        assert_eq!(urlencoding_encode("你好").len(), "你好".len() * 3);
+    }
+
+    #[tokio::test]
+    async fn fills_url_placeholders() {
+        let server = MockServer::start(vec![MockResponse::new(200, "text/plain", "bonjour")]);
+        let engine = engine(
+            format!(
+                "{}/tr?q={{text}}&sl={{from}}&tl={{to}}&k={{key}}",
+                server.url()
+            ),
+            "k 1",
+        );
+        let request = TranslateRequest {
+            text: "good day".to_string(),
+            from: "auto".to_string(),
+            to: "fr".to_string(),
+            glossary: Vec::new(),
+            prompt: None,
+        };
+
+        let result = engine.translate(&request).await.unwrap();
+
+        assert_eq!(result.translated, "bonjour");
+        assert_eq!(
+            server.requests()[0].path,
+            "/tr?q=good%20day&sl=auto&tl=fr&k=k%201"
+        );
+    }
+
+    #[tokio::test]
+    async fn keeps_glossary_terms_out_of_the_request() {
+        let server = MockServer::start(vec![MockResponse::new(200, "text/plain", "打开 ⟦0⟧")]);
+        let engine = engine(format!("{}/tr?q={{text}}", server.url()), "");
+        let request = TranslateRequest {
+            text: "Open Rotor".to_string(),
+            from: "auto".to_string(),
+            to: "zh-CN".to_string(),
+            glossary: vec![GlossaryEntry {
+                term: "Rotor".to_string(),
+                translation: "转子".to_string(),
+            }],
+            prompt: None,
+        };
+
+        let result = engine.translate(&request).await.unwrap();
+
+        assert_eq!(result.translated, "打开 转子");
+        assert_eq!(
+            server.requests()[0].path,
+            "/tr?q=Open%20%E2%9F%A60%E2%9F%A7"
+        );
+    }
+
+    #[tokio::test]
+    async fn posts_json_body_and_reads_result_pointer() {
+        let server = MockServer::start(vec![MockResponse::json(
+            200,
+            serde_json::json!({ "data": { "translations": ["Hallo", "\"Welt\""] } }),
+        )]);
+        let mut config = engine_config("custom");
+        config.custom_url = format!("{}/translate", server.url());
+        config.custom_key = "secret".to_string();
+        config.custom_method = "post".to_string();
+        config.custom_headers = "Authorization: Bearer {key}\n\nX-Target: {to}".to_string();
+        config.custom_body =
+            r#"{"q": "{text}", "source": "{from}", "target": "{to}", "format": "text"}"#
+                .to_string();
+        config.custom_result = "/data/translations".to_string();
+        let request = TranslateRequest {
+            text: "Hello \"world\"".to_string(),
+            from: "auto".to_string(),
+            to: "de".to_string(),
+            glossary: Vec::new(),
+            prompt: None,
+        };
+
+        let result = CustomEngine::new(&config)
+            .translate(&request)
+            .await
+            .unwrap();
+
+        assert_eq!(result.translated, "Hallo\n\"Welt\"");
+        let sent = &server.requests()[0];
+        assert_eq!(sent.method, "POST");
+        assert_eq!(sent.path, "/translate");
+        assert_eq!(sent.header("authorization"), Some("Bearer secret"));
+        assert_eq!(sent.header("x-target"), Some("de"));
+        assert_eq!(
+            sent.json(),
+            serde_json::json!({
+                "q": "Hello \"world\"",
+                "source": "auto",
+                "target": "de",
+                "format": "text",
+            })
+        );
+    }
+}
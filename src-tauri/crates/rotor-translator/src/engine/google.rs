@@ -0,0 +1,161 @@
+use crate::glossary;
+
+use super::dictionary::{self, DictionaryResult};
+use super::retry::status_error;
+use super::{
+    http_client, BoxFuture, OnEvent, TranslateError, TranslateRequest, TranslateResult,
+    TranslationEngine, REQUEST_TIMEOUT,
+};
+
+const GOOGLE_TRANSLATE_URL: &str = "https://translate.googleapis.com/translate_a/single";
+
+pub(super) struct GoogleEngine {
+    url: String,
+}
+
+impl GoogleEngine {
+    pub fn new() -> GoogleEngine {
+        GoogleEngine {
+            url: GOOGLE_TRANSLATE_URL.to_string(),
+        }
+    }
+
+    // Requests the `data` kinds (dt) for `text`: t for the translation, bd,
+    // md and rm for dictionary data.
+    async fn fetch(
+        &self,
+        request: &TranslateRequest,
+        text: &str,
+        data: &[&str],
+    ) -> Result<serde_json::Value, TranslateError> {
+        let client = http_client(REQUEST_TIMEOUT)?;
+
+        let mut query = vec![
+            ("client", "gtx"),
+            ("sl", request.from.as_str()),
+            ("tl", request.to.as_str()),
+            ("q", text),
+        ];
+        query.extend(data.iter().map(|data| ("dt", *data)));
+        let response = client.get(&self.url).query(&query).send().await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let message = format!("Google translate request failed: {status}");
+            return Err(status_error(status, response.headers(), message));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    async fn request(&self, request: &TranslateRequest) -> Result<TranslateResult, TranslateError> {
+        let protected = glossary::protect(&request.text, &request.glossary);
+        let body = self.fetch(request, &protected.text, &["t"]).await?;
+
+        let translated = body
+            .get(0)
+            .and_then(|segments| segments.as_array())
+            .map(|segments| {
+                segments
+                    .iter()
+                    .filter_map(|segment| segment.get(0).and_then(|translated| translated.as_str()))
+                    .collect::<String>()
+            })
+            .filter(|translated| !translated.is_empty())
+            .ok_or("Unexpected google translate response format")?;
+
+        let translated = protected.restore(&translated);
+        let from = body.get(2).and_then(|from| from.as_str()).unwrap_or("auto");
+
+        Ok(request.result(translated, from))
+    }
+
+    async fn lookup_word(
+        &self,
+        request: &TranslateRequest,
+    ) -> Result<Option<DictionaryResult>, TranslateError> {
+        let body = self
+            .fetch(request, &request.text, &["t", "bd", "md", "rm"])
+            .await?;
+        Ok(dictionary::parse_google_response(request, &body))
+    }
+
+    async fn ping(&self) -> Result<(), TranslateError> {
+        let request = TranslateRequest {
+            text: "hello".to_string(),
+            from: "en".to_string(),
+            to: "zh-CN".to_string(),
+            glossary: Vec::new(),
+            prompt: None,
+        };
+        self.request(&request).await.map(|_| ())
+    }
+}
+
+impl TranslationEngine for GoogleEngine {
+    fn id(&self) -> &'static str {
+        "google"
+    }
+
+    // The text travels in the query string.
+    fn max_chunk_len(&self) -> usize {
+        1500
+    }
+
+    fn stream<'a>(
+        &'a self,
+        request: &'a TranslateRequest,
+        _on_event: &'a OnEvent<'a>,
+    ) -> BoxFuture<'a, Result<TranslateResult, TranslateError>> {
+        Box::pin(self.request(request))
+    }
+
+    fn lookup<'a>(
+        &'a self,
+        request: &'a TranslateRequest,
+    ) -> BoxFuture<'a, Result<Option<DictionaryResult>, TranslateError>> {
+        Box::pin(self.lookup_word(request))
+    }
+
+    fn health_check(&self) -> BoxFuture<'_, Result<(), TranslateError>> {
+        Box::pin(self.ping())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::mock_server::{MockResponse, MockServer};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn joins_translated_segments_and_reads_source_language() {
+        let server = MockServer::start(vec![MockResponse::json(
+            200,
+            json!([
+                [["你好，", "Hello, ", null], ["世界", "world", null]],
+                null,
+                "en"
+            ]),
+        )]);
+        let engine = GoogleEngine {
+            url: format!("{}/translate_a/single", server.url()),
+        };
+        let request = TranslateRequest {
+            text: "Hello, world".to_string(),
+            from: "auto".to_string(),
+            to: "zh-CN".to_string(),
+            glossary: Vec::new(),
+            prompt: None,
+        };
+
+        let result = engine.translate(&request).await.unwrap();
+
+        assert_eq!(result.translated, "你好，世界");
+        assert_eq!(result.from, "en");
+        let path = &server.requests()[0].path;
+        assert!(path.starts_with("/translate_a/single?"));
+        assert!(path.contains("tl=zh-CN"));
+        assert!(path.contains("q=Hello%2C+world"));
+    }
+}
@@ -0,0 +1,138 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+// Minimal HTTP/1.1 server for engine tests: every connection is answered with
+// the next canned response, and the requests are recorded for assertions.
+pub(super) struct MockServer {
+    url: String,
+    requests: Arc<Mutex<Vec<MockRequest>>>,
+}
+
+#[derive(Clone, Debug)]
+pub(super) struct MockRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl MockRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn json(&self) -> serde_json::Value {
+        serde_json::from_str(&self.body).expect("request body is JSON")
+    }
+}
+
+pub(super) struct MockResponse {
+    status: u16,
+    content_type: &'static str,
+    body: String,
+}
+
+impl MockResponse {
+    pub fn new(status: u16, content_type: &'static str, body: impl Into<String>) -> MockResponse {
+        MockResponse {
+            status,
+            content_type,
+            body: body.into(),
+        }
+    }
+
+    pub fn json(status: u16, body: serde_json::Value) -> MockResponse {
+        MockResponse::new(status, "application/json", body.to_string())
+    }
+}
+
+impl MockServer {
+    pub fn start(responses: Vec<MockResponse>) -> MockServer {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind mock server");
+        let url = format!(
+            "http://{}",
+            listener.local_addr().expect("mock server address")
+        );
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let recorded = Arc::clone(&requests);
+        thread::spawn(move || {
+            for response in responses {
+                let Ok((stream, _)) = listener.accept() else {
+                    return;
+                };
+                if let Err(error) = serve(stream, response, &recorded) {
+                    eprintln!("mock server error: {error}");
+                }
+            }
+        });
+
+        MockServer { url, requests }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+fn serve(
+    stream: TcpStream,
+    response: MockResponse,
+    requests: &Mutex<Vec<MockRequest>>,
+) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+
+    let content_length = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.parse::<usize>().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    requests.lock().unwrap().push(MockRequest {
+        method,
+        path,
+        headers,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    });
+
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {} Mock\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        response.content_type,
+        response.body.len(),
+        response.body
+    )?;
+    stream.flush()
+}
@@ -0,0 +1,825 @@
+mod chat;
+mod chunk;
+mod custom;
+mod dictionary;
+mod google;
+#[cfg(test)]
+mod mock_server;
+mod network;
+mod offline;
+mod ollama;
+mod retry;
+
+use std::error::Error;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use futures_util::stream::{self, StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+
+use crate::cache::{CacheKey, CacheSettings, TranslationCache};
+use crate::detect::{self, Detection};
+use crate::glossary::{self, GlossaryEntry};
+use crate::history::TranslationHistory;
+use crate::prompt::{self, PromptPreset};
+
+use self::chat::ChatCompletionsEngine;
+use self::custom::CustomEngine;
+pub use self::dictionary::{is_lookup, Definition, DictionaryEntry, DictionaryResult};
+use self::google::GoogleEngine;
+pub use self::network::NetworkSettings;
+use self::offline::OfflineEngine;
+use self::ollama::OllamaEngine;
+use self::retry::{RetryPolicy, RetryingEngine};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+const LLM_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+pub type TranslateError = Box<dyn Error + Send + Sync>;
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+pub type OnEvent<'a> = dyn Fn(TranslateStreamEvent) + Send + Sync + 'a;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranslateResult {
+    pub text: String,
+    pub translated: String,
+    pub from: String,
+    pub to: String,
+    // The engine that produced the translation, a fallback when the configured
+    // one failed.
+    pub engine: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "event", rename_all = "camelCase")]
+pub enum TranslateStreamEvent {
+    Started {
+        text: String,
+        from: String,
+        to: String,
+    },
+    Delta {
+        content: String,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct TranslateRequest {
+    pub text: String,
+    pub from: String,
+    pub to: String,
+    // Glossary entries whose terms occur in `text`.
+    pub glossary: Vec<GlossaryEntry>,
+    // The prompt preset for LLM engines, None for the built-in prompt.
+    pub prompt: Option<PromptPreset>,
+}
+
+impl TranslateRequest {
+    fn result(&self, translated: String, from: &str) -> TranslateResult {
+        TranslateResult {
+            text: self.text.clone(),
+            translated,
+            from: from.to_string(),
+            to: self.to.clone(),
+            engine: String::new(),
+        }
+    }
+}
+
+// A translation backend. `stream` is the only way text gets translated; engines
+// that cannot stream simply return the whole result without emitting deltas.
+pub trait TranslationEngine: Send + Sync {
+    fn id(&self) -> &'static str;
+
+    // The model behind the engine, empty when it has no choice of model.
+    fn model(&self) -> &str {
+        ""
+    }
+
+    // Longest text, in chars, sent in one request. Longer texts are split.
+    fn max_chunk_len(&self) -> usize {
+        4000
+    }
+
+    fn stream<'a>(
+        &'a self,
+        request: &'a TranslateRequest,
+        on_event: &'a OnEvent<'a>,
+    ) -> BoxFuture<'a, Result<TranslateResult, TranslateError>>;
+
+    fn translate<'a>(
+        &'a self,
+        request: &'a TranslateRequest,
+    ) -> BoxFuture<'a, Result<TranslateResult, TranslateError>> {
+        self.stream(request, &ignore_event)
+    }
+
+    // Dictionary data for a single word, None when the engine has none.
+    fn lookup<'a>(
+        &'a self,
+        _request: &'a TranslateRequest,
+    ) -> BoxFuture<'a, Result<Option<DictionaryResult>, TranslateError>> {
+        Box::pin(async { Ok(None) })
+    }
+
+    // Target language codes the engine accepts, empty when it takes any.
+    fn supported_languages(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    // Checks that the engine is configured and its service answers.
+    fn health_check(&self) -> BoxFuture<'_, Result<(), TranslateError>>;
+}
+
+fn ignore_event(_event: TranslateStreamEvent) {}
+
+struct EngineRegistration {
+    id: &'static str,
+    build: fn(&EngineConfig) -> Box<dyn TranslationEngine>,
+}
+
+// Engines selectable with `translator_engine`. The first one is the fallback
+// for unknown ids.
+const ENGINES: &[EngineRegistration] = &[
+    EngineRegistration {
+        id: "google",
+        build: |_| Box::new(GoogleEngine::new()),
+    },
+    EngineRegistration {
+        id: "deepseek",
+        build: |config| Box::new(ChatCompletionsEngine::deepseek(config)),
+    },
+    EngineRegistration {
+        id: "openai",
+        build: |config| Box::new(ChatCompletionsEngine::openai_compatible(config)),
+    },
+    EngineRegistration {
+        id: "ollama",
+        build: |config| Box::new(OllamaEngine::new(config)),
+    },
+    EngineRegistration {
+        id: "custom",
+        build: |config| Box::new(CustomEngine::new(config)),
+    },
+    EngineRegistration {
+        id: "offline",
+        build: |config| Box::new(OfflineEngine::new(config)),
+    },
+];
+
+pub fn engine_ids() -> Vec<&'static str> {
+    ENGINES.iter().map(|registration| registration.id).collect()
+}
+
+pub fn build_engine(engine_config: &EngineConfig) -> Box<dyn TranslationEngine> {
+    let registration = ENGINES
+        .iter()
+        .find(|registration| registration.id == engine_config.engine)
+        .unwrap_or(&ENGINES[0]);
+    (registration.build)(engine_config)
+}
+
+#[derive(Clone, Debug)]
+pub struct EngineConfig {
+    pub engine: String,
+    pub deepseek_api_key: String,
+    pub deepseek_model: String,
+    pub openai_base_url: String,
+    pub openai_api_key: String,
+    pub openai_model: String,
+    pub ollama_url: String,
+    pub ollama_model: String,
+    pub custom_url: String,
+    pub custom_key: String,
+    pub custom_method: String,
+    pub custom_headers: String,
+    pub custom_body: String,
+    pub custom_result: String,
+    // A Marian model directory, or a directory of them named by language pair.
+    pub offline_model_dir: String,
+    pub target_lang: String,
+    // Languages the user reads, primary first.
+    pub my_languages: Vec<String>,
+    // Engines tried in order when the configured one fails.
+    pub fallback_engines: Vec<String>,
+}
+
+impl EngineConfig {
+    pub fn from_app_config() -> EngineConfig {
+        let config = rotor_common::AppConfig::lock_global();
+        let get = |key: &str| config.get(key).cloned().unwrap_or_default();
+        EngineConfig {
+            engine: config
+                .get("translator_engine")
+                .cloned()
+                .unwrap_or_else(|| "google".into()),
+            deepseek_api_key: get("translator_deepseek_api_key"),
+            deepseek_model: config
+                .get("translator_deepseek_model")
+                .cloned()
+                .unwrap_or_else(|| rotor_common::config::DEFAULT_TRANSLATOR_DEEPSEEK_MODEL.into()),
+            openai_base_url: get("translator_openai_base_url"),
+            openai_api_key: get("translator_openai_api_key"),
+            openai_model: get("translator_openai_model"),
+            ollama_url: get("translator_ollama_url"),
+            ollama_model: get("translator_ollama_model"),
+            custom_url: get("translator_custom_url"),
+            custom_key: get("translator_custom_key"),
+            custom_method: get("translator_custom_method"),
+            custom_headers: get("translator_custom_headers"),
+            custom_body: get("translator_custom_body"),
+            custom_result: get("translator_custom_result"),
+            offline_model_dir: get("translator_offline_model_dir"),
+            target_lang: config
+                .get("translator_target_lang")
+                .cloned()
+                .unwrap_or_else(|| "auto".into()),
+            my_languages: split_list(&get("translator_my_languages")),
+            fallback_engines: split_list(&get("translator_fallback_engines")),
+        }
+    }
+
+    // The configured engine followed by its fallbacks, without repeats.
+    fn engine_chain(&self) -> Vec<&str> {
+        let mut chain = vec![self.engine.as_str()];
+        for engine in &self.fallback_engines {
+            if !chain.contains(&engine.as_str()) {
+                chain.push(engine);
+            }
+        }
+        chain
+    }
+}
+
+fn split_list(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+// Translates `text` with the prompt preset `preset`, or the configured one
+// when it is None.
+pub async fn translate<F>(
+    text: &str,
+    preset: Option<&str>,
+    on_event: F,
+) -> Result<TranslateResult, TranslateError>
+where
+    F: Fn(TranslateStreamEvent) + Send + Sync,
+{
+    let engine_config = EngineConfig::from_app_config();
+    let detection = detect::detect(text);
+    let prompt = prompt::resolve(preset);
+    let request = TranslateRequest {
+        text: text.to_string(),
+        from: "auto".to_string(),
+        to: preset_target_lang(prompt.as_ref()).unwrap_or_else(|| {
+            resolve_target_lang(
+                &engine_config.target_lang,
+                &engine_config.my_languages,
+                detection,
+            )
+        }),
+        glossary: glossary::matching(&glossary::load(), text),
+        prompt,
+    };
+
+    // Engines that do not detect the source language report ours instead.
+    let source = detection
+        .filter(|detection| detection.confidence >= detect::MIN_CONFIDENCE)
+        .map_or("auto", |detection| detection.lang);
+
+    let result = translate_with_chain(&engine_config, &request, source, &on_event).await?;
+    TranslationHistory::global().record_translation(&result, &result.engine);
+    Ok(result)
+}
+
+// Translates each of `texts` on its own, all into the target language chosen
+// for the texts as a whole. Used for documents split into segments, which are
+// not recorded in the history.
+pub async fn translate_all(texts: &[String]) -> Result<Vec<TranslateResult>, TranslateError> {
+    let engine_config = EngineConfig::from_app_config();
+    let detection = detect::detect(&texts.join("\n"));
+    let prompt = prompt::resolve(None);
+    let to = preset_target_lang(prompt.as_ref()).unwrap_or_else(|| {
+        resolve_target_lang(
+            &engine_config.target_lang,
+            &engine_config.my_languages,
+            detection,
+        )
+    });
+    let source = detection
+        .filter(|detection| detection.confidence >= detect::MIN_CONFIDENCE)
+        .map_or("auto", |detection| detection.lang);
+    let glossary = glossary::load();
+
+    let engine_config = &engine_config;
+    let (to, glossary, prompt) = (&to, &glossary, &prompt);
+    stream::iter(texts)
+        .map(|text| async move {
+            let request = TranslateRequest {
+                text: text.clone(),
+                from: "auto".to_string(),
+                to: to.clone(),
+                glossary: glossary::matching(glossary, text),
+                prompt: prompt.clone(),
+            };
+            translate_with_chain(engine_config, &request, source, &ignore_event).await
+        })
+        .buffered(chunk::MAX_PARALLEL_CHUNKS)
+        .try_collect()
+        .await
+}
+
+// Runs `request` through the configured engine and its fallbacks. The next
+// engine is only tried when the previous one failed before streaming text.
+async fn translate_with_chain(
+    engine_config: &EngineConfig,
+    request: &TranslateRequest,
+    source: &str,
+    on_event: &OnEvent<'_>,
+) -> Result<TranslateResult, TranslateError> {
+    let retry_policy = RetryPolicy::from_app_config();
+    let cache_settings = CacheSettings::from_app_config();
+
+    let mut last_error = None;
+    for engine_id in engine_config.engine_chain() {
+        let engine = build_engine(&EngineConfig {
+            engine: engine_id.to_string(),
+            ..engine_config.clone()
+        });
+
+        let supported = engine.supported_languages();
+        if !supported.is_empty() && !supported.contains(&request.to.as_str()) {
+            last_error = Some(
+                format!(
+                    "The {} engine does not support target language {}",
+                    engine.id(),
+                    request.to
+                )
+                .into(),
+            );
+            continue;
+        }
+
+        // Once text is shown the next engine cannot start over.
+        let streamed = AtomicBool::new(false);
+        let on_event = |event| {
+            if let TranslateStreamEvent::Delta { .. } = event {
+                streamed.store(true, Ordering::Relaxed);
+            }
+            on_event(with_source(event, source));
+        };
+        let outcome = cached_stream(
+            &RetryingEngine::new(engine.as_ref(), retry_policy),
+            request,
+            TranslationCache::global(),
+            &cache_settings,
+            &on_event,
+        )
+        .await;
+
+        match outcome {
+            Ok(mut result) => {
+                if result.from == "auto" {
+                    result.from = source.to_string();
+                }
+                result.engine = engine.id().to_string();
+                return Ok(result);
+            }
+            Err(error) if !streamed.load(Ordering::Relaxed) => {
+                log::warn!("Translation with {} failed: {error}", engine.id());
+                last_error = Some(error);
+            }
+            Err(error) => return Err(error),
+        }
+    }
+    Err(last_error.unwrap_or_else(|| "No translation engine is configured".into()))
+}
+
+// Looks `text` up in the dictionary of the first engine in the chain that has
+// an entry for it. None when the text is not a single word or no engine knows it.
+pub async fn lookup(text: &str) -> Result<Option<DictionaryResult>, TranslateError> {
+    let enabled = rotor_common::AppConfig::lock_global()
+        .get("translator_dictionary")
+        .is_none_or(|enabled| enabled != "false");
+    if !enabled || !is_lookup(text) {
+        return Ok(None);
+    }
+
+    let engine_config = EngineConfig::from_app_config();
+    let detection = detect::detect(text);
+    let request = TranslateRequest {
+        text: text.trim().to_string(),
+        from: "auto".to_string(),
+        to: resolve_target_lang(
+            &engine_config.target_lang,
+            &engine_config.my_languages,
+            detection,
+        ),
+        glossary: Vec::new(),
+        prompt: None,
+    };
+
+    let mut last_error = None;
+    for engine_id in engine_config.engine_chain() {
+        let engine = build_engine(&EngineConfig {
+            engine: engine_id.to_string(),
+            ..engine_config.clone()
+        });
+        match engine.lookup(&request).await {
+            Ok(Some(mut result)) => {
+                if result.from == "auto" {
+                    if let Some(detection) = detection {
+                        result.from = detection.lang.to_string();
+                    }
+                }
+                result.engine = engine.id().to_string();
+                return Ok(Some(result));
+            }
+            Ok(None) => {}
+            Err(error) => {
+                log::warn!("Dictionary lookup with {} failed: {error}", engine.id());
+                last_error = Some(error);
+            }
+        }
+    }
+    last_error.map_or(Ok(None), Err)
+}
+
+fn with_source(event: TranslateStreamEvent, source: &str) -> TranslateStreamEvent {
+    match event {
+        TranslateStreamEvent::Started { text, from, to } if from == "auto" => {
+            TranslateStreamEvent::Started {
+                text,
+                from: source.to_string(),
+                to,
+            }
+        }
+        event => event,
+    }
+}
+
+// Answers from the cache when possible, replaying the cached text as one delta
+// so streaming callers see the same events as for a live translation.
+async fn cached_stream(
+    engine: &dyn TranslationEngine,
+    request: &TranslateRequest,
+    cache: &TranslationCache,
+    settings: &CacheSettings,
+    on_event: &OnEvent<'_>,
+) -> Result<TranslateResult, TranslateError> {
+    if !settings.enabled {
+        return chunk::stream_chunked(engine, request, on_event).await;
+    }
+
+    let key = CacheKey::new(engine, request);
+    if let Some(cached) = cache.get(&key, settings.ttl) {
+        on_event(TranslateStreamEvent::Started {
+            text: cached.text.clone(),
+            from: cached.from.clone(),
+            to: cached.to.clone(),
+        });
+        on_event(TranslateStreamEvent::Delta {
+            content: cached.translated.clone(),
+        });
+        return Ok(cached);
+    }
+
+    let result = chunk::stream_chunked(engine, request, on_event).await?;
+    cache.insert(key, &result, settings.limit);
+    Ok(result)
+}
+
+pub async fn health_check() -> Result<(), TranslateError> {
+    build_engine(&EngineConfig::from_app_config())
+        .health_check()
+        .await
+}
+
+// Every engine request goes through here so the proxy and CA settings apply.
+pub(crate) fn http_client(timeout: Duration) -> Result<reqwest::Client, TranslateError> {
+    NetworkSettings::from_app_config().client(timeout)
+}
+
+// Feeds a streamed response body to `consume` line by line until it reports
+// the end of the stream.
+async fn for_each_line<C>(
+    mut response: reqwest::Response,
+    mut consume: C,
+) -> Result<(), TranslateError>
+where
+    C: FnMut(&[u8]) -> Result<bool, TranslateError>,
+{
+    let mut line_buffer = Vec::new();
+
+    while let Some(chunk) = response.chunk().await? {
+        line_buffer.extend_from_slice(&chunk);
+
+        while let Some(newline) = line_buffer.iter().position(|byte| *byte == b'\n') {
+            let line = line_buffer.drain(..=newline).collect::<Vec<_>>();
+            if consume(&line)? {
+                return Ok(());
+            }
+        }
+    }
+
+    if !line_buffer.is_empty() {
+        consume(&line_buffer)?;
+    }
+    Ok(())
+}
+
+// The system prompt and user message for LLM engines. Templates that place
+// `{text}` themselves are sent as the user message, without a system prompt.
+fn llm_messages(request: &TranslateRequest) -> (String, String) {
+    let template = request
+        .prompt
+        .as_ref()
+        .map_or(prompt::DEFAULT_TEMPLATE, |preset| &preset.template);
+    let target_language = target_language_name(&request.to);
+    let glossary = glossary::prompt_section(&request.glossary).unwrap_or_default();
+    let mut rendered = prompt::render(
+        template,
+        &[
+            ("target_language", target_language.as_str()),
+            ("text", request.text.as_str()),
+            ("glossary", glossary.as_str()),
+        ],
+    )
+    .trim()
+    .to_string();
+    if !glossary.is_empty() && !prompt::uses_variable(template, "glossary") {
+        rendered = format!("{rendered}\n\n{glossary}");
+    }
+
+    if prompt::uses_variable(template, "text") {
+        (String::new(), rendered)
+    } else {
+        (rendered, request.text.clone())
+    }
+}
+
+// Chat messages for the OpenAI-style and Ollama APIs, without an empty
+// system prompt.
+fn chat_messages(system_prompt: String, text: &str) -> serde_json::Value {
+    let user = serde_json::json!({ "role": "user", "content": text });
+    if system_prompt.is_empty() {
+        serde_json::json!([user])
+    } else {
+        serde_json::json!([{ "role": "system", "content": system_prompt }, user])
+    }
+}
+
+fn preset_target_lang(preset: Option<&PromptPreset>) -> Option<String> {
+    preset
+        .map(|preset| preset.target_lang.clone())
+        .filter(|target_lang| !target_lang.is_empty())
+}
+
+fn target_language_name(language: &str) -> String {
+    let name = match language {
+        "zh-CN" => "Simplified Chinese",
+        "zh-TW" => "Traditional Chinese",
+        "en" => "English",
+        "ja" => "Japanese",
+        "ko" => "Korean",
+        "de" => "German",
+        "fr" => "French",
+        "es" => "Spanish",
+        "it" => "Italian",
+        "pt" => "Portuguese",
+        "nl" => "Dutch",
+        "ru" => "Russian",
+        "uk" => "Ukrainian",
+        other => return other.to_string(),
+    };
+    format!("{name} ({language})")
+}
+
+// An explicit target wins. Otherwise text in the primary language goes to the
+// secondary one and everything else to the primary one.
+fn resolve_target_lang(
+    target_lang: &str,
+    my_languages: &[String],
+    detection: Option<Detection>,
+) -> String {
+    if target_lang != "auto" && !target_lang.is_empty() {
+        return target_lang.to_string();
+    }
+
+    let primary = my_languages.first().map_or("zh-CN", String::as_str);
+    let is_primary =
+        detection.is_some_and(|detection| detect::same_language(detection.lang, primary));
+    if !is_primary {
+        return primary.to_string();
+    }
+
+    match my_languages.get(1) {
+        Some(secondary) => secondary.to_string(),
+        None if detect::same_language(primary, "en") => "zh-CN".to_string(),
+        None => "en".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    pub(super) fn engine_config(engine: &str) -> EngineConfig {
+        EngineConfig {
+            engine: engine.to_string(),
+            deepseek_api_key: String::new(),
+            deepseek_model: String::new(),
+            openai_base_url: String::new(),
+            openai_api_key: String::new(),
+            openai_model: String::new(),
+            ollama_url: String::new(),
+            ollama_model: String::new(),
+            custom_url: String::new(),
+            custom_key: String::new(),
+            custom_method: String::new(),
+            custom_headers: String::new(),
+            custom_body: String::new(),
+            custom_result: String::new(),
+            offline_model_dir: String::new(),
+            target_lang: "auto".to_string(),
+            my_languages: Vec::new(),
+            fallback_engines: Vec::new(),
+        }
+    }
+
+    fn resolve(target_lang: &str, my_languages: &[&str], text: &str) -> String {
+        let my_languages = my_languages
+            .iter()
+            .map(|language| language.to_string())
+            .collect::<Vec<_>>();
+        resolve_target_lang(target_lang, &my_languages, detect::detect(text))
+    }
+
+    #[test]
+    fn resolve_target_lang_prefers_configured_value() {
+        assert_eq!(resolve("ja", &["zh-CN", "en"], "hello"), "ja");
+    }
+
+    #[test]
+    fn resolve_target_lang_applies_my_languages() {
+        assert_eq!(resolve("auto", &["zh-CN", "en"], "你好世界"), "en");
+        assert_eq!(resolve("auto", &["zh-CN", "en"], "hello world"), "zh-CN");
+        assert_eq!(
+            resolve("auto", &["zh-CN", "en"], "こんにちは、世界"),
+            "zh-CN"
+        );
+        assert_eq!(resolve("auto", &["de", "fr"], "Привет, мир"), "de");
+        assert_eq!(
+            resolve(
+                "auto",
+                &["de", "fr"],
+                "Ich habe heute keine Zeit für das Treffen."
+            ),
+            "fr"
+        );
+        assert_eq!(resolve("auto", &["en"], "The meeting is at noon."), "zh-CN");
+        assert_eq!(resolve("auto", &[], "你好世界"), "en");
+    }
+
+    struct CountingEngine {
+        calls: AtomicUsize,
+    }
+
+    impl TranslationEngine for CountingEngine {
+        fn id(&self) -> &'static str {
+            "counting"
+        }
+
+        fn stream<'a>(
+            &'a self,
+            request: &'a TranslateRequest,
+            on_event: &'a OnEvent<'a>,
+        ) -> BoxFuture<'a, Result<TranslateResult, TranslateError>> {
+            Box::pin(async move {
+                let calls = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+                on_event(TranslateStreamEvent::Delta {
+                    content: "part".to_string(),
+                });
+                Ok(request.result(format!("translation {calls}"), "en"))
+            })
+        }
+
+        fn health_check(&self) -> BoxFuture<'_, Result<(), TranslateError>> {
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    #[tokio::test]
+    async fn replays_cached_translation_as_single_delta() {
+        let path =
+            std::env::temp_dir().join(format!("rotor-engine-cache-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let cache = TranslationCache::open(path);
+        let settings = CacheSettings {
+            enabled: true,
+            limit: 10,
+            ttl: None,
+        };
+        let engine = CountingEngine {
+            calls: AtomicUsize::new(0),
+        };
+        let request = TranslateRequest {
+            text: "hello".to_string(),
+            from: "auto".to_string(),
+            to: "fr".to_string(),
+            glossary: Vec::new(),
+            prompt: None,
+        };
+        let events = Mutex::new(Vec::new());
+        let on_event = |event| events.lock().unwrap().push(event);
+
+        cached_stream(&engine, &request, &cache, &settings, &on_event)
+            .await
+            .unwrap();
+        events.lock().unwrap().clear();
+        let cached = cached_stream(&engine, &request, &cache, &settings, &on_event)
+            .await
+            .unwrap();
+
+        assert_eq!(engine.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(cached.translated, "translation 1");
+        let events = events.lock().unwrap();
+        assert!(matches!(&events[0], TranslateStreamEvent::Started { from, .. } if from == "en"));
+        assert!(
+            matches!(&events[1], TranslateStreamEvent::Delta { content } if content == "translation 1")
+        );
+        assert_eq!(events.len(), 2);
+    }
+
+    #[test]
+    fn llm_messages_list_glossary_terms() {
+        let request = TranslateRequest {
+            text: "Open Rotor".to_string(),
+            from: "auto".to_string(),
+            to: "zh-CN".to_string(),
+            glossary: vec![GlossaryEntry {
+                term: "Rotor".to_string(),
+                translation: String::new(),
+            }],
+            prompt: None,
+        };
+
+        let (system_prompt, user_message) = llm_messages(&request);
+
+        assert!(system_prompt.contains("into Simplified Chinese (zh-CN)"));
+        assert!(system_prompt.ends_with("- Rotor => keep as is, do not translate"));
+        assert_eq!(user_message, "Open Rotor");
+    }
+
+    #[test]
+    fn llm_messages_send_templates_with_text_as_the_user_message() {
+        let request = TranslateRequest {
+            text: "Open Rotor".to_string(),
+            from: "auto".to_string(),
+            to: "en".to_string(),
+            glossary: vec![GlossaryEntry {
+                term: "Rotor".to_string(),
+                translation: String::new(),
+            }],
+            prompt: Some(PromptPreset {
+                id: "short".to_string(),
+                name: "Short".to_string(),
+                template: "Translate into {target_language}:\n{text}".to_string(),
+                target_lang: String::new(),
+            }),
+        };
+
+        let (system_prompt, user_message) = llm_messages(&request);
+
+        assert_eq!(system_prompt, "");
+        assert_eq!(
+            user_message,
+            "Translate into English (en):\nOpen Rotor\n\nAlways translate these terms exactly as listed:\n- Rotor => keep as is, do not translate"
+        );
+    }
+
+    #[test]
+    fn engine_chain_appends_fallbacks_once() {
+        let mut config = engine_config("deepseek");
+        config.fallback_engines = vec!["google".into(), "deepseek".into(), "google".into()];
+
+        assert_eq!(config.engine_chain(), vec!["deepseek", "google"]);
+    }
+
+    #[test]
+    fn builds_registered_engines_and_falls_back_to_google() {
+        for id in engine_ids() {
+            assert_eq!(build_engine(&engine_config(id)).id(), id);
+        }
+        assert_eq!(build_engine(&engine_config("unknown")).id(), "google");
+    }
+}
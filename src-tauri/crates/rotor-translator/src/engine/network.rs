@@ -0,0 +1,130 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use reqwest::{Certificate, NoProxy, Proxy};
+
+use super::{split_list, TranslateError};
+
+// Proxy and TLS settings shared by every outbound client.
+#[derive(Clone, Debug, Default)]
+pub struct NetworkSettings {
+    // An http://, https:// or socks5:// proxy URL, empty for a direct connection.
+    pub proxy: String,
+    // Comma separated hosts, domains and CIDR ranges that bypass the proxy.
+    pub no_proxy: String,
+    // PEM files with root certificates trusted on top of the built-in ones.
+    pub ca_certificates: Vec<PathBuf>,
+}
+
+impl NetworkSettings {
+    pub fn from_app_config() -> NetworkSettings {
+        let config = rotor_common::AppConfig::lock_global();
+        let get = |key: &str| config.get(key).cloned().unwrap_or_default();
+        NetworkSettings {
+            proxy: get("network_proxy").trim().to_string(),
+            no_proxy: get("network_no_proxy"),
+            ca_certificates: split_list(&get("network_ca_certificates"))
+                .into_iter()
+                .map(PathBuf::from)
+                .collect(),
+        }
+    }
+
+    pub fn client(&self, timeout: Duration) -> Result<reqwest::Client, TranslateError> {
+        // reqwest is built with `rustls-no-provider`, so install the ring
+        // provider process-wide (mirrors what tauri-plugin-updater does).
+        let _ = rustls::crypto::ring::default_provider().install_default();
+
+        let mut builder = reqwest::Client::builder().timeout(timeout);
+        if !self.proxy.is_empty() {
+            let proxy = Proxy::all(&self.proxy)
+                .map_err(|error| format!("Invalid proxy {}: {error}", self.proxy))?
+                .no_proxy(NoProxy::from_string(&self.no_proxy));
+            builder = builder.proxy(proxy);
+        }
+        for path in &self.ca_certificates {
+            let pem = fs::read(path).map_err(|error| {
+                format!("Failed to read CA certificate {}: {error}", path.display())
+            })?;
+            let certificates = Certificate::from_pem_bundle(&pem)
+                .map_err(|error| format!("Invalid CA certificate {}: {error}", path.display()))?;
+            if certificates.is_empty() {
+                return Err(format!("No certificate found in {}", path.display()).into());
+            }
+            builder = builder.tls_certs_merge(certificates);
+        }
+
+        Ok(builder.build()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::mock_server::{MockResponse, MockServer};
+
+    #[tokio::test]
+    async fn sends_requests_through_the_proxy() {
+        let proxy = MockServer::start(vec![MockResponse::new(200, "text/plain", "proxied")]);
+        let settings = NetworkSettings {
+            proxy: proxy.url().to_string(),
+            ..NetworkSettings::default()
+        };
+
+        let body = settings
+            .client(Duration::from_secs(5))
+            .unwrap()
+            .get("http://translate.example/ping")
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+
+        assert_eq!(body, "proxied");
+        assert_eq!(proxy.requests()[0].path, "http://translate.example/ping");
+    }
+
+    #[tokio::test]
+    async fn bypasses_the_proxy_for_no_proxy_hosts() {
+        let proxy = MockServer::start(Vec::new());
+        let server = MockServer::start(vec![MockResponse::new(200, "text/plain", "direct")]);
+        let settings = NetworkSettings {
+            proxy: proxy.url().to_string(),
+            no_proxy: "localhost, 127.0.0.1".to_string(),
+            ..NetworkSettings::default()
+        };
+
+        let response = settings
+            .client(Duration::from_secs(5))
+            .unwrap()
+            .get(format!("{}/ping", server.url()))
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.text().await.unwrap(), "direct");
+        assert!(proxy.requests().is_empty());
+    }
+
+    #[test]
+    fn accepts_socks5_and_rejects_bad_certificates() {
+        let settings = NetworkSettings {
+            proxy: "socks5://127.0.0.1:1080".to_string(),
+            ..NetworkSettings::default()
+        };
+        assert!(settings.client(Duration::from_secs(5)).is_ok());
+
+        let path = std::env::temp_dir().join(format!("rotor-ca-{}.pem", std::process::id()));
+        fs::write(&path, "not a certificate").unwrap();
+        let settings = NetworkSettings {
+            ca_certificates: vec![path.clone(), PathBuf::from("/nonexistent/ca.pem")],
+            ..NetworkSettings::default()
+        };
+        let error = settings.client(Duration::from_secs(5)).unwrap_err();
+        let _ = fs::remove_file(&path);
+        assert!(error.to_string().contains("rotor-ca-"), "{error}");
+    }
+}
@@ -0,0 +1,473 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Mutex, MutexGuard, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use ort::session::Session;
+use ort::value::TensorRef;
+use serde::Deserialize;
+use tokenizers::Tokenizer;
+
+use super::{
+    BoxFuture, EngineConfig, OnEvent, TranslateError, TranslateRequest, TranslateResult,
+    TranslationEngine,
+};
+use crate::detect;
+
+const OFFLINE_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+// Marian models are trained on sentences of at most 512 tokens.
+const MAX_OUTPUT_TOKENS: usize = 512;
+const MODEL_FILES: [&str; 2] = ["tokenizer.json", "config.json"];
+
+#[derive(Default)]
+struct OfflineCache {
+    model: Option<MarianModel>,
+    last_used: Option<Instant>,
+}
+
+static OFFLINE_MODEL: OnceLock<Mutex<OfflineCache>> = OnceLock::new();
+static OFFLINE_REAPER: Mutex<Option<mpsc::Sender<()>>> = Mutex::new(None);
+
+// A local Opus-MT/Marian model exported to ONNX, e.g. with Hugging Face
+// Optimum. Works without any network access.
+pub(super) struct OfflineEngine {
+    model_dir: PathBuf,
+    // The directory as text, so cached translations follow it.
+    model: String,
+}
+
+impl OfflineEngine {
+    pub fn new(engine_config: &EngineConfig) -> OfflineEngine {
+        let model = engine_config.offline_model_dir.trim().to_string();
+        OfflineEngine {
+            model_dir: PathBuf::from(&model),
+            model,
+        }
+    }
+
+    fn check_config(&self) -> Result<(), TranslateError> {
+        if self.model_dir.as_os_str().is_empty() {
+            return Err("Offline model directory is not configured".into());
+        }
+        if !self.model_dir.is_dir() {
+            return Err(format!(
+                "Offline model directory {} does not exist",
+                self.model_dir.display()
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    async fn request(&self, request: &TranslateRequest) -> Result<TranslateResult, TranslateError> {
+        self.check_config()?;
+
+        let detected = || {
+            detect::detect(&request.text)
+                .filter(|detection| detection.confidence >= detect::MIN_CONFIDENCE)
+                .map(|detection| detection.lang)
+        };
+        let from = if request.from != "auto" {
+            request.from.clone()
+        } else if is_model_dir(&self.model_dir) {
+            // A single model needs no detection to be picked.
+            match dir_pair(&self.model_dir) {
+                Some((model_from, _)) => model_from,
+                None => detected().unwrap_or("auto").to_string(),
+            }
+        } else {
+            detected()
+                .ok_or("Could not detect the source language for the offline model")?
+                .to_string()
+        };
+        let model_dir = model_dir(&self.model_dir, &from, &request.to).ok_or_else(|| {
+            format!(
+                "No offline model for {from} to {} in {}",
+                request.to,
+                self.model_dir.display()
+            )
+        })?;
+
+        let text = request.text.clone();
+        let translated =
+            tokio::task::spawn_blocking(move || translate_offline(&model_dir, &text)).await??;
+        Ok(request.result(translated, &from))
+    }
+}
+
+impl TranslationEngine for OfflineEngine {
+    fn id(&self) -> &'static str {
+        "offline"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    // Marian models lose quality well before their token limit.
+    fn max_chunk_len(&self) -> usize {
+        400
+    }
+
+    fn stream<'a>(
+        &'a self,
+        request: &'a TranslateRequest,
+        _on_event: &'a OnEvent<'a>,
+    ) -> BoxFuture<'a, Result<TranslateResult, TranslateError>> {
+        Box::pin(self.request(request))
+    }
+
+    fn health_check(&self) -> BoxFuture<'_, Result<(), TranslateError>> {
+        Box::pin(async move {
+            self.check_config()?;
+            let models = model_dirs(&self.model_dir);
+            if models.is_empty() {
+                return Err(
+                    format!("No offline model found in {}", self.model_dir.display()).into(),
+                );
+            }
+            for dir in models {
+                onnx_file(&dir, "encoder_model.onnx")?;
+                onnx_file(&dir, "decoder_model.onnx")?;
+            }
+            Ok(())
+        })
+    }
+}
+
+// The model directory for a language pair: `root` itself when it holds a
+// model for the pair, otherwise a sub directory named like `en-zh` or
+// `opus-mt-en-zh`.
+fn model_dir(root: &Path, from: &str, to: &str) -> Option<PathBuf> {
+    if is_model_dir(root) {
+        return serves_pair(root, from, to).then(|| root.to_path_buf());
+    }
+
+    let pair = format!("{}-{}", base_code(from), base_code(to));
+    [root.join(&pair), root.join(format!("opus-mt-{pair}"))]
+        .into_iter()
+        .find(|dir| is_model_dir(dir))
+}
+
+// Opus-MT names models by base language code only.
+fn base_code(code: &str) -> String {
+    code.split(['-', '_']).next().unwrap_or(code).to_lowercase()
+}
+
+// The language pair in a model directory name like `en-zh` or `opus-mt-en-zh`.
+fn dir_pair(dir: &Path) -> Option<(String, String)> {
+    let name = dir.file_name()?.to_str()?.to_lowercase();
+    let (from, to) = name
+        .strip_prefix("opus-mt-")
+        .unwrap_or(&name)
+        .split_once('-')?;
+    let is_code =
+        |code: &str| (2..=3).contains(&code.len()) && code.bytes().all(|b| b.is_ascii_lowercase());
+    (is_code(from) && is_code(to)).then(|| (from.to_string(), to.to_string()))
+}
+
+// A model whose directory name carries no pair cannot be checked, so it is
+// trusted with any pair.
+fn serves_pair(dir: &Path, from: &str, to: &str) -> bool {
+    dir_pair(dir).is_none_or(|(model_from, model_to)| {
+        (from == "auto" || base_code(from) == model_from) && base_code(to) == model_to
+    })
+}
+
+fn model_dirs(root: &Path) -> Vec<PathBuf> {
+    if is_model_dir(root) {
+        return vec![root.to_path_buf()];
+    }
+    let Ok(entries) = fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut dirs = entries
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|dir| is_model_dir(dir))
+        .collect::<Vec<_>>();
+    dirs.sort();
+    dirs
+}
+
+fn is_model_dir(dir: &Path) -> bool {
+    MODEL_FILES.iter().all(|file| dir.join(file).is_file())
+}
+
+// Optimum puts the ONNX files in an `onnx` sub directory, plain exports do not.
+fn onnx_file(dir: &Path, name: &str) -> Result<PathBuf, TranslateError> {
+    [dir.join("onnx").join(name), dir.join(name)]
+        .into_iter()
+        .find(|path| path.is_file())
+        .ok_or_else(|| format!("{name} not found in {}", dir.display()).into())
+}
+
+fn translate_offline(model_dir: &Path, text: &str) -> Result<String, TranslateError> {
+    let result = {
+        let model_cache = OFFLINE_MODEL.get_or_init(|| Mutex::new(OfflineCache::default()));
+        let mut cache = lock_offline_cache(model_cache);
+        if cache
+            .model
+            .as_ref()
+            .is_none_or(|model| model.dir != model_dir)
+        {
+            // Release the previous pair before loading the next one.
+            cache.model = None;
+            cache.model = Some(MarianModel::load(model_dir)?);
+        }
+
+        let result = cache
+            .model
+            .as_mut()
+            .expect("offline model was loaded")
+            .translate_lines(text);
+        cache.last_used = Some(Instant::now());
+        result
+    };
+    schedule_offline_reaper();
+    result
+}
+
+#[derive(Deserialize)]
+struct MarianConfig {
+    decoder_start_token_id: u32,
+    eos_token_id: u32,
+    pad_token_id: u32,
+    max_length: Option<usize>,
+}
+
+struct MarianModel {
+    dir: PathBuf,
+    config: MarianConfig,
+    tokenizer: Tokenizer,
+    encoder: Session,
+    decoder: Session,
+}
+
+impl MarianModel {
+    fn load(dir: &Path) -> Result<MarianModel, TranslateError> {
+        let config = fs::read_to_string(dir.join("config.json"))?;
+        let config = serde_json::from_str::<MarianConfig>(&config)
+            .map_err(|error| format!("Invalid config.json in {}: {error}", dir.display()))?;
+        let tokenizer = Tokenizer::from_file(dir.join("tokenizer.json"))?;
+        let encoder =
+            Session::builder()?.commit_from_file(onnx_file(dir, "encoder_model.onnx")?)?;
+        let decoder =
+            Session::builder()?.commit_from_file(onnx_file(dir, "decoder_model.onnx")?)?;
+        Ok(MarianModel {
+            dir: dir.to_path_buf(),
+            config,
+            tokenizer,
+            encoder,
+            decoder,
+        })
+    }
+
+    // Translates line by line so the layout of the text survives.
+    fn translate_lines(&mut self, text: &str) -> Result<String, TranslateError> {
+        let mut lines = Vec::new();
+        for line in text.lines() {
+            if line.trim().is_empty() {
+                lines.push(String::new());
+            } else {
+                lines.push(self.translate(line.trim())?);
+            }
+        }
+        Ok(lines.join("\n"))
+    }
+
+    // Greedy decoding without a KV cache; fine for sentence sized inputs.
+    fn translate(&mut self, text: &str) -> Result<String, TranslateError> {
+        let encoding = self.tokenizer.encode(text, true)?;
+        let input_ids = encoding
+            .get_ids()
+            .iter()
+            .map(|&id| i64::from(id))
+            .collect::<Vec<_>>();
+        let attention_mask = vec![1i64; input_ids.len()];
+        let input_shape = vec![1, input_ids.len() as i64];
+
+        let encoder_outputs = self.encoder.run(ort::inputs![
+            "input_ids" => TensorRef::from_array_view((input_shape.clone(), input_ids.as_slice()))?,
+            "attention_mask" => TensorRef::from_array_view((input_shape.clone(), attention_mask.as_slice()))?,
+        ])?;
+        let (hidden_shape, hidden_states) =
+            encoder_outputs["last_hidden_state"].try_extract_tensor::<f32>()?;
+        let hidden_shape = hidden_shape.to_vec();
+        let hidden_states = hidden_states.to_vec();
+        drop(encoder_outputs);
+
+        let max_tokens = self
+            .config
+            .max_length
+            .unwrap_or(MAX_OUTPUT_TOKENS)
+            .min(MAX_OUTPUT_TOKENS);
+        let mut output_ids = vec![i64::from(self.config.decoder_start_token_id)];
+        while output_ids.len() < max_tokens {
+            let outputs = self.decoder.run(ort::inputs![
+                "input_ids" => TensorRef::from_array_view((vec![1, output_ids.len() as i64], output_ids.as_slice()))?,
+                "encoder_attention_mask" => TensorRef::from_array_view((input_shape.clone(), attention_mask.as_slice()))?,
+                "encoder_hidden_states" => TensorRef::from_array_view((hidden_shape.clone(), hidden_states.as_slice()))?,
+            ])?;
+            let (shape, logits) = outputs["logits"].try_extract_tensor::<f32>()?;
+            let vocab_size = shape.last().copied().unwrap_or_default() as usize;
+            let Some(last) = logits.get(logits.len().saturating_sub(vocab_size)..) else {
+                break;
+            };
+            let Some(next) = next_token(last, self.config.pad_token_id) else {
+                break;
+            };
+            if next == self.config.eos_token_id {
+                break;
+            }
+            output_ids.push(i64::from(next));
+        }
+
+        let ids = output_ids[1..]
+            .iter()
+            .map(|&id| id as u32)
+            .collect::<Vec<_>>();
+        Ok(self.tokenizer.decode(&ids, true)?.trim().to_string())
+    }
+}
+
+// The most likely next token. Marian never generates padding, so it is
+// excluded the same way Hugging Face does.
+fn next_token(logits: &[f32], pad_token_id: u32) -> Option<u32> {
+    logits
+        .iter()
+        .enumerate()
+        .filter(|&(id, _)| id as u32 != pad_token_id)
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(id, _)| id as u32)
+}
+
+fn lock_offline_cache(cache: &Mutex<OfflineCache>) -> MutexGuard<'_, OfflineCache> {
+    match cache.lock() {
+        Ok(guard) => guard,
+        Err(error) => {
+            log::warn!("Recovering offline translation model after a panic");
+            let mut guard = error.into_inner();
+            *guard = OfflineCache::default();
+            cache.clear_poison();
+            guard
+        }
+    }
+}
+
+fn schedule_offline_reaper() {
+    let mut reaper = OFFLINE_REAPER
+        .lock()
+        .unwrap_or_else(|error| error.into_inner());
+    if reaper
+        .as_ref()
+        .is_some_and(|sender| sender.send(()).is_ok())
+    {
+        return;
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    match thread::Builder::new()
+        .name("rotor-offline-reaper".to_string())
+        .stack_size(64 * 1024)
+        .spawn(move || run_offline_reaper(receiver))
+    {
+        Ok(_) => {
+            let _ = sender.send(());
+            *reaper = Some(sender);
+        }
+        Err(error) => {
+            log::error!("Failed to start offline model reaper: {error}");
+            *reaper = None;
+        }
+    }
+}
+
+fn run_offline_reaper(receiver: mpsc::Receiver<()>) {
+    while receiver.recv().is_ok() {
+        loop {
+            let remaining = {
+                let Some(cache) = OFFLINE_MODEL.get() else {
+                    break;
+                };
+                let mut cache = lock_offline_cache(cache);
+                let Some(last_used) = cache.last_used else {
+                    break;
+                };
+
+                match OFFLINE_IDLE_TIMEOUT.checked_sub(last_used.elapsed()) {
+                    Some(remaining) if !remaining.is_zero() => remaining,
+                    _ => {
+                        cache.model.take();
+                        cache.last_used = None;
+                        break;
+                    }
+                }
+            };
+
+            match receiver.recv_timeout(remaining) {
+                Ok(()) | Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => return,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fake_model(dir: &Path) {
+        fs::create_dir_all(dir).unwrap();
+        for file in MODEL_FILES {
+            fs::write(dir.join(file), "{}").unwrap();
+        }
+    }
+
+    #[test]
+    fn finds_the_model_for_a_language_pair() {
+        let root = std::env::temp_dir().join(format!("rotor-offline-{}", std::process::id()));
+        fake_model(&root.join("en-zh"));
+        fake_model(&root.join("opus-mt-zh-en"));
+
+        assert_eq!(model_dir(&root, "en", "zh-CN"), Some(root.join("en-zh")));
+        assert_eq!(
+            model_dir(&root, "zh-CN", "en"),
+            Some(root.join("opus-mt-zh-en"))
+        );
+        assert_eq!(model_dir(&root, "ja", "en"), None);
+        assert_eq!(
+            model_dirs(&root),
+            vec![root.join("en-zh"), root.join("opus-mt-zh-en")]
+        );
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn single_model_root_only_serves_its_own_pair() {
+        let root = std::env::temp_dir().join(format!("rotor-offline-root-{}", std::process::id()));
+        let named = root.join("opus-mt-en-zh");
+        fake_model(&named);
+        let unnamed = root.join("marian");
+        fake_model(&unnamed);
+
+        assert_eq!(dir_pair(&named), Some(("en".to_string(), "zh".to_string())));
+        assert_eq!(model_dir(&named, "en-US", "zh-CN"), Some(named.clone()));
+        assert_eq!(model_dir(&named, "auto", "zh-TW"), Some(named.clone()));
+        assert_eq!(model_dir(&named, "ja", "zh-CN"), None);
+        assert_eq!(model_dir(&named, "en", "fr"), None);
+
+        assert_eq!(dir_pair(&unnamed), None);
+        assert_eq!(model_dir(&unnamed, "ja", "en"), Some(unnamed.clone()));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn picks_the_most_likely_token_except_padding() {
+        assert_eq!(next_token(&[0.1, 0.9, 0.5], 2), Some(1));
+        assert_eq!(next_token(&[0.1, 0.9, 0.5], 1), Some(2));
+        assert_eq!(next_token(&[], 0), None);
+    }
+}
@@ -0,0 +1,398 @@
+use serde_json::Value;
+
+use super::dictionary::{self, DictionaryResult};
+use super::retry::{request_error, status_error};
+use super::{
+    chat_messages, for_each_line, http_client, llm_messages, BoxFuture, EngineConfig, OnEvent,
+    TranslateError, TranslateRequest, TranslateResult, TranslateStreamEvent, TranslationEngine,
+    LLM_REQUEST_TIMEOUT, REQUEST_TIMEOUT,
+};
+
+const OLLAMA_DEFAULT_URL: &str = "http://127.0.0.1:11434";
+
+// A local Ollama server, streamed through its native `/api/chat` endpoint.
+pub(super) struct OllamaEngine {
+    base_url: String,
+    model: String,
+}
+
+impl OllamaEngine {
+    pub fn new(engine_config: &EngineConfig) -> OllamaEngine {
+        let base_url = engine_config.ollama_url.trim().trim_end_matches('/');
+        OllamaEngine {
+            base_url: if base_url.is_empty() {
+                OLLAMA_DEFAULT_URL.to_string()
+            } else {
+                base_url.to_string()
+            },
+            model: engine_config.ollama_model.trim().to_string(),
+        }
+    }
+
+    fn check_config(&self) -> Result<(), TranslateError> {
+        if self.model.is_empty() {
+            return Err("Ollama model is not configured".into());
+        }
+        Ok(())
+    }
+
+    fn unreachable(&self, error: reqwest::Error) -> TranslateError {
+        let message = format!("Ollama is not reachable at {}: {error}", self.base_url);
+        request_error(error, message)
+    }
+
+    async fn post_chat(
+        &self,
+        system_prompt: String,
+        text: &str,
+        extra: Value,
+    ) -> Result<reqwest::Response, TranslateError> {
+        self.check_config()?;
+
+        let mut request_body = serde_json::json!({
+            "model": self.model,
+            "messages": chat_messages(system_prompt, text)
+        });
+        if let (Some(body), Value::Object(extra)) = (request_body.as_object_mut(), extra) {
+            body.extend(extra);
+        }
+
+        let client = http_client(LLM_REQUEST_TIMEOUT)?;
+        let response = client
+            .post(format!("{}/api/chat", self.base_url))
+            .json(&request_body)
+            .send()
+            .await
+            .map_err(|error| self.unreachable(error))?;
+        let status = response.status();
+
+        if !status.is_success() {
+            let headers = response.headers().clone();
+            let body = response.text().await?;
+            let detail = parse_error(&body)
+                .map(|message| format!(": {message}"))
+                .unwrap_or_default();
+            let message = format!("Ollama translate request failed: {status}{detail}");
+            return Err(status_error(status, &headers, message));
+        }
+        Ok(response)
+    }
+
+    async fn stream_chat(
+        &self,
+        request: &TranslateRequest,
+        on_event: &OnEvent<'_>,
+    ) -> Result<TranslateResult, TranslateError> {
+        let (system_prompt, user_message) = llm_messages(request);
+        let response = self
+            .post_chat(
+                system_prompt,
+                &user_message,
+                serde_json::json!({ "stream": true }),
+            )
+            .await?;
+
+        on_event(TranslateStreamEvent::Started {
+            text: request.text.clone(),
+            from: request.from.clone(),
+            to: request.to.clone(),
+        });
+
+        let mut translated = String::new();
+        for_each_line(response, |line| {
+            consume_stream_line(line, &mut translated, on_event)
+        })
+        .await?;
+
+        let translated = translated.trim().to_string();
+        if translated.is_empty() {
+            return Err("Unexpected Ollama response format".into());
+        }
+
+        Ok(request.result(translated, &request.from))
+    }
+
+    async fn lookup_word(
+        &self,
+        request: &TranslateRequest,
+    ) -> Result<Option<DictionaryResult>, TranslateError> {
+        let response = self
+            .post_chat(
+                dictionary::llm_prompt(request),
+                &request.text,
+                serde_json::json!({ "stream": false, "format": "json" }),
+            )
+            .await?;
+
+        let body: Value = response.json().await?;
+        let content = body
+            .pointer("/message/content")
+            .and_then(Value::as_str)
+            .ok_or("Unexpected Ollama response format")?;
+        dictionary::parse_llm_response(request, content)
+    }
+
+    async fn check_model(&self) -> Result<(), TranslateError> {
+        self.check_config()?;
+
+        let client = http_client(REQUEST_TIMEOUT)?;
+        let response = client
+            .get(format!("{}/api/tags", self.base_url))
+            .send()
+            .await
+            .map_err(|error| self.unreachable(error))?;
+        if !response.status().is_success() {
+            return Err(format!("Ollama is not reachable: {}", response.status()).into());
+        }
+
+        let body: Value = response.json().await?;
+        let installed = body
+            .get("models")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|model| model.get("name").and_then(Value::as_str))
+            .any(|name| name == self.model || name.strip_suffix(":latest") == Some(&self.model));
+        if !installed {
+            return Err(format!("Ollama model {} is not installed", self.model).into());
+        }
+        Ok(())
+    }
+}
+
+impl TranslationEngine for OllamaEngine {
+    fn id(&self) -> &'static str {
+        "ollama"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    // Local models tend to have small context windows.
+    fn max_chunk_len(&self) -> usize {
+        2000
+    }
+
+    fn stream<'a>(
+        &'a self,
+        request: &'a TranslateRequest,
+        on_event: &'a OnEvent<'a>,
+    ) -> BoxFuture<'a, Result<TranslateResult, TranslateError>> {
+        Box::pin(self.stream_chat(request, on_event))
+    }
+
+    fn lookup<'a>(
+        &'a self,
+        request: &'a TranslateRequest,
+    ) -> BoxFuture<'a, Result<Option<DictionaryResult>, TranslateError>> {
+        Box::pin(self.lookup_word(request))
+    }
+
+    fn health_check(&self) -> BoxFuture<'_, Result<(), TranslateError>> {
+        Box::pin(self.check_model())
+    }
+}
+
+// Ollama streams one JSON object per line and marks the last one with `done`.
+fn consume_stream_line(
+    line: &[u8],
+    translated: &mut String,
+    on_event: &OnEvent<'_>,
+) -> Result<bool, TranslateError> {
+    let line = std::str::from_utf8(line)?.trim();
+    if line.is_empty() {
+        return Ok(false);
+    }
+
+    let payload: Value = serde_json::from_str(line)?;
+    if let Some(message) = payload.get("error").and_then(Value::as_str) {
+        return Err(format!("Ollama translate stream failed: {message}").into());
+    }
+
+    if let Some(content) = payload
+        .pointer("/message/content")
+        .and_then(Value::as_str)
+        .filter(|content| !content.is_empty())
+    {
+        translated.push_str(content);
+        on_event(TranslateStreamEvent::Delta {
+            content: content.to_string(),
+        });
+    }
+
+    Ok(payload.get("done").and_then(Value::as_bool) == Some(true))
+}
+
+fn parse_error(body: &str) -> Option<String> {
+    serde_json::from_str::<Value>(body)
+        .ok()?
+        .get("error")?
+        .as_str()
+        .map(str::trim)
+        .filter(|message| !message.is_empty())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::mock_server::{MockResponse, MockServer};
+    use crate::engine::retry::{RetryPolicy, RetryingEngine};
+    use crate::engine::tests::engine_config;
+    use serde_json::json;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    fn ollama_engine(base_url: &str, model: &str) -> OllamaEngine {
+        OllamaEngine {
+            base_url: base_url.to_string(),
+            model: model.to_string(),
+        }
+    }
+
+    #[test]
+    fn uses_default_url_when_unset() {
+        let engine = OllamaEngine::new(&engine_config("ollama"));
+        assert_eq!(engine.base_url, OLLAMA_DEFAULT_URL);
+        assert!(engine.check_config().is_err());
+    }
+
+    #[tokio::test]
+    async fn streams_chat_from_ollama_server() {
+        let server = MockServer::start(vec![MockResponse::new(
+            200,
+            "application/x-ndjson",
+            concat!(
+                "{\"message\":{\"role\":\"assistant\",\"content\":\"こんにちは\"},\"done\":false}\n",
+                "{\"message\":{\"role\":\"assistant\",\"content\":\"世界\"},\"done\":false}\n",
+                "{\"message\":{\"role\":\"assistant\",\"content\":\"\"},\"done\":true}\n",
+            ),
+        )]);
+        let engine = ollama_engine(server.url(), "qwen2.5:7b");
+        let events = Mutex::new(Vec::new());
+        let request = TranslateRequest {
+            text: "Hello world".to_string(),
+            from: "auto".to_string(),
+            to: "ja".to_string(),
+            glossary: Vec::new(),
+            prompt: None,
+        };
+
+        let result = engine
+            .stream(&request, &|event| events.lock().unwrap().push(event))
+            .await
+            .unwrap();
+
+        assert_eq!(result.translated, "こんにちは世界");
+        assert_eq!(events.lock().unwrap().len(), 3);
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/api/chat");
+        let body = requests[0].json();
+        assert_eq!(body["model"], json!("qwen2.5:7b"));
+        assert!(body["messages"][0]["content"]
+            .as_str()
+            .unwrap()
+            .contains("Japanese"));
+    }
+
+    #[tokio::test]
+    async fn reports_missing_model() {
+        let server = MockServer::start(vec![MockResponse::json(
+            404,
+            json!({ "error": "model \"llama9\" not found, try pulling it first" }),
+        )]);
+        let engine = ollama_engine(server.url(), "llama9");
+        let request = TranslateRequest {
+            text: "Hello".to_string(),
+            from: "auto".to_string(),
+            to: "zh-CN".to_string(),
+            glossary: Vec::new(),
+            prompt: None,
+        };
+
+        let error = engine.translate(&request).await.unwrap_err();
+
+        assert!(error.to_string().contains("try pulling it first"));
+    }
+
+    #[tokio::test]
+    async fn health_check_finds_installed_model() {
+        let tags = json!({ "models": [{ "name": "llama3:latest" }, { "name": "qwen2.5:7b" }] });
+        let server = MockServer::start(vec![
+            MockResponse::json(200, tags.clone()),
+            MockResponse::json(200, tags),
+        ]);
+
+        ollama_engine(server.url(), "llama3")
+            .health_check()
+            .await
+            .unwrap();
+        let error = ollama_engine(server.url(), "qwen2.5")
+            .health_check()
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.to_string(), "Ollama model qwen2.5 is not installed");
+    }
+
+    #[tokio::test]
+    async fn unreachable_server_is_retried() {
+        // A port that was just free, so the connection is refused.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        let request = TranslateRequest {
+            text: "Hello".to_string(),
+            from: "auto".to_string(),
+            to: "zh-CN".to_string(),
+            glossary: Vec::new(),
+            prompt: None,
+        };
+
+        let error = ollama_engine(&url, "qwen2.5")
+            .translate(&request)
+            .await
+            .unwrap_err();
+
+        assert!(error.to_string().contains("Ollama is not reachable"));
+        assert!(error.source().is_some());
+        let policy = RetryPolicy {
+            retries: 1,
+            base_delay: Duration::from_millis(1),
+        };
+        assert!(policy.delay(0, &error).is_some());
+    }
+
+    #[tokio::test]
+    async fn retries_server_errors_until_ollama_answers() {
+        let server = MockServer::start(vec![
+            MockResponse::json(503, json!({ "error": "server busy" })),
+            MockResponse::new(
+                200,
+                "application/x-ndjson",
+                "{\"message\":{\"role\":\"assistant\",\"content\":\"你好\"},\"done\":true}\n",
+            ),
+        ]);
+        let engine = ollama_engine(server.url(), "qwen2.5");
+        let policy = RetryPolicy {
+            retries: 1,
+            base_delay: Duration::from_millis(1),
+        };
+        let request = TranslateRequest {
+            text: "Hello".to_string(),
+            from: "auto".to_string(),
+            to: "zh-CN".to_string(),
+            glossary: Vec::new(),
+            prompt: None,
+        };
+
+        let result = RetryingEngine::new(&engine, policy)
+            .translate(&request)
+            .await
+            .unwrap();
+
+        assert_eq!(result.translated, "你好");
+        assert_eq!(server.requests().len(), 2);
+    }
+}
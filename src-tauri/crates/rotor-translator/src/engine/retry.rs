@@ -0,0 +1,372 @@
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+
+use super::{
+    BoxFuture, DictionaryResult, OnEvent, TranslateError, TranslateRequest, TranslateResult,
+    TranslateStreamEvent, TranslationEngine,
+};
+
+const BASE_DELAY: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(8);
+// Longer Retry-After waits are not worth it for an interactive translation.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(30);
+
+// A failed request that may succeed when sent again, such as a 429 or a 5xx.
+#[derive(Debug)]
+pub(crate) struct TransientError {
+    message: String,
+    retry_after: Option<Duration>,
+    source: Option<reqwest::Error>,
+}
+
+impl fmt::Display for TransientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for TransientError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source
+            .as_ref()
+            .map(|source| source as &(dyn std::error::Error + 'static))
+    }
+}
+
+// The error for a response with a failure `status`, transient when retrying
+// can help.
+pub(super) fn status_error(
+    status: StatusCode,
+    headers: &HeaderMap,
+    message: String,
+) -> TranslateError {
+    let transient = status == StatusCode::TOO_MANY_REQUESTS
+        || status == StatusCode::REQUEST_TIMEOUT
+        || status.is_server_error();
+    if !transient {
+        return message.into();
+    }
+
+    let retry_after = headers
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| parse_retry_after(value, SystemTime::now()));
+    Box::new(TransientError {
+        message,
+        retry_after,
+        source: None,
+    })
+}
+
+// The error for a request that got no response, described by `message`.
+// Timeouts and refused connections are transient, the reqwest error is kept
+// as the source.
+pub(super) fn request_error(error: reqwest::Error, message: String) -> TranslateError {
+    if !error.is_timeout() && !error.is_connect() {
+        return message.into();
+    }
+    Box::new(TransientError {
+        message,
+        retry_after: None,
+        source: Some(error),
+    })
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RetryPolicy {
+    // Attempts after the first one.
+    pub retries: u32,
+    pub base_delay: Duration,
+}
+
+impl RetryPolicy {
+    pub fn from_app_config() -> RetryPolicy {
+        let retries = rotor_common::AppConfig::lock_global()
+            .get("translator_retries")
+            .and_then(|retries| retries.parse().ok())
+            .unwrap_or(2);
+        RetryPolicy {
+            retries,
+            base_delay: BASE_DELAY,
+        }
+    }
+
+    // How long to wait before retry number `attempt` (from 0) after `error`,
+    // or None when the error is not worth retrying.
+    pub(super) fn delay(&self, attempt: u32, error: &TranslateError) -> Option<Duration> {
+        if attempt >= self.retries {
+            return None;
+        }
+
+        if let Some(error) = error.downcast_ref::<TransientError>() {
+            if let Some(retry_after) = error.retry_after {
+                return (retry_after <= MAX_RETRY_AFTER).then_some(retry_after);
+            }
+        } else if !error
+            .downcast_ref::<reqwest::Error>()
+            .is_some_and(|error| error.is_timeout() || error.is_connect())
+        {
+            return None;
+        }
+
+        // Exponential backoff with jitter over the upper half of the window.
+        let backoff = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(MAX_BACKOFF);
+        let half = backoff.as_millis() as u64 / 2;
+        Some(Duration::from_millis(half + random() % (half + 1)))
+    }
+}
+
+// Retries transient failures of `engine` as long as it has not streamed any
+// text yet; output already shown cannot be taken back.
+pub(super) struct RetryingEngine<'e> {
+    engine: &'e dyn TranslationEngine,
+    policy: RetryPolicy,
+}
+
+impl<'e> RetryingEngine<'e> {
+    pub fn new(engine: &'e dyn TranslationEngine, policy: RetryPolicy) -> RetryingEngine<'e> {
+        RetryingEngine { engine, policy }
+    }
+
+    async fn stream_with_retries(
+        &self,
+        request: &TranslateRequest,
+        on_event: &OnEvent<'_>,
+    ) -> Result<TranslateResult, TranslateError> {
+        let streamed = AtomicBool::new(false);
+        let on_event = |event| {
+            if let TranslateStreamEvent::Delta { .. } = event {
+                streamed.store(true, Ordering::Relaxed);
+            }
+            on_event(event);
+        };
+
+        let mut attempt = 0;
+        loop {
+            let error = match self.engine.stream(request, &on_event).await {
+                Ok(result) => return Ok(result),
+                Err(error) => error,
+            };
+            if streamed.load(Ordering::Relaxed) {
+                return Err(error);
+            }
+            let Some(delay) = self.policy.delay(attempt, &error) else {
+                return Err(error);
+            };
+
+            log::warn!(
+                "Translation with {} failed, retrying in {delay:?}: {error}",
+                self.engine.id()
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+}
+
+impl TranslationEngine for RetryingEngine<'_> {
+    fn id(&self) -> &'static str {
+        self.engine.id()
+    }
+
+    fn model(&self) -> &str {
+        self.engine.model()
+    }
+
+    fn max_chunk_len(&self) -> usize {
+        self.engine.max_chunk_len()
+    }
+
+    fn stream<'a>(
+        &'a self,
+        request: &'a TranslateRequest,
+        on_event: &'a OnEvent<'a>,
+    ) -> BoxFuture<'a, Result<TranslateResult, TranslateError>> {
+        Box::pin(self.stream_with_retries(request, on_event))
+    }
+
+    fn lookup<'a>(
+        &'a self,
+        request: &'a TranslateRequest,
+    ) -> BoxFuture<'a, Result<Option<DictionaryResult>, TranslateError>> {
+        self.engine.lookup(request)
+    }
+
+    fn supported_languages(&self) -> &'static [&'static str] {
+        self.engine.supported_languages()
+    }
+
+    fn health_check(&self) -> BoxFuture<'_, Result<(), TranslateError>> {
+        self.engine.health_check()
+    }
+}
+
+fn random() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+// Retry-After holds either a number of seconds or an HTTP date such as
+// "Wed, 21 Oct 2015 07:28:00 GMT".
+fn parse_retry_after(value: &str, now: SystemTime) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let at = parse_http_date(value)?;
+    Some(at.duration_since(now).unwrap_or_default())
+}
+
+fn parse_http_date(value: &str) -> Option<SystemTime> {
+    let parts = value.split_whitespace().collect::<Vec<_>>();
+    let [_, day, month, year, time, "GMT"] = parts.as_slice() else {
+        return None;
+    };
+
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let month = MONTHS.iter().position(|name| name == month)? as i64 + 1;
+    let day = day.parse::<i64>().ok()?;
+    let year = year.parse::<i64>().ok()?;
+    let mut time = time.split(':').map(|part| part.parse::<u64>().ok());
+    let (hours, minutes, seconds) = (time.next()??, time.next()??, time.next()??);
+
+    // Days since 1970-01-01 in the proleptic Gregorian calendar.
+    let shifted_year = if month <= 2 { year - 1 } else { year };
+    let era = shifted_year.div_euclid(400);
+    let year_of_era = shifted_year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = u64::try_from(era * 146097 + day_of_era - 719468).ok()?;
+
+    let seconds = days * 86400 + hours * 3600 + minutes * 60 + seconds;
+    Some(UNIX_EPOCH + Duration::from_secs(seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::TranslateResult;
+    use std::sync::atomic::AtomicUsize;
+
+    fn transient(retry_after: Option<Duration>) -> TranslateError {
+        Box::new(TransientError {
+            message: "429 Too Many Requests".to_string(),
+            retry_after,
+            source: None,
+        })
+    }
+
+    #[test]
+    fn parses_retry_after_seconds_and_dates() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_445_412_470);
+
+        assert_eq!(
+            parse_retry_after("120", now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT", now),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn backs_off_transient_errors_only() {
+        let policy = RetryPolicy {
+            retries: 3,
+            base_delay: Duration::from_millis(100),
+        };
+
+        let first = policy.delay(0, &transient(None)).unwrap();
+        assert!((50..=100).contains(&first.as_millis()));
+        let third = policy.delay(2, &transient(None)).unwrap();
+        assert!((200..=400).contains(&third.as_millis()));
+        assert_eq!(policy.delay(3, &transient(None)), None);
+        assert_eq!(
+            policy.delay(0, &transient(Some(Duration::from_secs(2)))),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(
+            policy.delay(0, &transient(Some(Duration::from_secs(60)))),
+            None
+        );
+        assert_eq!(policy.delay(0, &"API key is not configured".into()), None);
+    }
+
+    // Fails with a 429 until the given number of calls.
+    struct FlakyEngine {
+        calls: AtomicUsize,
+        failures: usize,
+    }
+
+    impl TranslationEngine for FlakyEngine {
+        fn id(&self) -> &'static str {
+            "flaky"
+        }
+
+        fn stream<'a>(
+            &'a self,
+            request: &'a TranslateRequest,
+            _on_event: &'a OnEvent<'a>,
+        ) -> BoxFuture<'a, Result<TranslateResult, TranslateError>> {
+            Box::pin(async move {
+                if self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+                    return Err(transient(None));
+                }
+                Ok(request.result("你好".to_string(), "en"))
+            })
+        }
+
+        fn health_check(&self) -> BoxFuture<'_, Result<(), TranslateError>> {
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    #[tokio::test]
+    async fn retries_until_the_engine_answers() {
+        let request = TranslateRequest {
+            text: "hello".to_string(),
+            from: "auto".to_string(),
+            to: "zh-CN".to_string(),
+            glossary: Vec::new(),
+            prompt: None,
+        };
+        let policy = RetryPolicy {
+            retries: 2,
+            base_delay: Duration::from_millis(1),
+        };
+
+        let engine = FlakyEngine {
+            calls: AtomicUsize::new(0),
+            failures: 2,
+        };
+        let result = RetryingEngine::new(&engine, policy)
+            .translate(&request)
+            .await
+            .unwrap();
+        assert_eq!(result.translated, "你好");
+        assert_eq!(engine.calls.load(Ordering::SeqCst), 3);
+
+        let engine = FlakyEngine {
+            calls: AtomicUsize::new(0),
+            failures: 3,
+        };
+        assert!(RetryingEngine::new(&engine, policy)
+            .translate(&request)
+            .await
+            .is_err());
+        assert_eq!(engine.calls.load(Ordering::SeqCst), 3);
+    }
+}
@@ -0,0 +1,193 @@
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+
+use rotor_common::AppConfig;
+
+use crate::placeholder::ProtectedText;
+
+const CONFIG_KEY: &str = "translator_glossary";
+
+// A term with its fixed translation. An empty translation keeps the term as is.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GlossaryEntry {
+    pub term: String,
+    #[serde(default)]
+    pub translation: String,
+}
+
+impl GlossaryEntry {
+    fn replacement(&self) -> &str {
+        if self.translation.is_empty() {
+            &self.term
+        } else {
+            &self.translation
+        }
+    }
+}
+
+pub fn load() -> Vec<GlossaryEntry> {
+    let glossary = AppConfig::lock_global().get(CONFIG_KEY).cloned();
+    let Some(glossary) = glossary.filter(|glossary| !glossary.trim().is_empty()) else {
+        return Vec::new();
+    };
+
+    serde_json::from_str(&glossary).unwrap_or_else(|error| {
+        log::warn!("Invalid translator glossary config: {error}");
+        Vec::new()
+    })
+}
+
+pub fn save(entries: Vec<GlossaryEntry>) -> Result<Vec<GlossaryEntry>, Box<dyn Error>> {
+    let entries = normalize(entries);
+    let serialized = serde_json::to_string(&entries)?;
+    AppConfig::lock_global().set(CONFIG_KEY.to_string(), serialized)?;
+    Ok(entries)
+}
+
+// Trims every entry, drops empty terms and keeps the first entry of a term.
+pub fn normalize(entries: Vec<GlossaryEntry>) -> Vec<GlossaryEntry> {
+    let mut normalized: Vec<GlossaryEntry> = Vec::new();
+    for entry in entries {
+        let term = entry.term.trim();
+        if term.is_empty() || normalized.iter().any(|existing| existing.term == term) {
+            continue;
+        }
+        normalized.push(GlossaryEntry {
+            term: term.to_string(),
+            translation: entry.translation.trim().to_string(),
+        });
+    }
+    normalized
+}
+
+// The entries whose term occurs in `text`, longest term first so that longer
+// terms win over the shorter ones they contain.
+pub fn matching(entries: &[GlossaryEntry], text: &str) -> Vec<GlossaryEntry> {
+    let mut matched = entries
+        .iter()
+        .filter(|entry| find_term(text, &entry.term).is_some())
+        .cloned()
+        .collect::<Vec<_>>();
+    matched.sort_by_key(|entry| std::cmp::Reverse(entry.term.len()));
+    matched
+}
+
+// Glossary instructions appended to an LLM system prompt.
+pub fn prompt_section(entries: &[GlossaryEntry]) -> Option<String> {
+    if entries.is_empty() {
+        return None;
+    }
+
+    let lines = entries
+        .iter()
+        .map(|entry| {
+            if entry.translation.is_empty() {
+                format!("- {} => keep as is, do not translate", entry.term)
+            } else {
+                format!("- {} => {}", entry.term, entry.translation)
+            }
+        })
+        .collect::<Vec<_>>();
+    Some(format!(
+        "Always translate these terms exactly as listed:\n{}",
+        lines.join("\n")
+    ))
+}
+
+// Swaps glossary terms for placeholders that machine translation leaves
+// alone, so the terms can be put back untouched afterwards.
+pub fn protect(text: &str, entries: &[GlossaryEntry]) -> ProtectedText {
+    ProtectedText::new(text, ('⟦', '⟧'), |text, position| {
+        entries
+            .iter()
+            .find(|entry| is_term_at(text, position, &entry.term))
+            .map(|entry| (entry.term.len(), entry.replacement().to_string()))
+    })
+}
+
+fn find_term(text: &str, term: &str) -> Option<usize> {
+    if term.is_empty() {
+        return None;
+    }
+    text.match_indices(term)
+        .map(|(position, _)| position)
+        .find(|position| is_term_at(text, *position, term))
+}
+
+// Whether `term` starts at `position` without being part of a longer word.
+fn is_term_at(text: &str, position: usize, term: &str) -> bool {
+    if term.is_empty() || !text[position..].starts_with(term) {
+        return false;
+    }
+
+    let before = text[..position].chars().next_back();
+    let after = text[position + term.len()..].chars().next();
+    let joins_word = |edge: Option<char>, neighbour: Option<char>| {
+        edge.is_some_and(is_word_char) && neighbour.is_some_and(is_word_char)
+    };
+    !joins_word(term.chars().next(), before) && !joins_word(term.chars().next_back(), after)
+}
+
+// Only alphabetic scripts separate words with spaces; CJK terms match anywhere.
+fn is_word_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || (c.is_alphanumeric() && (c as u32) < 0x2E80)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(term: &str, translation: &str) -> GlossaryEntry {
+        GlossaryEntry {
+            term: term.to_string(),
+            translation: translation.to_string(),
+        }
+    }
+
+    #[test]
+    fn normalize_trims_and_deduplicates_terms() {
+        let entries = normalize(vec![
+            entry(" Rotor ", " 转子 "),
+            entry("", "empty"),
+            entry("Rotor", "duplicate"),
+        ]);
+
+        assert_eq!(entries, vec![entry("Rotor", "转子")]);
+    }
+
+    #[test]
+    fn matches_whole_words_longest_first() {
+        let entries = vec![entry("Rotor", ""), entry("Rotor Search", "Rotor 搜索")];
+
+        let matched = matching(&entries, "Open Rotor Search now");
+        assert_eq!(matched[0].term, "Rotor Search");
+        assert_eq!(matched.len(), 2);
+        assert!(matching(&entries, "Rotorcraft and rotors").is_empty());
+        assert_eq!(matching(&[entry("转子", "Rotor")], "打开转子设置").len(), 1);
+    }
+
+    #[test]
+    fn protects_and_restores_terms() {
+        let entries = matching(
+            &[entry("getUserId", ""), entry("Rotor", "ROTOR")],
+            "Rotor calls getUserId, then Rotor exits.",
+        );
+
+        let protected = protect("Rotor calls getUserId, then Rotor exits.", &entries);
+        assert_eq!(protected.text, "⟦0⟧ calls ⟦1⟧, then ⟦0⟧ exits.");
+        assert_eq!(
+            protected.restore("⟦ 0 ⟧ 调用 ⟦1⟧，然后 ⟦0⟧ 退出。"),
+            "ROTOR 调用 getUserId，然后 ROTOR 退出。"
+        );
+    }
+
+    #[test]
+    fn prompt_section_lists_terms() {
+        let section = prompt_section(&[entry("Rotor", ""), entry("pin", "贴图")]).unwrap();
+
+        assert!(section.contains("- Rotor => keep as is, do not translate"));
+        assert!(section.contains("- pin => 贴图"));
+        assert_eq!(prompt_section(&[]), None);
+    }
+}
@@ -0,0 +1,497 @@
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rotor_common::{file_path, AppConfig};
+
+use crate::engine::TranslateResult;
+
+const DEFAULT_HISTORY_LIMIT: usize = 5000;
+const DEFAULT_PAGE_SIZE: usize = 50;
+
+static INSTANCE: LazyLock<TranslationHistory> = LazyLock::new(TranslationHistory::new);
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    pub id: u64,
+    // Seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub engine: String,
+    pub text: String,
+    pub translated: String,
+    pub from: String,
+    pub to: String,
+}
+
+// Filters for listing the history. Every word of `query` must occur in the
+// source or translated text; `since` and `until` are inclusive Unix seconds.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HistoryQuery {
+    pub query: String,
+    pub since: Option<u64>,
+    pub until: Option<u64>,
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryPage {
+    // Matches before pagination.
+    pub total: usize,
+    pub entries: Vec<HistoryEntry>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Tmx,
+}
+
+// Every finished translation, appended to a JSON lines file in the user data
+// directory and rewritten only when entries are removed.
+pub struct TranslationHistory {
+    path: Option<PathBuf>,
+    entries: Mutex<Vec<HistoryEntry>>,
+}
+
+impl TranslationHistory {
+    pub fn new() -> TranslationHistory {
+        match file_path::get_userdata_path() {
+            Some(path) => TranslationHistory::open(path.join("translation_history.jsonl")),
+            None => {
+                log::error!("Unable to resolve user data path for translation history");
+                TranslationHistory {
+                    path: None,
+                    entries: Mutex::default(),
+                }
+            }
+        }
+    }
+
+    pub fn open(path: PathBuf) -> TranslationHistory {
+        let entries = fs::read_to_string(&path)
+            .unwrap_or_default()
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| match serde_json::from_str(line) {
+                Ok(entry) => Some(entry),
+                Err(error) => {
+                    log::warn!("Skipping invalid translation history line: {error}");
+                    None
+                }
+            })
+            .collect();
+
+        TranslationHistory {
+            path: Some(path),
+            entries: Mutex::new(entries),
+        }
+    }
+
+    pub fn global() -> &'static TranslationHistory {
+        &INSTANCE
+    }
+
+    pub fn is_enabled() -> bool {
+        AppConfig::lock_global()
+            .get("translator_history")
+            .is_none_or(|value| value == "true")
+    }
+
+    fn limit() -> usize {
+        AppConfig::lock_global()
+            .get("translator_history_limit")
+            .and_then(|value| value.parse::<usize>().ok())
+            .unwrap_or(DEFAULT_HISTORY_LIMIT)
+    }
+
+    // Records a translation when the history is enabled, logging failures so a
+    // broken history never fails the translation itself.
+    pub fn record_translation(&self, result: &TranslateResult, engine: &str) {
+        if !TranslationHistory::is_enabled() {
+            return;
+        }
+        if let Err(error) = self.add(result, engine, TranslationHistory::limit()) {
+            log::warn!("Failed to record translation history: {error}");
+        }
+    }
+
+    pub fn add(
+        &self,
+        result: &TranslateResult,
+        engine: &str,
+        limit: usize,
+    ) -> Result<HistoryEntry, Box<dyn Error>> {
+        let path = self.path()?;
+        let mut entries = self.lock_entries();
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?;
+        let last_id = entries.last().map(|entry| entry.id);
+        let id = last_id
+            .map_or(0, |id| id + 1)
+            .max(timestamp.as_millis() as u64);
+
+        let entry = HistoryEntry {
+            id,
+            timestamp: timestamp.as_secs(),
+            engine: engine.to_string(),
+            text: result.text.clone(),
+            translated: result.translated.clone(),
+            from: result.from.clone(),
+            to: result.to.clone(),
+        };
+        entries.push(entry.clone());
+
+        // Prune in batches so a full history is not rewritten on every add.
+        let limit = limit.max(1);
+        if entries.len() > limit + limit / 10 {
+            let overflow = entries.len() - limit;
+            entries.drain(..overflow);
+            write_entries(path, &entries)?;
+        } else {
+            append_entry(path, &entry)?;
+        }
+        Ok(entry)
+    }
+
+    // Matching entries, newest first.
+    pub fn search(&self, query: &HistoryQuery) -> HistoryPage {
+        let entries = self.lock_entries();
+        let matches = filter(&entries, query);
+        HistoryPage {
+            total: matches.len(),
+            entries: matches
+                .into_iter()
+                .skip(query.offset)
+                .take(query.limit.unwrap_or(DEFAULT_PAGE_SIZE))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    // Writes every entry matching the query's filters, oldest first, ignoring
+    // its pagination. Returns the number of exported entries.
+    pub fn export(
+        &self,
+        query: &HistoryQuery,
+        format: ExportFormat,
+        path: &Path,
+    ) -> Result<usize, Box<dyn Error>> {
+        let entries = self.lock_entries();
+        let mut matches = filter(&entries, query);
+        matches.reverse();
+
+        let content = match format {
+            ExportFormat::Csv => to_csv(&matches),
+            ExportFormat::Tmx => to_tmx(&matches),
+        };
+        fs::write(path, content)?;
+        Ok(matches.len())
+    }
+
+    pub fn delete(&self, id: u64) -> Result<(), Box<dyn Error>> {
+        let path = self.path()?;
+        let mut entries = self.lock_entries();
+        let len = entries.len();
+        entries.retain(|entry| entry.id != id);
+        if entries.len() != len {
+            write_entries(path, &entries)?;
+        }
+        Ok(())
+    }
+
+    pub fn clear(&self) -> Result<(), Box<dyn Error>> {
+        let path = self.path()?;
+        let mut entries = self.lock_entries();
+        entries.clear();
+        write_entries(path, &entries)
+    }
+
+    fn path(&self) -> Result<&Path, Box<dyn Error>> {
+        self.path
+            .as_deref()
+            .ok_or_else(|| Box::<dyn Error>::from("Translation history path is unavailable"))
+    }
+
+    fn lock_entries(&self) -> MutexGuard<'_, Vec<HistoryEntry>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Default for TranslationHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn filter<'a>(entries: &'a [HistoryEntry], query: &HistoryQuery) -> Vec<&'a HistoryEntry> {
+    let terms = query
+        .query
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>();
+
+    entries
+        .iter()
+        .rev()
+        .filter(|entry| query.since.is_none_or(|since| entry.timestamp >= since))
+        .filter(|entry| query.until.is_none_or(|until| entry.timestamp <= until))
+        .filter(|entry| {
+            let text = format!("{}\n{}", entry.text, entry.translated).to_lowercase();
+            terms.iter().all(|term| text.contains(term.as_str()))
+        })
+        .collect()
+}
+
+fn append_entry(path: &Path, entry: &HistoryEntry) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(entry)?)?;
+    Ok(())
+}
+
+fn write_entries(path: &Path, entries: &[HistoryEntry]) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut content = String::new();
+    for entry in entries {
+        content.push_str(&serde_json::to_string(entry)?);
+        content.push('\n');
+    }
+    fs::write(path, content)?;
+    Ok(())
+}
+
+fn to_csv(entries: &[&HistoryEntry]) -> String {
+    let mut csv = String::from("timestamp,engine,from,to,text,translated\r\n");
+    for entry in entries {
+        let fields = [
+            entry.timestamp.to_string(),
+            entry.engine.clone(),
+            entry.from.clone(),
+            entry.to.clone(),
+            entry.text.clone(),
+            entry.translated.clone(),
+        ];
+        let row = fields
+            .iter()
+            .map(|field| csv_field(field))
+            .collect::<Vec<_>>();
+        csv.push_str(&row.join(","));
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// TMX 1.4 with one translation unit per entry. Units whose source language was
+// never detected are tagged `und`.
+fn to_tmx(entries: &[&HistoryEntry]) -> String {
+    let mut tmx = String::from(concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<tmx version=\"1.4\">\n",
+        "  <header creationtool=\"Rotor\" creationtoolversion=\"",
+        env!("CARGO_PKG_VERSION"),
+        "\" segtype=\"block\" o-tmf=\"rotor\" adminlang=\"en\" srclang=\"*all*\" datatype=\"plaintext\"/>\n",
+        "  <body>\n",
+    ));
+    for entry in entries {
+        let from = if entry.from.is_empty() || entry.from == "auto" {
+            "und"
+        } else {
+            &entry.from
+        };
+        tmx.push_str(&format!(
+            "    <tu creationdate=\"{}\" creationtool=\"{}\">\n",
+            tmx_date(entry.timestamp),
+            xml_escape(&entry.engine)
+        ));
+        for (lang, segment) in [(from, &entry.text), (entry.to.as_str(), &entry.translated)] {
+            tmx.push_str(&format!(
+                "      <tuv xml:lang=\"{}\"><seg>{}</seg></tuv>\n",
+                xml_escape(lang),
+                xml_escape(segment)
+            ));
+        }
+        tmx.push_str("    </tu>\n");
+    }
+    tmx.push_str("  </body>\n</tmx>\n");
+    tmx
+}
+
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters other than tab and line breaks are invalid XML.
+            c if c.is_control() && !matches!(c, '\t' | '\n' | '\r') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// TMX dates use the basic ISO 8601 UTC form, e.g. 20240131T081500Z.
+fn tmx_date(timestamp: u64) -> String {
+    let days = (timestamp / 86_400) as i64;
+    let seconds = timestamp % 86_400;
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm).
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}{month:02}{day:02}T{:02}{:02}{:02}Z",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "rotor-translation-history-{name}-{}.jsonl",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    fn result(text: &str, translated: &str) -> TranslateResult {
+        TranslateResult {
+            text: text.to_string(),
+            translated: translated.to_string(),
+            from: "en".to_string(),
+            to: "zh-CN".to_string(),
+            engine: "google".to_string(),
+        }
+    }
+
+    #[test]
+    fn searches_pages_and_reloads_entries() {
+        let path = temp_path("search");
+        let history = TranslationHistory::open(path.clone());
+        let first = history
+            .add(&result("Access denied", "访问被拒绝"), "google", 10)
+            .unwrap();
+        let second = history
+            .add(&result("File not found", "找不到文件"), "deepseek", 10)
+            .unwrap();
+        history.add(&result("Hello", "你好"), "google", 10).unwrap();
+
+        let reopened = TranslationHistory::open(path);
+        let page = reopened.search(&HistoryQuery {
+            offset: 1,
+            limit: Some(1),
+            ..HistoryQuery::default()
+        });
+        assert_eq!(page.total, 3);
+        assert_eq!(page.entries, vec![second.clone()]);
+
+        let ids = |query: HistoryQuery| {
+            reopened
+                .search(&query)
+                .entries
+                .into_iter()
+                .map(|entry| entry.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            ids(HistoryQuery {
+                query: "文件 FILE".to_string(),
+                ..HistoryQuery::default()
+            }),
+            vec![second.id]
+        );
+        assert!(ids(HistoryQuery {
+            until: Some(first.timestamp - 1),
+            ..HistoryQuery::default()
+        })
+        .is_empty());
+
+        reopened.delete(first.id).unwrap();
+        assert_eq!(reopened.search(&HistoryQuery::default()).total, 2);
+    }
+
+    #[test]
+    fn prunes_oldest_entries_over_limit() {
+        let path = temp_path("limit");
+        let history = TranslationHistory::open(path.clone());
+        for text in ["one", "two", "three"] {
+            history.add(&result(text, text), "google", 2).unwrap();
+        }
+
+        let texts = TranslationHistory::open(path)
+            .search(&HistoryQuery::default())
+            .entries
+            .into_iter()
+            .map(|entry| entry.text)
+            .collect::<Vec<_>>();
+        assert_eq!(texts, vec!["three", "two"]);
+    }
+
+    #[test]
+    fn exports_csv_and_tmx() {
+        let history = TranslationHistory::open(temp_path("export"));
+        history
+            .add(&result("Say \"hi\", <b>", "说“你好”"), "google", 10)
+            .unwrap();
+        let entries = history.lock_entries().clone();
+        let entries = entries.iter().collect::<Vec<_>>();
+
+        let csv = to_csv(&entries);
+        assert!(csv.starts_with("timestamp,engine,from,to,text,translated\r\n"));
+        assert!(csv.contains(",google,en,zh-CN,\"Say \"\"hi\"\", <b>\",说“你好”\r\n"));
+
+        let tmx = to_tmx(&entries);
+        assert!(tmx.contains("<tuv xml:lang=\"en\"><seg>Say &quot;hi&quot;, &lt;b&gt;</seg></tuv>"));
+        assert!(tmx.contains("<tuv xml:lang=\"zh-CN\"><seg>说“你好”</seg></tuv>"));
+    }
+
+    #[test]
+    fn formats_tmx_dates() {
+        assert_eq!(tmx_date(0), "19700101T000000Z");
+        assert_eq!(tmx_date(1_706_688_900), "20240131T081500Z");
+        assert_eq!(tmx_date(951_782_400), "20000229T000000Z");
+    }
+}
@@ -1,4 +1,11 @@
+pub mod cache;
+pub mod detect;
+pub mod document;
 pub mod engine;
+pub mod glossary;
+pub mod history;
+pub mod placeholder;
+pub mod prompt;
 mod selection;
 
 use std::error::Error;
@@ -9,12 +16,16 @@ use std::time::Duration;
 use tauri::{Emitter, Manager, PhysicalPosition, WebviewUrl, WebviewWindow, WebviewWindowBuilder};
 use tauri_plugin_clipboard_manager::ClipboardExt;
 use tauri_plugin_global_shortcut::Shortcut;
+use tauri_plugin_notification::NotificationExt;
 
-use rotor_common::AppConfig;
+use rotor_common::{i18n, AppConfig};
 
 const WINDOW_LABEL: &str = "translator";
 const CLIPBOARD_POLL_INTERVAL: Duration = Duration::from_millis(50);
 const CLIPBOARD_WAIT_TIMEOUT: Duration = Duration::from_millis(1000);
+// Applications read the clipboard some time after the paste keystroke, so it
+// is only restored after this delay.
+const PASTE_SETTLE_DELAY: Duration = Duration::from_millis(300);
 const CURSOR_OFFSET: i32 = 16;
 
 pub struct Translator {
@@ -44,12 +55,16 @@ impl Translator {
         Self::read_shortcut("shortcut_translate_input")
     }
 
+    pub fn get_replace_shortcut(&self) -> Option<Shortcut> {
+        Self::read_shortcut("shortcut_translate_replace")
+    }
+
     fn read_shortcut(key: &str) -> Option<Shortcut> {
         let app_config = AppConfig::lock_global();
         let shortcut = app_config.get(key).cloned();
         drop(app_config);
 
-        if let Some(shortcut_str) = shortcut {
+        if let Some(shortcut_str) = shortcut.filter(|shortcut| !shortcut.is_empty()) {
             match Shortcut::from_str(&shortcut_str) {
                 Ok(shortcut) => return Some(shortcut),
                 Err(error) => {
@@ -88,6 +103,32 @@ impl Translator {
         Ok(())
     }
 
+    /// Replace translation: translate the selected text and paste the
+    /// translation over the selection, leaving the clipboard as it was.
+    pub fn run_replace(&self) -> Result<(), Box<dyn Error>> {
+        let app_handle = match &self.app_handler {
+            Some(handle) => handle.clone(),
+            None => return Err("AppHandle not initialized".into()),
+        };
+
+        thread::Builder::new()
+            .name("rotor-translate-replace".to_string())
+            .spawn(move || {
+                match capture_selected_text(&app_handle) {
+                    Ok(Some(text)) => {
+                        if let Err(e) = replace_selection(&app_handle, &text) {
+                            log::error!("Translator replace selection error: {e}");
+                            notify_replace_error(&app_handle, &e.to_string());
+                        }
+                    }
+                    Ok(None) => log::debug!("Translator: no selected text captured"),
+                    Err(e) => log::error!("Translator capture selection error: {e}"),
+                }
+            })?;
+
+        Ok(())
+    }
+
     /// Input translation: show the translator window near the cursor with an
     /// empty, focused input box.
     pub fn run_input(&self) -> Result<(), Box<dyn Error>> {
@@ -134,6 +175,11 @@ impl Translator {
 fn capture_selected_text(
     app: &tauri::AppHandle,
 ) -> Result<Option<String>, Box<dyn Error + Send + Sync>> {
+    // Linux exposes the selection directly, without touching the clipboard.
+    if let Some(text) = selection::primary_selection() {
+        return Ok(Some(text.trim().to_string()));
+    }
+
     let clipboard = app.clipboard();
     let previous_text = clipboard.read_text().ok();
     let previous_change_count = selection::clipboard_change_count();
@@ -174,6 +220,48 @@ fn capture_selected_text(
     Ok(text)
 }
 
+fn replace_selection(
+    app: &tauri::AppHandle,
+    text: &str,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let result = tauri::async_runtime::block_on(engine::translate(text, None, |_| {}))?;
+    paste_text(app, &result.translated)
+}
+
+// Replacing shows no window, so without a notification a failure would look
+// like the shortcut did nothing.
+fn notify_replace_error(app: &tauri::AppHandle, message: &str) {
+    let shown = app
+        .notification()
+        .builder()
+        .title(i18n::t("translateReplaceFailed"))
+        .body(message)
+        .show();
+    if let Err(e) = shown {
+        log::warn!("Translator: failed to show replace error notification: {e}");
+    }
+}
+
+// Pastes `text` over the selection through the clipboard, then puts the
+// previous clipboard text back.
+fn paste_text(app: &tauri::AppHandle, text: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let clipboard = app.clipboard();
+    let previous_text = clipboard.read_text().ok();
+
+    clipboard
+        .write_text(text)
+        .map_err(|e| format!("Failed to write translation to clipboard: {e}"))?;
+    let pasted = selection::simulate_paste();
+    thread::sleep(PASTE_SETTLE_DELAY);
+
+    if let Some(previous) = previous_text {
+        if let Err(e) = clipboard.write_text(previous) {
+            log::warn!("Translator: failed to restore clipboard text: {e}");
+        }
+    }
+    pasted
+}
+
 fn clipboard_has_changed(
     previous_text: &Option<String>,
     current_text: &Option<String>,
@@ -0,0 +1,91 @@
+// Text with the parts that engines must not rewrite swapped for numbered
+// placeholders, so they can be put back untouched after translation.
+pub struct ProtectedText {
+    pub text: String,
+    // Opening and closing characters of the placeholders.
+    brackets: (char, char),
+    replacements: Vec<(String, String)>,
+}
+
+impl ProtectedText {
+    // `matcher` is called at every position of `text` and returns the length
+    // of the part to protect there along with what to put back in its place.
+    // Parts restored to the same text share a placeholder.
+    pub fn new<F>(text: &str, brackets: (char, char), matcher: F) -> ProtectedText
+    where
+        F: Fn(&str, usize) -> Option<(usize, String)>,
+    {
+        let (open, close) = brackets;
+        let mut protected = String::with_capacity(text.len());
+        let mut replacements: Vec<(String, String)> = Vec::new();
+        let mut position = 0;
+
+        while position < text.len() {
+            match matcher(text, position).filter(|(len, _)| *len > 0) {
+                Some((len, replacement)) => {
+                    let existing = replacements
+                        .iter()
+                        .find(|(_, existing)| *existing == replacement);
+                    let placeholder = match existing {
+                        Some((placeholder, _)) => placeholder.clone(),
+                        None => {
+                            let placeholder = format!("{open}{}{close}", replacements.len());
+                            replacements.push((placeholder.clone(), replacement));
+                            placeholder
+                        }
+                    };
+                    protected.push_str(&placeholder);
+                    position += len;
+                }
+                None => {
+                    let c = text[position..]
+                        .chars()
+                        .next()
+                        .expect("position is in text");
+                    protected.push(c);
+                    position += c.len_utf8();
+                }
+            }
+        }
+
+        ProtectedText {
+            text: protected,
+            brackets,
+            replacements,
+        }
+    }
+
+    pub fn restore(&self, translated: &str) -> String {
+        let (open, close) = self.brackets;
+        let mut restored = translated.to_string();
+        for (placeholder, replacement) in &self.replacements {
+            // Some engines pad the placeholder with spaces.
+            let padded = placeholder
+                .replace(open, &format!("{open} "))
+                .replace(close, &format!(" {close}"));
+            restored = restored
+                .replace(&padded, replacement)
+                .replace(placeholder, replacement);
+        }
+        restored
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shares_placeholders_and_restores_padded_ones() {
+        let protected = ProtectedText::new("a1b22a3", ('⟦', '⟧'), |text, position| {
+            let len = text[position..]
+                .chars()
+                .take_while(|c| c.is_ascii_digit())
+                .count();
+            Some((len, "#".repeat(len)))
+        });
+
+        assert_eq!(protected.text, "a⟦0⟧b⟦1⟧a⟦0⟧");
+        assert_eq!(protected.restore("A⟦ 0 ⟧B⟦1⟧A⟦0⟧"), "A#B##A#");
+    }
+}
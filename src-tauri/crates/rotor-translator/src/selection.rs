@@ -1,12 +1,24 @@
-//! Simulates a copy keystroke (Ctrl+C / Cmd+C) so the currently selected
-//! text in the foreground application lands on the clipboard.
+//! Simulates copy and paste keystrokes (Ctrl+C / Cmd+C, Ctrl+V / Cmd+V) so
+//! the selected text in the foreground application lands on the clipboard,
+//! or the clipboard replaces it. On Linux the selection is read from the
+//! PRIMARY selection first.
 
 use std::error::Error;
 
 #[cfg(target_os = "windows")]
 pub fn simulate_copy() -> Result<(), Box<dyn Error + Send + Sync>> {
+    simulate_ctrl_key(windows::Win32::UI::Input::KeyboardAndMouse::VK_C.0)
+}
+
+#[cfg(target_os = "windows")]
+pub fn simulate_paste() -> Result<(), Box<dyn Error + Send + Sync>> {
+    simulate_ctrl_key(windows::Win32::UI::Input::KeyboardAndMouse::VK_V.0)
+}
+
+#[cfg(target_os = "windows")]
+fn simulate_ctrl_key(key: u16) -> Result<(), Box<dyn Error + Send + Sync>> {
     use windows::Win32::UI::Input::KeyboardAndMouse::{
-        SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYEVENTF_KEYUP, VK_C, VK_CONTROL,
+        SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYEVENTF_KEYUP, VK_CONTROL,
     };
 
     wait_for_modifiers_release();
@@ -30,8 +42,8 @@ pub fn simulate_copy() -> Result<(), Box<dyn Error + Send + Sync>> {
 
     let inputs = [
         input(VK_CONTROL.0, false),
-        input(VK_C.0, false),
-        input(VK_C.0, true),
+        input(key, false),
+        input(key, true),
         input(VK_CONTROL.0, true),
     ];
 
@@ -69,12 +81,25 @@ fn wait_for_modifiers_release() {
 
 #[cfg(target_os = "macos")]
 pub fn simulate_copy() -> Result<(), Box<dyn Error + Send + Sync>> {
-    use core_graphics::event::{CGEvent, CGEventFlags, CGEventTapLocation};
-    use core_graphics::event_source::{CGEventSource, CGEventSourceStateID};
-
     // Virtual key code for the "C" key.
     const KEY_CODE_C: u16 = 8;
 
+    simulate_cmd_key(KEY_CODE_C, "Cmd+C")
+}
+
+#[cfg(target_os = "macos")]
+pub fn simulate_paste() -> Result<(), Box<dyn Error + Send + Sync>> {
+    // Virtual key code for the "V" key.
+    const KEY_CODE_V: u16 = 9;
+
+    simulate_cmd_key(KEY_CODE_V, "Cmd+V")
+}
+
+#[cfg(target_os = "macos")]
+fn simulate_cmd_key(key_code: u16, name: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
+    use core_graphics::event::{CGEvent, CGEventFlags, CGEventTapLocation};
+    use core_graphics::event_source::{CGEventSource, CGEventSourceStateID};
+
     if !request_accessibility_permission() {
         return Err("macOS Accessibility permission is required for selection translation".into());
     }
@@ -84,13 +109,13 @@ pub fn simulate_copy() -> Result<(), Box<dyn Error + Send + Sync>> {
     let source = CGEventSource::new(CGEventSourceStateID::CombinedSessionState)
         .map_err(|_| std::io::Error::other("Failed to create CGEventSource"))?;
 
-    let key_down = CGEvent::new_keyboard_event(source.clone(), KEY_CODE_C, true)
-        .map_err(|_| std::io::Error::other("Failed to create Cmd+C key-down event"))?;
+    let key_down = CGEvent::new_keyboard_event(source.clone(), key_code, true)
+        .map_err(|_| std::io::Error::other(format!("Failed to create {name} key-down event")))?;
     key_down.set_flags(CGEventFlags::CGEventFlagCommand);
     key_down.post(CGEventTapLocation::HID);
 
-    let key_up = CGEvent::new_keyboard_event(source, KEY_CODE_C, false)
-        .map_err(|_| std::io::Error::other("Failed to create Cmd+C key-up event"))?;
+    let key_up = CGEvent::new_keyboard_event(source, key_code, false)
+        .map_err(|_| std::io::Error::other(format!("Failed to create {name} key-up event")))?;
     key_up.set_flags(CGEventFlags::CGEventFlagCommand);
     key_up.post(CGEventTapLocation::HID);
 
@@ -153,7 +178,354 @@ pub fn clipboard_change_count() -> Option<isize> {
     None
 }
 
-#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
 pub fn simulate_copy() -> Result<(), Box<dyn Error + Send + Sync>> {
     Err("Simulated copy is not supported on this platform".into())
 }
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+pub fn simulate_paste() -> Result<(), Box<dyn Error + Send + Sync>> {
+    Err("Simulated paste is not supported on this platform".into())
+}
+
+/// Linux exposes the selected text directly as the PRIMARY selection (on X11,
+/// and on Wayland through the data-control protocol), so no keystroke needs
+/// to be simulated and the clipboard stays untouched.
+#[cfg(target_os = "linux")]
+pub fn primary_selection() -> Option<String> {
+    use arboard::{Clipboard, GetExtLinux, LinuxClipboardKind};
+
+    let mut clipboard = Clipboard::new()
+        .map_err(|error| log::debug!("Failed to open the clipboard: {error}"))
+        .ok()?;
+    clipboard
+        .get()
+        .clipboard(LinuxClipboardKind::Primary)
+        .text()
+        .map_err(|error| log::debug!("Failed to read the primary selection: {error}"))
+        .ok()
+        .filter(|text| !text.trim().is_empty())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn primary_selection() -> Option<String> {
+    None
+}
+
+// The X11 keysym and evdev key code of a key.
+#[cfg(target_os = "linux")]
+#[derive(Clone, Copy)]
+struct LinuxKey {
+    keysym: u32,
+    code: u16,
+}
+
+#[cfg(target_os = "linux")]
+const LINUX_KEY_CONTROL: LinuxKey = LinuxKey {
+    keysym: 0xffe3,
+    code: 29,
+};
+#[cfg(target_os = "linux")]
+const LINUX_KEY_C: LinuxKey = LinuxKey {
+    keysym: 0x63,
+    code: 46,
+};
+#[cfg(target_os = "linux")]
+const LINUX_KEY_V: LinuxKey = LinuxKey {
+    keysym: 0x76,
+    code: 47,
+};
+
+// Left and right Ctrl, Shift, Alt and Super.
+#[cfg(target_os = "linux")]
+const LINUX_MODIFIER_CODES: [u16; 8] = [29, 97, 42, 54, 56, 100, 125, 126];
+
+#[cfg(target_os = "linux")]
+pub fn simulate_copy() -> Result<(), Box<dyn Error + Send + Sync>> {
+    simulate_linux_ctrl_key(LINUX_KEY_C)
+}
+
+#[cfg(target_os = "linux")]
+pub fn simulate_paste() -> Result<(), Box<dyn Error + Send + Sync>> {
+    simulate_linux_ctrl_key(LINUX_KEY_V)
+}
+
+/// XTest only reaches X clients, so Wayland sessions (and X servers without
+/// the extension) go through a short-lived uinput keyboard instead.
+#[cfg(target_os = "linux")]
+fn simulate_linux_ctrl_key(key: LinuxKey) -> Result<(), Box<dyn Error + Send + Sync>> {
+    if std::env::var_os("WAYLAND_DISPLAY").is_none() {
+        match simulate_xtest_ctrl_key(key) {
+            Ok(()) => return Ok(()),
+            Err(error) => log::warn!("XTest key simulation failed, trying uinput: {error}"),
+        }
+    }
+    simulate_uinput_ctrl_key(key)
+}
+
+#[cfg(target_os = "linux")]
+fn simulate_xtest_ctrl_key(key: LinuxKey) -> Result<(), Box<dyn Error + Send + Sync>> {
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{ConnectionExt as _, KEY_PRESS_EVENT, KEY_RELEASE_EVENT};
+    use x11rb::protocol::xtest::ConnectionExt as _;
+    use x11rb::wrapper::ConnectionExt as _;
+
+    let (connection, screen) = x11rb::connect(None)?;
+    let setup = connection.setup();
+    let root = setup.roots[screen].root;
+    let min_keycode = setup.min_keycode;
+    let mapping = connection
+        .get_keyboard_mapping(min_keycode, setup.max_keycode - min_keycode + 1)?
+        .reply()?;
+    let keycode = |key: LinuxKey| {
+        keycode_for_keysym(
+            &mapping.keysyms,
+            mapping.keysyms_per_keycode,
+            min_keycode,
+            key.keysym,
+        )
+        .ok_or_else(|| format!("No keycode for keysym {:#x}", key.keysym))
+    };
+    let control = keycode(LINUX_KEY_CONTROL)?;
+    let key = keycode(key)?;
+
+    wait_for_x11_modifiers_release(&connection)?;
+    for (event, keycode) in [
+        (KEY_PRESS_EVENT, control),
+        (KEY_PRESS_EVENT, key),
+        (KEY_RELEASE_EVENT, key),
+        (KEY_RELEASE_EVENT, control),
+    ] {
+        connection.xtest_fake_input(event, keycode, x11rb::CURRENT_TIME, root, 0, 0, 0)?;
+    }
+    connection.sync()?;
+    Ok(())
+}
+
+/// The first keycode whose keysyms include `keysym`.
+#[cfg(target_os = "linux")]
+fn keycode_for_keysym(
+    keysyms: &[u32],
+    keysyms_per_keycode: u8,
+    min_keycode: u8,
+    keysym: u32,
+) -> Option<u8> {
+    let index = keysyms
+        .chunks(usize::from(keysyms_per_keycode.max(1)))
+        .position(|keysyms| keysyms.contains(&keysym))?;
+    u8::try_from(index + usize::from(min_keycode)).ok()
+}
+
+/// Same as the Windows and macOS variants: a held Shift from the hotkey would
+/// turn the simulated Ctrl+C into Ctrl+Shift+C.
+#[cfg(target_os = "linux")]
+fn wait_for_x11_modifiers_release(
+    connection: &impl x11rb::connection::Connection,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    use std::time::{Duration, Instant};
+    use x11rb::protocol::xproto::ConnectionExt as _;
+
+    const WAIT_TIMEOUT: Duration = Duration::from_millis(1500);
+    const POLL_INTERVAL: Duration = Duration::from_millis(30);
+
+    let modifiers = connection.get_modifier_mapping()?.reply()?.keycodes;
+    let start = Instant::now();
+    while start.elapsed() < WAIT_TIMEOUT {
+        let keys = connection.query_keymap()?.reply()?.keys;
+        let pressed = modifiers.iter().any(|&keycode| {
+            keycode != 0 && keys[usize::from(keycode / 8)] & (1 << (keycode % 8)) != 0
+        });
+        if !pressed {
+            break;
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+    Ok(())
+}
+
+/// Wayland has no way to query the keyboard, so the held modifiers are read
+/// from the evdev devices this user can open, usually through the `input`
+/// group. Without any, the keys are sent right away.
+#[cfg(target_os = "linux")]
+fn wait_for_evdev_modifiers_release() {
+    use std::os::fd::AsRawFd;
+    use std::time::{Duration, Instant};
+
+    // EVIOCGKEY with a bitmap large enough for KEY_MAX.
+    const EVIOCGKEY: u32 = 0x8060_4518;
+    const WAIT_TIMEOUT: Duration = Duration::from_millis(1500);
+    const POLL_INTERVAL: Duration = Duration::from_millis(30);
+
+    let devices = std::fs::read_dir("/dev/input")
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("event"))
+        .filter_map(|entry| std::fs::File::open(entry.path()).ok())
+        .collect::<Vec<_>>();
+    if devices.is_empty() {
+        log::debug!("No readable input device to wait for modifier release");
+        return;
+    }
+
+    let modifier_held = || {
+        devices.iter().any(|device| {
+            let mut keys = [0u8; 96];
+            let result = unsafe {
+                libc::ioctl(
+                    device.as_raw_fd(),
+                    EVIOCGKEY as libc::Ioctl,
+                    keys.as_mut_ptr(),
+                )
+            };
+            result >= 0 && has_modifier_down(&keys)
+        })
+    };
+    let start = Instant::now();
+    while start.elapsed() < WAIT_TIMEOUT && modifier_held() {
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Whether an evdev key bitmap has any modifier pressed.
+#[cfg(target_os = "linux")]
+fn has_modifier_down(keys: &[u8]) -> bool {
+    LINUX_MODIFIER_CODES.iter().any(|&code| {
+        keys.get(usize::from(code / 8))
+            .is_some_and(|byte| byte & (1 << (code % 8)) != 0)
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn simulate_uinput_ctrl_key(key: LinuxKey) -> Result<(), Box<dyn Error + Send + Sync>> {
+    use std::fs::OpenOptions;
+    use std::io::Write;
+    use std::os::fd::AsRawFd;
+    use std::time::Duration;
+
+    // From linux/uinput.h and linux/input-event-codes.h.
+    const UI_DEV_CREATE: u32 = 0x5501;
+    const UI_DEV_DESTROY: u32 = 0x5502;
+    const UI_DEV_SETUP: u32 = 0x405c_5503;
+    const UI_SET_EVBIT: u32 = 0x4004_5564;
+    const UI_SET_KEYBIT: u32 = 0x4004_5565;
+    const BUS_VIRTUAL: u16 = 0x06;
+    const EV_SYN: u16 = 0x00;
+    const EV_KEY: u16 = 0x01;
+    const SYN_REPORT: u16 = 0;
+    // The compositor needs a moment to pick up a new device, and to read its
+    // events before the device goes away again.
+    const DEVICE_SETTLE_DELAY: Duration = Duration::from_millis(200);
+
+    let mut device = OpenOptions::new()
+        .write(true)
+        .open("/dev/uinput")
+        .map_err(|error| {
+            format!("Failed to open /dev/uinput, which needs write access for this user: {error}")
+        })?;
+    let fd = device.as_raw_fd();
+    let check = |result: libc::c_int| {
+        if result < 0 {
+            Err(std::io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    };
+
+    let mut setup: libc::uinput_setup = unsafe { std::mem::zeroed() };
+    setup.id.bustype = BUS_VIRTUAL;
+    for (target, source) in setup.name.iter_mut().zip(b"rotor-keyboard") {
+        *target = *source as libc::c_char;
+    }
+    unsafe {
+        check(libc::ioctl(
+            fd,
+            UI_SET_EVBIT as libc::Ioctl,
+            libc::c_int::from(EV_KEY),
+        ))?;
+        for code in [LINUX_KEY_CONTROL.code, key.code] {
+            check(libc::ioctl(
+                fd,
+                UI_SET_KEYBIT as libc::Ioctl,
+                libc::c_int::from(code),
+            ))?;
+        }
+        check(libc::ioctl(fd, UI_DEV_SETUP as libc::Ioctl, &setup))?;
+        check(libc::ioctl(fd, UI_DEV_CREATE as libc::Ioctl))?;
+    }
+    std::thread::sleep(DEVICE_SETTLE_DELAY);
+    wait_for_evdev_modifiers_release();
+
+    let mut write_event = |type_: u16, code: u16, value: i32| {
+        let mut event: libc::input_event = unsafe { std::mem::zeroed() };
+        event.type_ = type_;
+        event.code = code;
+        event.value = value;
+        let bytes = unsafe {
+            std::slice::from_raw_parts(
+                (&event as *const libc::input_event).cast::<u8>(),
+                std::mem::size_of::<libc::input_event>(),
+            )
+        };
+        device.write_all(bytes)
+    };
+    let written = [
+        (LINUX_KEY_CONTROL.code, 1),
+        (key.code, 1),
+        (key.code, 0),
+        (LINUX_KEY_CONTROL.code, 0),
+    ]
+    .into_iter()
+    .try_for_each(|(code, value)| {
+        write_event(EV_KEY, code, value)?;
+        write_event(EV_SYN, SYN_REPORT, 0)
+    });
+    std::thread::sleep(DEVICE_SETTLE_DELAY);
+
+    unsafe {
+        libc::ioctl(fd, UI_DEV_DESTROY as libc::Ioctl);
+    }
+    Ok(written?)
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_keycodes_by_keysym() {
+        // Two keysyms per keycode, starting at keycode 8.
+        let keysyms = [0xffe3, 0, 0x63, 0x43, 0x76, 0x56];
+
+        assert_eq!(
+            keycode_for_keysym(&keysyms, 2, 8, LINUX_KEY_CONTROL.keysym),
+            Some(8)
+        );
+        assert_eq!(
+            keycode_for_keysym(&keysyms, 2, 8, LINUX_KEY_C.keysym),
+            Some(9)
+        );
+        assert_eq!(
+            keycode_for_keysym(&keysyms, 2, 8, LINUX_KEY_V.keysym),
+            Some(10)
+        );
+        assert_eq!(keycode_for_keysym(&keysyms, 2, 8, 0x61), None);
+    }
+
+    #[test]
+    fn detects_held_modifiers_in_key_bitmap() {
+        let mut keys = [0u8; 96];
+        // KEY_A is not a modifier.
+        keys[30 / 8] |= 1 << (30 % 8);
+        assert!(!has_modifier_down(&keys));
+
+        // KEY_LEFTSHIFT
+        keys[42 / 8] |= 1 << (42 % 8);
+        assert!(has_modifier_down(&keys));
+
+        let mut keys = [0u8; 96];
+        // KEY_RIGHTMETA
+        keys[126 / 8] |= 1 << (126 % 8);
+        assert!(has_modifier_down(&keys));
+    }
+}
@@ -0,0 +1,222 @@
+use std::io::{self, IsTerminal, Read};
+use std::path::{Path, PathBuf};
+
+use rotor_runtime::ipc;
+use serde_json::{json, Value};
+
+const USAGE: &str = "Usage:
+  rotor search <query> [--limit <n>] [--json]
+  rotor screenshot --out <file.png> [--region <x,y,w,h>] [--json]
+  rotor ocr <image> [--json]
+  rotor translate [<text>] [--json]      reads stdin when no text is given
+  rotor history [<words>] [--limit <n>] [--json]
+
+Commands are forwarded to the running Rotor instance.";
+
+enum CliCommand {
+    Search { query: String, limit: Option<u64> },
+    Screenshot { out: PathBuf, region: Option<Value> },
+    Ocr { image: PathBuf },
+    Translate { text: String },
+    History { query: String, limit: Option<u64> },
+    Help,
+}
+
+// Runs `rotor <command>` when the process was started as a command-line client
+// and returns its exit code, or `None` to start the application as usual.
+pub fn run_from_args() -> Option<i32> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let command = args.first()?;
+    if !matches!(
+        command.as_str(),
+        "search" | "screenshot" | "ocr" | "translate" | "history" | "help" | "--help" | "-h"
+    ) {
+        return None;
+    }
+
+    #[cfg(target_os = "windows")]
+    rotor_platform::sys_util::attach_parent_console();
+
+    let mut json = false;
+    let rest = args[1..]
+        .iter()
+        .filter(|arg| {
+            let is_json = *arg == "--json";
+            json |= is_json;
+            !is_json
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let result = parse_command(command, &rest).and_then(|command| match command {
+        CliCommand::Help => {
+            println!("{USAGE}");
+            Ok(())
+        }
+        command => execute(command, json),
+    });
+    match result {
+        Ok(()) => Some(0),
+        Err(error) => {
+            eprintln!("rotor: {error}");
+            Some(1)
+        }
+    }
+}
+
+fn parse_command(command: &str, args: &[String]) -> Result<CliCommand, String> {
+    let mut positional = Vec::new();
+    let mut options = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--limit" | "--out" | "--region" => {
+                let value = iter
+                    .next()
+                    .ok_or_else(|| format!("Missing value for {arg}\n\n{USAGE}"))?;
+                options.push((arg.as_str(), value.as_str()));
+            }
+            "--" => positional.extend(iter.by_ref().cloned()),
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {arg}\n\n{USAGE}")),
+            _ => positional.push(arg.clone()),
+        }
+    }
+    let option = |name: &str| {
+        options
+            .iter()
+            .rev()
+            .find(|(option, _)| *option == name)
+            .map(|(_, value)| *value)
+    };
+
+    match command {
+        "search" => {
+            let query = positional.join(" ");
+            if query.trim().is_empty() {
+                return Err(format!("Missing search query\n\n{USAGE}"));
+            }
+            Ok(CliCommand::Search {
+                query,
+                limit: parse_limit(option("--limit"))?,
+            })
+        }
+        "screenshot" => {
+            let out = option("--out").ok_or_else(|| format!("Missing --out\n\n{USAGE}"))?;
+            let region = option("--region").map(parse_region).transpose()?;
+            Ok(CliCommand::Screenshot {
+                out: absolute_path(Path::new(out))?,
+                region,
+            })
+        }
+        "ocr" => match positional.as_slice() {
+            [image] => Ok(CliCommand::Ocr {
+                image: absolute_path(Path::new(image))?,
+            }),
+            _ => Err(format!("Expected a single image path\n\n{USAGE}")),
+        },
+        "translate" => {
+            let mut text = positional.join(" ");
+            if text.is_empty() && !io::stdin().is_terminal() {
+                io::stdin()
+                    .read_to_string(&mut text)
+                    .map_err(|error| format!("Failed to read stdin: {error}"))?;
+            }
+            if text.trim().is_empty() {
+                return Err(format!("Missing text to translate\n\n{USAGE}"));
+            }
+            Ok(CliCommand::Translate { text })
+        }
+        "history" => Ok(CliCommand::History {
+            query: positional.join(" "),
+            limit: parse_limit(option("--limit"))?,
+        }),
+        _ => Ok(CliCommand::Help),
+    }
+}
+
+fn parse_limit(limit: Option<&str>) -> Result<Option<u64>, String> {
+    limit
+        .map(|limit| {
+            limit
+                .parse::<u64>()
+                .map_err(|_| format!("Invalid limit `{limit}`"))
+        })
+        .transpose()
+}
+
+fn parse_region(region: &str) -> Result<Value, String> {
+    let invalid = || format!("Invalid region `{region}`, expected x,y,w,h");
+    let parts = region.split(',').map(str::trim).collect::<Vec<_>>();
+    let [x, y, width, height] = parts.as_slice() else {
+        return Err(invalid());
+    };
+
+    let x = x.parse::<i32>().map_err(|_| invalid())?;
+    let y = y.parse::<i32>().map_err(|_| invalid())?;
+    let width = width.parse::<u32>().map_err(|_| invalid())?;
+    let height = height.parse::<u32>().map_err(|_| invalid())?;
+    if width == 0 || height == 0 {
+        return Err(invalid());
+    }
+
+    Ok(json!({ "x": x, "y": y, "width": width, "height": height }))
+}
+
+// The running instance has its own working directory, so send absolute paths.
+fn absolute_path(path: &Path) -> Result<PathBuf, String> {
+    std::path::absolute(path).map_err(|error| format!("Invalid path {}: {error}", path.display()))
+}
+
+fn execute(command: CliCommand, json: bool) -> Result<(), String> {
+    let (method, params) = match &command {
+        CliCommand::Search { query, limit } => ("find", json!({ "query": query, "limit": limit })),
+        CliCommand::Screenshot { out, region } => {
+            ("screenshot", json!({ "out": out, "region": region }))
+        }
+        CliCommand::Ocr { image } => ("ocr", json!({ "image": image })),
+        CliCommand::Translate { text } => ("translate", json!({ "text": text })),
+        CliCommand::History { query, limit } => (
+            "screenshot_history",
+            json!({ "query": query, "limit": limit }),
+        ),
+        CliCommand::Help => return Ok(()),
+    };
+    let result = ipc::call(method, params)?;
+
+    if json {
+        let output = serde_json::to_string_pretty(&result)
+            .map_err(|error| format!("Failed to format result: {error}"))?;
+        println!("{output}");
+        return Ok(());
+    }
+
+    let field = |value: &Value, name: &str| -> String {
+        value
+            .get(name)
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string()
+    };
+    let items = || result.as_array().cloned().unwrap_or_default();
+    match command {
+        CliCommand::Search { .. } => {
+            for item in items() {
+                println!("{}", field(&item, "file_path"));
+            }
+        }
+        CliCommand::Screenshot { .. } => println!("{}", field(&result, "path")),
+        CliCommand::Ocr { .. } => {
+            for item in items() {
+                println!("{}", field(&item, "text"));
+            }
+        }
+        CliCommand::Translate { .. } => println!("{}", field(&result, "translated")),
+        CliCommand::History { .. } => {
+            for item in items() {
+                println!("{}", field(&item, "path"));
+            }
+        }
+        CliCommand::Help => {}
+    }
+    Ok(())
+}
@@ -24,7 +24,7 @@ pub fn get_all_cfg() -> Config {
 #[tauri::command]
 pub fn set_cfg(k: String, mut v: String, app: AppHandle) -> Result<(), String> {
     let tokens = k.split('_').collect::<Vec<&str>>();
-    let should_rebuild_search_index = k == "search_excluded_dirs";
+    let should_rebuild_search_index = k == "search_excluded_dirs" || k == "search_mount_points";
     let is_global_shortcut = is_global_shortcut_key(&k);
     let _shortcut_update_guard = is_global_shortcut.then(|| {
         GLOBAL_SHORTCUT_UPDATE
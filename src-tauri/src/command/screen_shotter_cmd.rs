@@ -8,6 +8,7 @@ use tauri_plugin_dialog::DialogExt;
 use rotor_common::AppConfig;
 use rotor_platform::sys_util;
 use rotor_runtime::Application;
+use rotor_screenshot::history::HistoryMatch;
 use rotor_screenshot::img_util::{self, TextResult};
 use rotor_screenshot::shotter_record::ShotterConfig;
 
@@ -288,8 +289,34 @@ pub async fn delete_pin_record(id: u32) {
     }
 }
 
+// Command for screenshot history
+
+const SCREENSHOT_HISTORY_PAGE: usize = 50;
+
+#[tauri::command]
+pub async fn search_screenshot_history(query: String, limit: Option<usize>) -> Vec<HistoryMatch> {
+    let history = lock_app().screenshot.history();
+    history.search(&query, limit.unwrap_or(SCREENSHOT_HISTORY_PAGE))
+}
+
 #[tauri::command]
-pub async fn save_img(img_buf: Vec<u8>, app: tauri::AppHandle) -> bool {
+pub async fn delete_screenshot_history(id: u64) {
+    let history = lock_app().screenshot.history();
+    if let Err(error) = history.delete(id) {
+        log::error!("Failed to delete screenshot history {id}: {error}");
+    }
+}
+
+#[tauri::command]
+pub async fn clear_screenshot_history() {
+    let history = lock_app().screenshot.history();
+    if let Err(error) = history.clear() {
+        log::error!("Failed to clear screenshot history: {error}");
+    }
+}
+
+#[tauri::command]
+pub async fn save_img(id: Option<u32>, img_buf: Vec<u8>, app: tauri::AppHandle) -> bool {
     let config = {
         let app_config = AppConfig::lock_global();
 
@@ -343,6 +370,12 @@ pub async fn save_img(img_buf: Vec<u8>, app: tauri::AppHandle) -> bool {
             return false;
         }
 
+        if let Some(id) = id {
+            match image::load_from_memory(&img_buf) {
+                Ok(img) => lock_app().screenshot.record_saved_pin(id, img),
+                Err(error) => log::warn!("Failed to decode saved image for history: {error}"),
+            }
+        }
         return true;
     }
     false
@@ -25,10 +25,14 @@ pub async fn searcher_index_status() -> Result<SearchIndexStatus, String> {
 
 #[tauri::command]
 pub fn open_file(file_path: String) -> Result<(), String> {
-    rotor_platform::file_util::open_file(file_path).map_err(|e| e.to_string())
+    rotor_platform::file_util::open_file(file_path.clone()).map_err(|e| e.to_string())?;
+    Application::lock_global().searcher.record_launch(file_path);
+    Ok(())
 }
 
 #[tauri::command]
 pub fn open_file_as_admin(file_path: String) -> Result<(), String> {
-    rotor_platform::file_util::open_file_as_admin(file_path).map_err(|e| e.to_string())
+    rotor_platform::file_util::open_file_as_admin(file_path.clone()).map_err(|e| e.to_string())?;
+    Application::lock_global().searcher.record_launch(file_path);
+    Ok(())
 }
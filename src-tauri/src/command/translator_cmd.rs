@@ -1,9 +1,14 @@
-use rotor_translator::engine::{TranslateResult, TranslateStreamEvent};
+use rotor_translator::cache::TranslationCache;
+use rotor_translator::engine::{DictionaryResult, TranslateResult, TranslateStreamEvent};
+use rotor_translator::glossary::{self, GlossaryEntry};
+use rotor_translator::history::{ExportFormat, HistoryPage, HistoryQuery, TranslationHistory};
+use rotor_translator::prompt::{self, PromptPreset};
 use tauri::ipc::Channel;
 
 #[tauri::command]
 pub async fn translator_translate(
     text: String,
+    preset: Option<String>,
     on_event: Channel<TranslateStreamEvent>,
 ) -> Result<TranslateResult, String> {
     let text = text.trim().to_string();
@@ -11,9 +16,127 @@ pub async fn translator_translate(
         return Err("Empty text".to_string());
     }
 
-    rotor_translator::engine::translate(&text, |event| {
+    rotor_translator::engine::translate(&text, preset.as_deref(), |event| {
         let _ = on_event.send(event);
     })
     .await
     .map_err(|error| format!("Translate error: {error}"))
 }
+
+// Translates the Markdown, subtitle, HTML or text file at `path` into a copy
+// and returns the path of that copy.
+#[tauri::command]
+pub async fn translator_translate_file(
+    path: String,
+    output: Option<String>,
+) -> Result<String, String> {
+    let output = output.map(std::path::PathBuf::from);
+    rotor_translator::document::translate_file(std::path::Path::new(&path), output.as_deref())
+        .await
+        .map(|output| output.to_string_lossy().into_owned())
+        .map_err(|error| {
+            let message = format!("Failed to translate {path}: {error}");
+            log::error!("{message}");
+            message
+        })
+}
+
+#[tauri::command]
+pub async fn translator_lookup(text: String) -> Result<Option<DictionaryResult>, String> {
+    rotor_translator::engine::lookup(&text)
+        .await
+        .map_err(|error| format!("Dictionary error: {error}"))
+}
+
+#[tauri::command]
+pub async fn translator_health_check() -> Result<(), String> {
+    rotor_translator::engine::health_check()
+        .await
+        .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+pub fn translator_clear_cache() -> Result<(), String> {
+    TranslationCache::global()
+        .clear()
+        .map_err(|error| format!("Failed to clear translation cache: {error}"))
+}
+
+#[tauri::command]
+pub fn translator_get_glossary() -> Vec<GlossaryEntry> {
+    glossary::load()
+}
+
+#[tauri::command]
+pub fn translator_set_glossary(entries: Vec<GlossaryEntry>) -> Result<Vec<GlossaryEntry>, String> {
+    let entries = glossary::save(entries).map_err(|error| {
+        let message = format!("Failed to save translator glossary: {error}");
+        log::error!("{message}");
+        message
+    })?;
+
+    // Cached translations were made with the old terms.
+    if let Err(error) = TranslationCache::global().clear() {
+        log::warn!("Failed to clear translation cache after glossary change: {error}");
+    }
+    Ok(entries)
+}
+
+#[tauri::command]
+pub fn translator_get_prompts() -> Vec<PromptPreset> {
+    prompt::load()
+}
+
+#[tauri::command]
+pub fn translator_set_prompts(presets: Vec<PromptPreset>) -> Result<Vec<PromptPreset>, String> {
+    let presets = prompt::save(presets).map_err(|error| {
+        let message = format!("Failed to save translator prompt presets: {error}");
+        log::error!("{message}");
+        message
+    })?;
+
+    // Cached translations were made with the old templates.
+    if let Err(error) = TranslationCache::global().clear() {
+        log::warn!("Failed to clear translation cache after prompt change: {error}");
+    }
+    Ok(presets)
+}
+
+#[tauri::command]
+pub async fn translator_search_history(query: HistoryQuery) -> HistoryPage {
+    TranslationHistory::global().search(&query)
+}
+
+#[tauri::command]
+pub async fn translator_delete_history(id: u64) -> Result<(), String> {
+    TranslationHistory::global()
+        .delete(id)
+        .map_err(|error| format!("Failed to delete translation history {id}: {error}"))
+}
+
+#[tauri::command]
+pub async fn translator_clear_history() -> Result<(), String> {
+    TranslationHistory::global()
+        .clear()
+        .map_err(|error| format!("Failed to clear translation history: {error}"))
+}
+
+// Exports the entries matching `query` and returns how many were written.
+#[tauri::command]
+pub async fn translator_export_history(
+    path: String,
+    format: ExportFormat,
+    query: Option<HistoryQuery>,
+) -> Result<usize, String> {
+    TranslationHistory::global()
+        .export(
+            &query.unwrap_or_default(),
+            format,
+            std::path::Path::new(&path),
+        )
+        .map_err(|error| {
+            let message = format!("Failed to export translation history: {error}");
+            log::error!("{message}");
+            message
+        })
+}
@@ -1,3 +1,4 @@
+mod cli;
 mod command;
 
 use tauri::Manager;
@@ -65,6 +66,10 @@ fn log_targets() -> Vec<Target> {
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    if let Some(code) = cli::run_from_args() {
+        std::process::exit(code);
+    }
+
     #[cfg(target_os = "windows")]
     if rotor_platform::sys_util::run_as_admin().unwrap_or_else(|e| {
         log::error!("run_as_admin error: {:?}", e);
@@ -89,6 +94,7 @@ pub fn run() {
                 .build(),
         )
         .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_autostart::init(
             MacosLauncher::LaunchAgent,
             None,
@@ -128,6 +134,9 @@ pub fn run() {
             screen_shotter_cmd::update_pin_state,
             screen_shotter_cmd::update_pin_selection,
             screen_shotter_cmd::delete_pin_record,
+            screen_shotter_cmd::search_screenshot_history,
+            screen_shotter_cmd::delete_screenshot_history,
+            screen_shotter_cmd::clear_screenshot_history,
             screen_shotter_cmd::img2text,
             searcher_cmd::searcher_find,
             searcher_cmd::searcher_release,
@@ -135,6 +144,18 @@ pub fn run() {
             searcher_cmd::open_file,
             searcher_cmd::open_file_as_admin,
             translator_cmd::translator_translate,
+            translator_cmd::translator_translate_file,
+            translator_cmd::translator_lookup,
+            translator_cmd::translator_health_check,
+            translator_cmd::translator_clear_cache,
+            translator_cmd::translator_get_glossary,
+            translator_cmd::translator_set_glossary,
+            translator_cmd::translator_get_prompts,
+            translator_cmd::translator_set_prompts,
+            translator_cmd::translator_search_history,
+            translator_cmd::translator_delete_history,
+            translator_cmd::translator_clear_history,
+            translator_cmd::translator_export_history,
         ])
         .setup(move |app| {
             #[cfg(target_os = "macos")]
@@ -29,6 +29,17 @@ export interface TextResult {
   text: string
 }
 
+export interface ScreenshotHistoryItem {
+  id: number
+  timestamp: number
+  monitor_id: number | null
+  monitor_pos: [number, number]
+  monitor_size: [number, number]
+  rect: [number, number, number, number]
+  texts: TextResult[]
+  path: string
+}
+
 export function changeCurrentMask() {
   return invoke<void>('change_current_mask')
 }
@@ -189,10 +200,22 @@ export function deletePinRecord(id: number) {
   return invoke<void>('delete_pin_record', { id })
 }
 
-export function saveImage(imgBuf: ArrayBuffer) {
-  return invoke<boolean>('save_img', { imgBuf })
+export function saveImage(id: number | null, imgBuf: ArrayBuffer) {
+  return invoke<boolean>('save_img', { id, imgBuf })
 }
 
 export function imageToText(imgBuf: ArrayBuffer) {
   return invoke<TextResult[]>('img2text', { imgBuf })
 }
+
+export function searchScreenshotHistory(query: string, limit?: number) {
+  return invoke<ScreenshotHistoryItem[]>('search_screenshot_history', { query, limit })
+}
+
+export function deleteScreenshotHistory(id: number) {
+  return invoke<void>('delete_screenshot_history', { id })
+}
+
+export function clearScreenshotHistory() {
+  return invoke<void>('clear_screenshot_history')
+}
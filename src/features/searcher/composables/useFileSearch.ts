@@ -32,11 +32,13 @@ export function useFileSearch(
   onResize: () => void | Promise<void>,
 ) {
   const searchResults = ref<SearchItem[]>([])
+  const searchError = ref<string | null>(null)
   const selectedIndex = ref(0)
 
   const resetSearch = () => {
     selectedIndex.value = 0
     searchResults.value = []
+    searchError.value = null
   }
 
   const requestSearch = (query = searchQuery.value) => {
@@ -55,13 +57,20 @@ export function useFileSearch(
   }
 
   const handleUpdateResult = async (payload: UpdateResultPayload) => {
-    const [filename, getSearchResults, ifIncrease] = payload
+    const [filename, getSearchResults, ifIncrease, error] = payload
     if (filename !== searchQuery.value) return
 
     if (!ifIncrease) {
       searchResults.value = []
     }
 
+    searchError.value = error
+    if (error) {
+      warn(`Invalid search query: ${error}`)
+      await onResize()
+      return
+    }
+
     const availableSlots = MAX_RETAINED_RESULTS - searchResults.value.length
     if (availableSlots <= 0) return
 
@@ -93,6 +102,7 @@ export function useFileSearch(
 
   return {
     searchResults,
+    searchError,
     selectedIndex,
     resetSearch,
     requestSearch,
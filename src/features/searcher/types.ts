@@ -22,9 +22,13 @@ export interface SearchResultItem {
   rank: number
   icon_data?: string
   alias?: string
+  size?: number | null
+  modified_at?: number | null
+  is_dir: boolean
 }
 
-export type UpdateResultPayload = [string, SearchResultItem[], boolean]
+// [query, results, ifIncrease, error]
+export type UpdateResultPayload = [string, SearchResultItem[], boolean, string | null]
 
 export type SearchIndexState =
   | 'unbuilt'
@@ -1,11 +1,74 @@
 import { Channel, invoke } from '@tauri-apps/api/core'
-import type { TranslateResult, TranslateStreamEvent } from './types'
+import type {
+  DictionaryResult,
+  GlossaryEntry,
+  PromptPreset,
+  TranslateResult,
+  TranslateStreamEvent,
+  TranslationHistoryFormat,
+  TranslationHistoryPage,
+  TranslationHistoryQuery,
+} from './types'
 
+// `preset` picks the LLM prompt preset, the configured one when omitted.
 export function translatorTranslate(
   text: string,
   onStreamEvent: (event: TranslateStreamEvent) => void,
+  preset?: string,
 ) {
   const onEvent = new Channel<TranslateStreamEvent>()
   onEvent.onmessage = onStreamEvent
-  return invoke<TranslateResult>('translator_translate', { text, onEvent })
+  return invoke<TranslateResult>('translator_translate', { text, preset, onEvent })
+}
+
+export function translatorTranslateFile(path: string, output?: string) {
+  return invoke<string>('translator_translate_file', { path, output })
+}
+
+export function translatorLookup(text: string) {
+  return invoke<DictionaryResult | null>('translator_lookup', { text })
+}
+
+export function translatorHealthCheck() {
+  return invoke<void>('translator_health_check')
+}
+
+export function translatorClearCache() {
+  return invoke<void>('translator_clear_cache')
+}
+
+export function translatorGetGlossary() {
+  return invoke<GlossaryEntry[]>('translator_get_glossary')
+}
+
+export function translatorSetGlossary(entries: GlossaryEntry[]) {
+  return invoke<GlossaryEntry[]>('translator_set_glossary', { entries })
+}
+
+export function translatorGetPrompts() {
+  return invoke<PromptPreset[]>('translator_get_prompts')
+}
+
+export function translatorSetPrompts(presets: PromptPreset[]) {
+  return invoke<PromptPreset[]>('translator_set_prompts', { presets })
+}
+
+export function translatorSearchHistory(query: TranslationHistoryQuery = {}) {
+  return invoke<TranslationHistoryPage>('translator_search_history', { query })
+}
+
+export function translatorDeleteHistory(id: number) {
+  return invoke<void>('translator_delete_history', { id })
+}
+
+export function translatorClearHistory() {
+  return invoke<void>('translator_clear_history')
+}
+
+export function translatorExportHistory(
+  path: string,
+  format: TranslationHistoryFormat,
+  query?: TranslationHistoryQuery,
+) {
+  return invoke<number>('translator_export_history', { path, format, query })
 }
@@ -3,6 +3,27 @@ export interface TranslateResult {
   translated: string
   from: string
   to: string
+  engine: string
+}
+
+export interface DictionaryDefinition {
+  definition: string
+  example: string
+}
+
+export interface DictionaryEntry {
+  partOfSpeech: string
+  translations: string[]
+  definitions: DictionaryDefinition[]
+}
+
+export interface DictionaryResult {
+  word: string
+  phonetic: string
+  from: string
+  to: string
+  engine: string
+  entries: DictionaryEntry[]
 }
 
 export type TranslateStreamEvent =
@@ -16,3 +37,45 @@ export type TranslateStreamEvent =
       event: 'delta'
       content: string
     }
+
+export interface GlossaryEntry {
+  term: string
+  // Empty keeps the term untranslated.
+  translation: string
+}
+
+// `{target_language}`, `{text}` and `{glossary}` in the template are filled in
+// for every translation.
+export interface PromptPreset {
+  id: string
+  name: string
+  template: string
+  // Empty uses the target language chosen for the text.
+  targetLang: string
+}
+
+export interface TranslationHistoryEntry {
+  id: number
+  // Seconds since the Unix epoch.
+  timestamp: number
+  engine: string
+  text: string
+  translated: string
+  from: string
+  to: string
+}
+
+export interface TranslationHistoryQuery {
+  query?: string
+  since?: number
+  until?: number
+  offset?: number
+  limit?: number
+}
+
+export interface TranslationHistoryPage {
+  total: number
+  entries: TranslationHistoryEntry[]
+}
+
+export type TranslationHistoryFormat = 'csv' | 'tmx'
@@ -35,6 +35,7 @@ export default {
     browse: 'Browse',
     autoChangeSavePath: 'Auto Change Save Path',
     askSavePath: 'Ask Save Path Each Time',
+    screenshotHistory: 'Keep Searchable Screenshot History',
     zoomDelta: 'Zoom Delta',
     pressShortcut: 'Press Shortcut',
     systemDefault: 'System Default',
@@ -65,10 +66,13 @@ export default {
     openFolderTip: 'Open folder',
     searchIndexing: 'Search Indexing',
     searchExcludedDirs: 'Excluded Directories',
+    searchMountPoints: 'Extra Mount Points',
+    searchFuzzyMatch: 'Fuzzy Matching',
     // Translator
     translator: 'Translator',
     translateSelect: 'Selection Translate',
     translateInput: 'Input Translate',
+    translateReplace: 'Replace with Translation',
     translatorPlaceholder: 'Enter text and press Enter to translate...',
     translating: 'Translating...',
     translatorCopy: 'Copy',
@@ -76,7 +80,10 @@ export default {
     translatorEngine: 'Translate Engine',
     translatorEngineGoogle: 'Google Translate (Free)',
     translatorEngineDeepseek: 'DeepSeek (LLM)',
+    translatorEngineOpenai: 'OpenAI-compatible API',
+    translatorEngineOllama: 'Ollama (Local)',
     translatorEngineCustom: 'Custom API',
+    translatorEngineOffline: 'Offline (local model)',
     translatorDeepseekApiKey: 'DeepSeek API Key',
     translatorDeepseekApiKeyPlaceholder: 'Enter your DeepSeek API Key',
     translatorDeepseekModel: 'Model Name',
@@ -84,10 +91,67 @@ export default {
     translatorCustomUrl: 'API URL',
     translatorCustomUrlPlaceholder: 'Supports {text} {from} {to} {key} placeholders',
     translatorCustomKey: 'API Key',
+    translatorCustomMethod: 'Method',
+    translatorCustomHeaders: 'Headers',
+    translatorCustomBody: 'JSON Body',
+    translatorCustomResult: 'Result Path',
+    translatorCustomResultPlaceholder: 'JSON pointer such as /translatedText, empty to guess',
+    translatorOfflineModelDir: 'Model Directory',
+    translatorOfflineModelDirPlaceholder: 'Folder with an ONNX Marian model, or with sub folders such as en-zh',
+    translatorOfflineModelDirPick: 'Browse',
+    translatorBaseUrl: 'Base URL',
+    translatorApiKeyOptional: 'API Key (optional)',
+    translatorOpenaiModelPlaceholder: 'e.g. gpt-4o-mini',
+    translatorOllamaModelPlaceholder: 'e.g. qwen2.5:7b',
+    translatorHealthCheck: 'Connection',
+    translatorHealthCheckRun: 'Test',
+    translatorHealthCheckOk: 'Engine is ready',
+    translatorCache: 'Cache Translations',
+    translatorClearCache: 'Clear',
+    translatorCacheCleared: 'Translation cache cleared',
+    translatorHistory: 'Translation History',
+    translatorClearHistory: 'Clear',
+    translatorHistoryCleared: 'Translation history cleared',
+    translatorHistoryExported: 'Exported {count} translations',
+    translatorDocument: 'Translate Document',
+    translatorDocumentPick: 'Choose File',
+    translatorDocumentSaved: 'Saved to {path}',
     translatorTargetLang: 'Target Language',
-    translatorTargetAuto: 'Auto (zh ⇄ en)',
+    translatorTargetAuto: 'Auto (by my languages)',
     japanese: 'Japanese',
     korean: 'Korean',
+    german: 'German',
+    french: 'French',
+    spanish: 'Spanish',
+    russian: 'Russian',
+    translatorMyLanguages: 'My Languages',
+    translatorGlossary: 'Glossary',
+    addGlossaryTerm: 'Add Term',
+    deleteGlossaryTerm: 'Delete Term',
+    glossaryTerm: 'Term',
+    glossaryKeepTerm: 'Translation (empty keeps the term)',
+    noGlossaryTerms: 'No glossary terms',
+    translatorPrompts: 'Prompt Presets',
+    addPromptPreset: 'Add Preset',
+    deletePromptPreset: 'Delete Preset',
+    defaultPromptPreset: 'Default Preset',
+    promptPresetName: 'Name',
+    promptPresetTargetLang: 'Target language (optional)',
+    promptPresetTemplate: 'Prompt template',
+    promptVariablesHint:
+      'Used by the DeepSeek, OpenAI-compatible and Ollama engines. Variables: {variables}',
+    noPromptPresets: 'No prompt presets, the built-in prompt is used',
+    translatorMyLanguagesPlaceholder: 'Primary language first',
+    translatorFallbackEngines: 'Fallback Engines',
+    translatorFallbackEnginesPlaceholder: 'Tried in order when the engine fails',
+    translatorRetries: 'Retries',
+    translatorDictionary: 'Dictionary for Single Words',
+    network: 'Network',
+    networkProxy: 'Proxy',
+    networkNoProxy: 'No Proxy For',
+    networkNoProxyPlaceholder: 'localhost, 127.0.0.1, .corp.example.com',
+    networkCaCertificates: 'Extra CA Certificates',
+    networkCaCertificatesPlaceholder: 'PEM file paths, comma separated',
     // Update related translations
     updateAvailable: 'Update Available',
     newVersionAvailable: 'A new version',
@@ -35,6 +35,7 @@ export default {
     browse: '浏览',
     autoChangeSavePath: '自动更改保存路径',
     askSavePath: '每次询问保存路径',
+    screenshotHistory: '保存可搜索的截图历史',
     zoomDelta: '缩放增量',
     pressShortcut: '按下快捷键',
     systemDefault: '系统默认',
@@ -65,10 +66,13 @@ export default {
     openFolderTip: '打开文件所在目录',
     searchIndexing: '搜索索引',
     searchExcludedDirs: '排除目录',
+    searchMountPoints: '额外挂载点',
+    searchFuzzyMatch: '模糊匹配',
     // Translator
     translator: '翻译',
     translateSelect: '划词翻译',
     translateInput: '输入翻译',
+    translateReplace: '翻译并替换',
     translatorPlaceholder: '输入要翻译的内容，回车翻译...',
     translating: '翻译中...',
     translatorCopy: '复制',
@@ -76,7 +80,10 @@ export default {
     translatorEngine: '翻译引擎',
     translatorEngineGoogle: '谷歌翻译（免费）',
     translatorEngineDeepseek: 'DeepSeek（LLM）',
+    translatorEngineOpenai: 'OpenAI 兼容 API',
+    translatorEngineOllama: 'Ollama（本地）',
     translatorEngineCustom: '自定义 API',
+    translatorEngineOffline: '离线（本地模型）',
     translatorDeepseekApiKey: 'DeepSeek API Key',
     translatorDeepseekApiKeyPlaceholder: '请输入 DeepSeek API Key',
     translatorDeepseekModel: '模型名称',
@@ -84,10 +91,66 @@ export default {
     translatorCustomUrl: 'API 地址',
     translatorCustomUrlPlaceholder: '支持 {text} {from} {to} {key} 占位符',
     translatorCustomKey: 'API Key',
+    translatorCustomMethod: '请求方法',
+    translatorCustomHeaders: '请求头',
+    translatorCustomBody: 'JSON 请求体',
+    translatorCustomResult: '结果路径',
+    translatorCustomResultPlaceholder: 'JSON Pointer，例如 /translatedText，留空自动识别',
+    translatorOfflineModelDir: '模型目录',
+    translatorOfflineModelDirPlaceholder: 'ONNX Marian 模型目录，或包含 en-zh 等子目录的目录',
+    translatorOfflineModelDirPick: '选择',
+    translatorBaseUrl: 'Base URL',
+    translatorApiKeyOptional: 'API Key（可选）',
+    translatorOpenaiModelPlaceholder: '例如 gpt-4o-mini',
+    translatorOllamaModelPlaceholder: '例如 qwen2.5:7b',
+    translatorHealthCheck: '连接测试',
+    translatorHealthCheckRun: '测试',
+    translatorHealthCheckOk: '引擎可用',
+    translatorCache: '缓存翻译结果',
+    translatorClearCache: '清空',
+    translatorCacheCleared: '翻译缓存已清空',
+    translatorHistory: '翻译历史',
+    translatorClearHistory: '清空',
+    translatorHistoryCleared: '翻译历史已清空',
+    translatorHistoryExported: '已导出 {count} 条翻译',
+    translatorDocument: '翻译文档',
+    translatorDocumentPick: '选择文件',
+    translatorDocumentSaved: '已保存到 {path}',
     translatorTargetLang: '目标语言',
-    translatorTargetAuto: '自动（中英互译）',
+    translatorTargetAuto: '自动（按我的语言）',
     japanese: '日文',
     korean: '韩文',
+    german: '德文',
+    french: '法文',
+    spanish: '西班牙文',
+    russian: '俄文',
+    translatorMyLanguages: '我的语言',
+    translatorGlossary: '术语表',
+    addGlossaryTerm: '添加术语',
+    deleteGlossaryTerm: '删除术语',
+    glossaryTerm: '术语',
+    glossaryKeepTerm: '译文（留空则保留原文）',
+    noGlossaryTerms: '暂无术语',
+    translatorPrompts: '提示词预设',
+    addPromptPreset: '添加预设',
+    deletePromptPreset: '删除预设',
+    defaultPromptPreset: '默认预设',
+    promptPresetName: '名称',
+    promptPresetTargetLang: '目标语言（可选）',
+    promptPresetTemplate: '提示词模板',
+    promptVariablesHint: '用于 DeepSeek、OpenAI 兼容和 Ollama 引擎。可用变量：{variables}',
+    noPromptPresets: '暂无提示词预设，将使用内置提示词',
+    translatorMyLanguagesPlaceholder: '首选语言放在第一位',
+    translatorFallbackEngines: '备用引擎',
+    translatorFallbackEnginesPlaceholder: '引擎失败时按顺序尝试',
+    translatorRetries: '重试次数',
+    translatorDictionary: '单词词典释义',
+    network: '网络',
+    networkProxy: '代理',
+    networkNoProxy: '不使用代理',
+    networkNoProxyPlaceholder: 'localhost, 127.0.0.1, .corp.example.com',
+    networkCaCertificates: '额外 CA 证书',
+    networkCaCertificatesPlaceholder: 'PEM 文件路径，用逗号分隔',
     // Update related translations
     updateAvailable: '有可用更新',
     newVersionAvailable: '新版本',